@@ -0,0 +1,451 @@
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use hcl::eval::{Context, Evaluate};
+use hcl::{Expression, Value};
+
+use crate::error::Error;
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct BakeTarget {
+    pub context: Option<String>,
+    pub dockerfile: Option<String>,
+    pub args: BTreeMap<String, String>,
+    pub target: Option<String>,
+    pub platforms: Vec<String>,
+}
+
+impl BakeTarget {
+    pub fn dockerfile_path(&self) -> PathBuf {
+        PathBuf::from(self.context.as_deref().unwrap_or("."))
+            .join(self.dockerfile.as_deref().unwrap_or("Dockerfile"))
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Block {
+    kind: String,
+    label: String,
+    attrs: Vec<(String, Expression)>,
+}
+
+/// Resolves `target` from a bake file. Only the attributes dfq reads are
+/// evaluated, so other attributes may use any HCL, including function calls;
+/// the ones that are read may use variables and `${}` interpolation.
+pub fn load_bake(
+    input: &str,
+    target: &str,
+    env: &BTreeMap<String, String>,
+) -> Result<BakeTarget, Error> {
+    let blocks = if input.trim_start().starts_with('{') {
+        json_blocks(input)?
+    } else {
+        hcl_blocks(input)?
+    };
+    let bake = Bake {
+        context: variables(&blocks, env),
+        blocks,
+    };
+    bake.target(target, &mut Vec::new())
+}
+
+struct Bake {
+    blocks: Vec<Block>,
+    context: Context<'static>,
+}
+
+impl Bake {
+    fn blocks<'b>(&'b self, kind: &'b str, label: &'b str) -> impl Iterator<Item = &'b Block> {
+        self.blocks
+            .iter()
+            .filter(move |block| block.kind == kind && block.label == label)
+    }
+
+    fn target(&self, name: &str, stack: &mut Vec<String>) -> Result<BakeTarget, Error> {
+        if stack.iter().any(|seen| seen == name) {
+            return Err(Error::config(format!(
+                "bake target '{name}' refers to itself"
+            )));
+        }
+
+        let blocks: Vec<&Block> = self.blocks("target", name).collect();
+        if blocks.is_empty() {
+            return self.group(name, stack);
+        }
+
+        stack.push(name.to_string());
+        let mut out = BakeTarget::default();
+        for block in &blocks {
+            if let Some(parents) = attr(block, "inherits") {
+                for parent in self.strings(parents, "inherits")? {
+                    merge(&mut out, self.target(&parent, stack)?);
+                }
+            }
+        }
+        for block in blocks {
+            for (key, expr) in &block.attrs {
+                match key.as_str() {
+                    "context" => out.context = self.optional_string(expr, key)?,
+                    "dockerfile" => out.dockerfile = self.optional_string(expr, key)?,
+                    "target" => out.target = self.optional_string(expr, key)?,
+                    "platforms" => out.platforms = self.strings(expr, key)?,
+                    "args" => {
+                        let Value::Object(entries) = self.evaluate(expr, key)? else {
+                            return Err(Error::config("bake attribute 'args' must be a map"));
+                        };
+                        for (arg, value) in entries {
+                            match scalar(value, &arg)? {
+                                Some(value) => out.args.insert(arg, value),
+                                None => out.args.remove(&arg),
+                            };
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+        stack.pop();
+        Ok(out)
+    }
+
+    fn group(&self, name: &str, stack: &mut Vec<String>) -> Result<BakeTarget, Error> {
+        let mut members = Vec::new();
+        for block in self.blocks("group", name) {
+            if let Some(targets) = attr(block, "targets") {
+                members.extend(self.strings(targets, "targets")?);
+            }
+        }
+        match members.as_slice() {
+            [member] => {
+                stack.push(name.to_string());
+                let target = self.target(member, stack)?;
+                stack.pop();
+                Ok(target)
+            }
+            [] => {
+                let known: Vec<&str> = self
+                    .blocks
+                    .iter()
+                    .filter(|block| block.kind == "target")
+                    .map(|block| block.label.as_str())
+                    .collect();
+                Err(Error::config(format!(
+                    "unknown bake target '{name}' (available: {})",
+                    known.join(", ")
+                )))
+            }
+            _ => Err(Error::config(format!(
+                "bake group '{name}' has {} targets ({}); select one with --bake-target",
+                members.len(),
+                members.join(", ")
+            ))),
+        }
+    }
+
+    fn evaluate(&self, expr: &Expression, key: &str) -> Result<Value, Error> {
+        expr.evaluate(&self.context)
+            .map_err(|error| Error::config(format!("bake attribute '{key}': {error}")))
+    }
+
+    fn optional_string(&self, expr: &Expression, key: &str) -> Result<Option<String>, Error> {
+        scalar(self.evaluate(expr, key)?, key)
+    }
+
+    fn strings(&self, expr: &Expression, key: &str) -> Result<Vec<String>, Error> {
+        let Value::Array(items) = self.evaluate(expr, key)? else {
+            return Err(Error::config(format!(
+                "bake attribute '{key}' must be a list"
+            )));
+        };
+        let mut out = Vec::with_capacity(items.len());
+        for item in items {
+            out.extend(scalar(item, key)?);
+        }
+        Ok(out)
+    }
+}
+
+/// Declares every bake variable that can be evaluated, taking the value from
+/// the environment when it is set there. Defaults may refer to variables
+/// declared later in the file, so evaluation repeats until nothing changes;
+/// a variable that never resolves stays undeclared and is reported where it
+/// is used.
+fn variables(blocks: &[Block], env: &BTreeMap<String, String>) -> Context<'static> {
+    let mut context = Context::new();
+    let mut pending: Vec<&Block> = Vec::new();
+    for block in blocks.iter().filter(|block| block.kind == "variable") {
+        match env.get(&block.label) {
+            Some(value) => context.declare_var(block.label.as_str(), value.as_str()),
+            None => pending.push(block),
+        }
+    }
+
+    loop {
+        let before = pending.len();
+        pending.retain(|block| {
+            let value = match attr(block, "default") {
+                Some(expr) => match expr.evaluate(&context) {
+                    Ok(value) => value,
+                    Err(_) => return true,
+                },
+                None => Value::String(String::new()),
+            };
+            context.declare_var(block.label.as_str(), value);
+            false
+        });
+        if pending.len() == before {
+            return context;
+        }
+    }
+}
+
+fn scalar(value: Value, key: &str) -> Result<Option<String>, Error> {
+    match value {
+        Value::Null => Ok(None),
+        Value::Bool(value) => Ok(Some(value.to_string())),
+        Value::Number(value) => Ok(Some(value.to_string())),
+        Value::String(value) => Ok(Some(value)),
+        Value::Array(_) | Value::Object(_) => Err(Error::config(format!(
+            "bake attribute '{key}' must be a string"
+        ))),
+    }
+}
+
+fn attr<'b>(block: &'b Block, key: &str) -> Option<&'b Expression> {
+    block
+        .attrs
+        .iter()
+        .rev()
+        .find(|(name, _)| name == key)
+        .map(|(_, expr)| expr)
+}
+
+fn merge(out: &mut BakeTarget, parent: BakeTarget) {
+    if parent.context.is_some() {
+        out.context = parent.context;
+    }
+    if parent.dockerfile.is_some() {
+        out.dockerfile = parent.dockerfile;
+    }
+    if parent.target.is_some() {
+        out.target = parent.target;
+    }
+    if !parent.platforms.is_empty() {
+        out.platforms = parent.platforms;
+    }
+    out.args.extend(parent.args);
+}
+
+fn hcl_blocks(input: &str) -> Result<Vec<Block>, Error> {
+    let body = hcl::parse(input).map_err(|error| match error {
+        hcl::Error::Parse(error) => Error::config(format!(
+            "bake file line {}: {}",
+            error.location().line(),
+            error.message()
+        )),
+        other => Error::config(format!("bake file: {other}")),
+    })?;
+    Ok(body
+        .into_blocks()
+        .filter_map(|block| {
+            let label = block.labels.first()?.as_str().to_string();
+            Some(Block {
+                kind: block.identifier.to_string(),
+                label,
+                attrs: block
+                    .body
+                    .into_attributes()
+                    .map(|attr| (attr.key.to_string(), attr.expr))
+                    .collect(),
+            })
+        })
+        .collect())
+}
+
+fn json_blocks(input: &str) -> Result<Vec<Block>, Error> {
+    let root: serde_json::Value = serde_json::from_str(input)
+        .map_err(|error| Error::config(format!("bake file line {}: {error}", error.line())))?;
+    let serde_json::Value::Object(sections) = root else {
+        return Err(Error::config("bake JSON must be an object"));
+    };
+    let mut blocks = Vec::new();
+    for (kind, section) in sections {
+        if !matches!(kind.as_str(), "variable" | "target" | "group") {
+            continue;
+        }
+        let serde_json::Value::Object(entries) = section else {
+            return Err(Error::config(format!("bake '{kind}' must be an object")));
+        };
+        for (label, body) in entries {
+            let serde_json::Value::Object(attrs) = body else {
+                return Err(Error::config(format!(
+                    "bake {kind} '{label}' must be an object"
+                )));
+            };
+            blocks.push(Block {
+                kind: kind.clone(),
+                label,
+                attrs: attrs
+                    .into_iter()
+                    .map(|(key, value)| (key, json_expr(value)))
+                    .collect(),
+            });
+        }
+    }
+    Ok(blocks)
+}
+
+/// Strings in bake JSON are templates, as in HCL's JSON syntax.
+fn json_expr(value: serde_json::Value) -> Expression {
+    match value {
+        serde_json::Value::Null => Expression::Null,
+        serde_json::Value::Bool(value) => Expression::Bool(value),
+        serde_json::Value::Number(number) => match number.as_i64() {
+            Some(value) => Expression::Number(value.into()),
+            None => number
+                .as_f64()
+                .and_then(hcl::Number::from_f64)
+                .map_or(Expression::Null, Expression::Number),
+        },
+        serde_json::Value::String(value) => {
+            Expression::TemplateExpr(Box::new(hcl::TemplateExpr::QuotedString(value)))
+        }
+        serde_json::Value::Array(items) => {
+            Expression::Array(items.into_iter().map(json_expr).collect())
+        }
+        serde_json::Value::Object(entries) => Expression::Object(
+            entries
+                .into_iter()
+                .map(|(key, value)| (hcl::ObjectKey::from(key), json_expr(value)))
+                .collect(),
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use crate::bake::load_bake;
+    use crate::error::Error;
+
+    const HCL: &str = r#"
+        # shared settings
+        variable "VERSION" {
+          default = "1.2.3"
+        }
+        variable "IMAGE" {
+          default = "alpine:${VERSION}"
+        }
+
+        group "default" {
+          targets = ["app"]
+        }
+
+        target "base" {
+          context = "services/app"
+          platforms = ["linux/amd64", "linux/arm64"]
+          args = {
+            BASE_IMAGE = IMAGE
+            DEBUG = false
+          }
+        }
+
+        target "app" {
+          inherits = ["base"]
+          dockerfile = "build/Dockerfile" // relative to context
+          target = "runtime"
+          args = {
+            VERSION = "${VERSION}"
+            DEBUG = null
+            LITERAL = "$${VERSION}"
+          }
+        }
+
+        function "tag" {
+          params = [name]
+          result = "example/${name}:${VERSION}"
+        }
+
+        target "other" {
+          tags = [tag("other"), upper("latest")]
+        }
+    "#;
+
+    #[test]
+    fn resolves_hcl_targets_with_variables_and_inheritance() {
+        let target = load_bake(HCL, "default", &BTreeMap::new()).expect("bake should load");
+        assert_eq!(target.context.as_deref(), Some("services/app"));
+        assert_eq!(
+            target.dockerfile_path().to_str(),
+            Some("services/app/build/Dockerfile")
+        );
+        assert_eq!(target.target.as_deref(), Some("runtime"));
+        assert_eq!(target.platforms, vec!["linux/amd64", "linux/arm64"]);
+        assert_eq!(
+            target.args,
+            BTreeMap::from([
+                ("BASE_IMAGE".to_string(), "alpine:1.2.3".to_string()),
+                ("LITERAL".to_string(), "${VERSION}".to_string()),
+                ("VERSION".to_string(), "1.2.3".to_string()),
+            ])
+        );
+    }
+
+    #[test]
+    fn environment_overrides_declared_variables() {
+        let env = BTreeMap::from([("VERSION".to_string(), "2.0".to_string())]);
+        let target = load_bake(HCL, "app", &env).expect("bake should load");
+        assert_eq!(target.args["BASE_IMAGE"], "alpine:2.0");
+        assert_eq!(target.args["VERSION"], "2.0");
+
+        let target = load_bake(HCL, "other", &env).expect("bake should load");
+        assert_eq!(target.dockerfile_path().to_str(), Some("./Dockerfile"));
+    }
+
+    #[test]
+    fn resolves_json_definitions() {
+        let json = r#"{
+          "variable": {"TAG": {"default": "edge"}},
+          "target": {
+            "app": {
+              "dockerfile": "app.Dockerfile",
+              "args": {"TAG": "${TAG}", "JOBS": 4},
+              "platforms": ["linux/amd64"]
+            }
+          }
+        }"#;
+        let target = load_bake(json, "app", &BTreeMap::new()).expect("bake should load");
+        assert_eq!(target.dockerfile.as_deref(), Some("app.Dockerfile"));
+        assert_eq!(target.args["TAG"], "edge");
+        assert_eq!(target.args["JOBS"], "4");
+        assert_eq!(target.platforms, vec!["linux/amd64"]);
+    }
+
+    #[test]
+    fn reports_unknown_targets_and_syntax_errors() {
+        let error = load_bake(HCL, "nope", &BTreeMap::new()).expect_err("target is unknown");
+        assert_eq!(
+            error.to_string(),
+            "config error: unknown bake target 'nope' (available: base, app, other)"
+        );
+
+        let error = load_bake("target \"app\" {\n  args = [\n", "app", &BTreeMap::new())
+            .expect_err("bake file is malformed");
+        assert!(matches!(error, Error::Config { .. }));
+        assert!(error.to_string().contains("line 2"), "{error}");
+
+        let error = load_bake(
+            "target \"app\" {\n  dockerfile = lower(\"Dockerfile\")\n}\n",
+            "app",
+            &BTreeMap::new(),
+        )
+        .expect_err("functions are not evaluated");
+        assert!(
+            error
+                .to_string()
+                .starts_with("config error: bake attribute 'dockerfile': "),
+            "{error}"
+        );
+    }
+}
@@ -0,0 +1,396 @@
+use std::collections::BTreeSet;
+
+use crate::eval::{
+    ARG_FIELDS, COMMENT_FIELDS, COMMENTS_COLLECTION_FIELDS, CONTEXT_FIELDS, COPY_COLLECTION_FIELDS,
+    COPY_FIELDS, DIRECTIVE_FIELDS, INSTRUCTION_COLLECTION_FIELDS, INSTRUCTION_FIELDS,
+    INSTRUCTIONS_FIELDS, PACKAGE_FIELDS, PACKAGES_COLLECTION_FIELDS, PARENT_FIELDS, ROOT_FIELDS,
+    RUN_COLLECTION_FIELDS, RUN_FIELDS, STAGE_FIELDS, STAGES_FIELDS,
+};
+use crate::model::{DockerfileModel, Stage};
+use crate::packages::parse_packages;
+use crate::query::{Index, Segment, parse_query};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Completion {
+    pub start: usize,
+    pub candidates: Vec<String>,
+}
+
+pub fn complete_query(model: &DockerfileModel, input: &str) -> Completion {
+    let path_start = path_start(input);
+    let path = &input[path_start..];
+    let word_start = path_start + last_segment_start(path);
+    let word = &input[word_start..];
+
+    let prefix = input[path_start..word_start].trim_end_matches('.');
+    let context = join_path(&absolute_prefix(input, path_start), prefix);
+    let segments = if context.is_empty() {
+        Vec::new()
+    } else {
+        match parse_query(&context) {
+            Ok(query) => query.segments,
+            Err(_) => {
+                return Completion {
+                    start: word_start,
+                    candidates: Vec::new(),
+                };
+            }
+        }
+    };
+
+    let mut candidates = fields_after(model, &segments);
+    if word.contains('[') {
+        candidates.extend(indexed_forms(model, &segments));
+    }
+
+    let mut seen = BTreeSet::new();
+    candidates.retain(|candidate| candidate.starts_with(word) && seen.insert(candidate.clone()));
+    Completion {
+        start: word_start,
+        candidates,
+    }
+}
+
+fn fields_after(model: &DockerfileModel, segments: &[Segment]) -> Vec<String> {
+    match segments {
+        [] => names(ROOT_FIELDS),
+        [Segment::Ident(ident)] if ident == "ARG" => model.global_args.keys().cloned().collect(),
+        [Segment::Ident(ident), Segment::Ident(_)] if ident == "ARG" => names(ARG_FIELDS),
+        [Segment::Indexed { ident, .. }] if ident == "FROM" => names(PARENT_FIELDS),
+        [Segment::Ident(ident)] if ident == "FROM" || ident == "STAGE" => names(STAGES_FIELDS),
+        [
+            Segment::Indexed {
+                ident,
+                index: Index::Position(_),
+            },
+        ] if ident == "RUN" => names(RUN_FIELDS),
+        [first, rest @ ..] if is_run_collection(first) && rest.iter().all(is_grep) => {
+            names(RUN_COLLECTION_FIELDS)
+        }
+        [
+            Segment::Indexed {
+                ident,
+                index: Index::Position(_),
+            },
+        ] if ident == "COPY" => names(COPY_FIELDS),
+        [Segment::Ident(ident)]
+        | [
+            Segment::Indexed {
+                ident,
+                index: Index::Wildcard,
+            },
+        ] if ident == "COPY" => names(COPY_COLLECTION_FIELDS),
+        [
+            Segment::Indexed {
+                ident,
+                index: Index::Position(_),
+            },
+        ] if ident == "PACKAGES" => names(PACKAGE_FIELDS),
+        [Segment::Ident(ident)]
+        | [
+            Segment::Indexed {
+                ident,
+                index: Index::Wildcard,
+            },
+        ] if ident == "PACKAGES" => names(PACKAGES_COLLECTION_FIELDS),
+        [
+            Segment::Indexed {
+                ident,
+                index: Index::Position(_),
+            },
+        ] if ident == "INSTRUCTION" => names(INSTRUCTION_FIELDS),
+        [Segment::Ident(ident)]
+        | [
+            Segment::Indexed {
+                ident,
+                index: Index::Wildcard,
+            },
+        ] if ident == "INSTRUCTION" => names(INSTRUCTION_COLLECTION_FIELDS),
+        [
+            Segment::Indexed {
+                ident,
+                index: Index::Position(_),
+            },
+        ] if ident == "COMMENTS" => names(COMMENT_FIELDS),
+        [Segment::Ident(ident)]
+        | [
+            Segment::Indexed {
+                ident,
+                index: Index::Wildcard,
+            },
+        ] if ident == "COMMENTS" => names(COMMENTS_COLLECTION_FIELDS),
+        [Segment::Ident(ident)] if ident == "DIRECTIVES" => names(DIRECTIVE_FIELDS),
+        [Segment::Ident(ident)] if ident == "CONTEXT" => names(CONTEXT_FIELDS),
+        [Segment::Indexed { ident, index }, rest @ ..] if ident == "STAGE" => {
+            stage_fields_after(model, &selected_stages(model, index), rest)
+        }
+        _ => Vec::new(),
+    }
+}
+
+fn stage_fields_after(model: &DockerfileModel, stages: &[&Stage], rest: &[Segment]) -> Vec<String> {
+    match rest {
+        [] => names(STAGE_FIELDS),
+        [Segment::Ident(field)] if field == "ARG" => stages
+            .iter()
+            .flat_map(|stage| stage.arg_defaults.keys())
+            .chain(model.global_args.keys())
+            .cloned()
+            .collect(),
+        [Segment::Ident(field), Segment::Ident(_)] if field == "ARG" => names(ARG_FIELDS),
+        [Segment::Ident(field)] if field == "PARENT" => names(PARENT_FIELDS),
+        [Segment::Ident(field)] if field == "INSTRUCTIONS" => names(INSTRUCTIONS_FIELDS),
+        [Segment::Indexed { ident, .. }] if ident == "INSTRUCTIONS" => names(INSTRUCTION_FIELDS),
+        _ => Vec::new(),
+    }
+}
+
+fn indexed_forms(model: &DockerfileModel, segments: &[Segment]) -> Vec<String> {
+    match segments {
+        [] => {
+            let count = |keywords: &[&str]| {
+                model
+                    .stages
+                    .iter()
+                    .flat_map(|stage| &stage.instructions)
+                    .filter(|instruction| keywords.contains(&instruction.keyword.as_str()))
+                    .count()
+            };
+            let mut out = positions("STAGE", model.stages.len());
+            out.extend(
+                model
+                    .stages
+                    .iter()
+                    .filter_map(|stage| stage.name.as_ref())
+                    .map(|name| format!("STAGE[\"{name}\"]")),
+            );
+            out.extend(positions("FROM", model.stages.len()));
+            out.extend(positions("RUN", count(&["RUN"])));
+            out.extend(positions("COPY", count(&["COPY", "ADD"])));
+            out.extend(positions(
+                "PACKAGES",
+                model
+                    .stages
+                    .iter()
+                    .flat_map(|stage| &stage.instructions)
+                    .filter(|instruction| instruction.keyword == "RUN")
+                    .map(|instruction| parse_packages(&instruction.raw).len())
+                    .sum(),
+            ));
+            out.extend(positions("INSTRUCTION", model.raw_instructions.len()));
+            out.extend(positions("COMMENTS", model.comments.len()));
+            out
+        }
+        [Segment::Indexed { ident, index }] if ident == "STAGE" => {
+            instruction_positions(&selected_stages(model, index))
+        }
+        _ => Vec::new(),
+    }
+}
+
+fn instruction_positions(stages: &[&Stage]) -> Vec<String> {
+    let count = stages
+        .iter()
+        .map(|stage| stage.instructions.len())
+        .max()
+        .unwrap_or(0);
+    positions("INSTRUCTIONS", count)
+        .into_iter()
+        .filter(|form| form != "INSTRUCTIONS[*]")
+        .collect()
+}
+
+fn selected_stages<'m>(model: &'m DockerfileModel, index: &Index) -> Vec<&'m Stage> {
+    match index {
+        Index::Position(position) => model.stages.get(*position).into_iter().collect(),
+        Index::Wildcard => model.stages.iter().collect(),
+        Index::Key(name) => model
+            .stages
+            .iter()
+            .filter(|stage| stage.name.as_deref() == Some(name.as_str()))
+            .collect(),
+    }
+}
+
+fn is_run_collection(segment: &Segment) -> bool {
+    match segment {
+        Segment::Ident(ident) => ident == "RUN",
+        Segment::Indexed {
+            ident,
+            index: Index::Wildcard,
+        } => ident == "RUN",
+        _ => false,
+    }
+}
+
+fn is_grep(segment: &Segment) -> bool {
+    matches!(segment, Segment::Function { ident, .. } if ident == "GREP")
+}
+
+fn names(fields: &[&str]) -> Vec<String> {
+    fields.iter().map(|field| field.to_string()).collect()
+}
+
+fn positions(ident: &str, count: usize) -> Vec<String> {
+    let mut out = vec![format!("{ident}[*]")];
+    out.extend((0..count).map(|index| format!("{ident}[{index}]")));
+    out
+}
+
+fn join_path(base: &str, path: &str) -> String {
+    match (base.is_empty(), path.is_empty()) {
+        (true, _) => path.to_string(),
+        (_, true) => base.to_string(),
+        _ => format!("{base}.{path}"),
+    }
+}
+
+fn path_start(input: &str) -> usize {
+    let mut start = 0;
+    let mut quote = None;
+    let mut previous = None;
+    for (index, ch) in input.char_indices() {
+        match quote {
+            Some(open) if ch == open => quote = None,
+            Some(_) => {}
+            None if ch == '"' || ch == '\'' => quote = Some(ch),
+            None if ch.is_whitespace() || "(,{:/".contains(ch) => start = index + 1,
+            None if ch == '[' && !previous.is_some_and(is_ident_char) => start = index + 1,
+            None => {}
+        }
+        previous = Some(ch);
+    }
+    start
+}
+
+fn last_segment_start(path: &str) -> usize {
+    let mut start = 0;
+    let mut quote = None;
+    let mut depth = 0usize;
+    for (index, ch) in path.char_indices() {
+        match quote {
+            Some(open) if ch == open => quote = None,
+            Some(_) => {}
+            None if ch == '"' || ch == '\'' => quote = Some(ch),
+            None if ch == '[' || ch == '(' => depth += 1,
+            None if ch == ']' || ch == ')' => depth = depth.saturating_sub(1),
+            None if ch == '.' && depth == 0 => start = index + 1,
+            None => {}
+        }
+    }
+    start
+}
+
+fn absolute_prefix(input: &str, end: usize) -> String {
+    let mut openers: Vec<(usize, char)> = Vec::new();
+    let mut quote = None;
+    let mut previous = None;
+    for (index, ch) in input[..end].char_indices() {
+        match quote {
+            Some(open) if ch == open => quote = None,
+            Some(_) => {}
+            None if ch == '"' || ch == '\'' => quote = Some(ch),
+            None if ch == '{' || ch == '(' => openers.push((index, ch)),
+            None if ch == '[' => {
+                let kind = if previous.is_some_and(is_ident_char) {
+                    'i'
+                } else {
+                    '['
+                };
+                openers.push((index, kind));
+            }
+            None if ch == '}' || ch == ')' || ch == ']' => {
+                openers.pop();
+            }
+            None => {}
+        }
+        previous = Some(ch);
+    }
+
+    let Some(&(open, kind)) = openers.iter().rev().find(|(_, kind)| *kind != 'i') else {
+        return String::new();
+    };
+    let mut head = &input[..open];
+    if kind == '(' {
+        head = head.trim_end_matches(is_ident_char);
+    }
+    let head = head.trim_end().trim_end_matches('.');
+    let start = path_start(head);
+    join_path(&absolute_prefix(input, start), &head[start..])
+}
+
+fn is_ident_char(ch: char) -> bool {
+    ch.is_ascii_alphanumeric() || ch == '_'
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::complete::complete_query;
+    use crate::parser::parse_dockerfile;
+
+    const DOCKERFILE: &str = "ARG VERSION=1\n\
+                              FROM alpine AS builder\n\
+                              ARG TARGET=x\n\
+                              RUN make\n\
+                              FROM scratch AS runtime\n";
+
+    fn candidates(input: &str) -> (usize, Vec<String>) {
+        let model = parse_dockerfile(DOCKERFILE).expect("dockerfile should parse");
+        let completion = complete_query(&model, input);
+        (completion.start, completion.candidates)
+    }
+
+    #[test]
+    fn completes_namespaces_and_fields() {
+        assert_eq!(candidates("ST"), (0, vec!["STAGE".to_string()]));
+        assert_eq!(candidates("ARG."), (4, vec!["VERSION".to_string()]));
+        assert_eq!(
+            candidates("ARG.VERSION.D"),
+            (12, vec!["DEFAULT".to_string()])
+        );
+        assert_eq!(candidates("RUN[0].COM"), (7, vec!["COMMAND".to_string()]));
+        assert_eq!(candidates("STAGE.C"), (6, vec!["COUNT".to_string()]));
+        assert_eq!(
+            candidates("COPY[*].SOURCES_"),
+            (8, vec!["SOURCES_MATCHED".to_string()])
+        );
+        assert_eq!(
+            candidates("STAGE[0].ARG."),
+            (13, vec!["TARGET".to_string(), "VERSION".to_string()])
+        );
+        assert_eq!(
+            candidates("INSTRUCTION[1].CO"),
+            (15, vec!["COMMENT".to_string()])
+        );
+        assert_eq!(candidates("DIRECTIVES.S"), (11, vec!["SYNTAX".to_string()]));
+        assert_eq!(
+            candidates("PACKAGES[*].V"),
+            (12, vec!["VERSION".to_string()])
+        );
+    }
+
+    #[test]
+    fn completes_stage_selectors() {
+        assert_eq!(
+            candidates("STAGE[\"r"),
+            (0, vec!["STAGE[\"runtime\"]".to_string()])
+        );
+        assert_eq!(
+            candidates("STAGE[\"builder\"].INSTRUCTIONS[").1,
+            vec!["INSTRUCTIONS[0]".to_string(), "INSTRUCTIONS[1]".to_string()]
+        );
+    }
+
+    #[test]
+    fn completes_inside_projections_and_alternatives() {
+        assert_eq!(
+            candidates("STAGE[0].{name: NAME, base: PARENT.RES"),
+            (35, vec!["RESOLVED".to_string()])
+        );
+        assert_eq!(
+            candidates("ARG.NOPE // ARG.V"),
+            (16, vec!["VERSION".to_string()])
+        );
+        assert_eq!(candidates("EXISTS(AR").1, vec!["ARG".to_string()]);
+    }
+}
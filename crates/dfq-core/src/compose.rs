@@ -0,0 +1,306 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use crate::error::Error;
+use crate::yaml::{Yaml, parse_yaml};
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct ComposeBuild {
+    pub context: String,
+    pub dockerfile: Option<String>,
+    pub args: BTreeMap<String, String>,
+    pub target: Option<String>,
+    pub platforms: Vec<String>,
+}
+
+impl ComposeBuild {
+    pub fn dockerfile_path(&self, project_dir: &Path) -> PathBuf {
+        project_dir
+            .join(&self.context)
+            .join(self.dockerfile.as_deref().unwrap_or("Dockerfile"))
+    }
+}
+
+pub fn load_compose(
+    input: &str,
+    service: Option<&str>,
+    env: &BTreeMap<String, String>,
+) -> Result<ComposeBuild, Error> {
+    let root = parse_yaml(input)?;
+    let Some(Yaml::Map(services)) = root.get("services") else {
+        return Err(Error::config("compose file has no services"));
+    };
+
+    let (name, definition) = match service {
+        Some(name) => {
+            let definition = services
+                .iter()
+                .rev()
+                .find(|(candidate, _)| candidate == name)
+                .map(|(_, definition)| definition)
+                .ok_or_else(|| {
+                    let known: Vec<&str> = services.iter().map(|(name, _)| name.as_str()).collect();
+                    Error::config(format!(
+                        "unknown compose service '{name}' (available: {})",
+                        known.join(", ")
+                    ))
+                })?;
+            (name, definition)
+        }
+        None => {
+            let built: Vec<&(String, Yaml)> = services
+                .iter()
+                .filter(|(_, definition)| definition.get("build").is_some())
+                .collect();
+            let [(name, definition)] = built.as_slice() else {
+                let names: Vec<&str> = built.iter().map(|(name, _)| name.as_str()).collect();
+                return Err(Error::config(format!(
+                    "compose file has {} services with a build section ({}); select one with --service",
+                    built.len(),
+                    names.join(", ")
+                )));
+            };
+            (name.as_str(), definition)
+        }
+    };
+
+    let build = match definition.get("build") {
+        Some(Yaml::Scalar(context)) => {
+            return Ok(ComposeBuild {
+                context: interpolate(context, env)?,
+                ..ComposeBuild::default()
+            });
+        }
+        Some(build @ Yaml::Map(_)) => build,
+        _ => {
+            return Err(Error::config(format!(
+                "compose service '{name}' has no build section"
+            )));
+        }
+    };
+    if build.get("dockerfile_inline").is_some() {
+        return Err(Error::config(format!(
+            "compose service '{name}' uses dockerfile_inline, which is not supported"
+        )));
+    }
+
+    let string = |key: &str| match build.get(key) {
+        Some(Yaml::Scalar(value)) => interpolate(value, env).map(Some),
+        Some(Yaml::Null) | None => Ok(None),
+        Some(_) => Err(Error::config(format!("build.{key} must be a string"))),
+    };
+    let mut out = ComposeBuild {
+        context: string("context")?.unwrap_or_else(|| ".".to_string()),
+        dockerfile: string("dockerfile")?,
+        target: string("target")?,
+        ..ComposeBuild::default()
+    };
+
+    match build.get("platforms") {
+        Some(Yaml::Seq(items)) => {
+            for item in items {
+                let Yaml::Scalar(platform) = item else {
+                    return Err(Error::config("build.platforms must be a list of strings"));
+                };
+                out.platforms.push(interpolate(platform, env)?);
+            }
+        }
+        None | Some(Yaml::Null) => {}
+        Some(_) => return Err(Error::config("build.platforms must be a list")),
+    }
+
+    match build.get("args") {
+        Some(Yaml::Map(entries)) => {
+            for (key, value) in entries {
+                let value = match value {
+                    Yaml::Scalar(value) => Some(interpolate(value, env)?),
+                    Yaml::Null => env.get(key).cloned(),
+                    _ => return Err(Error::config(format!("build.args.{key} must be a string"))),
+                };
+                if let Some(value) = value {
+                    out.args.insert(key.clone(), value);
+                }
+            }
+        }
+        Some(Yaml::Seq(items)) => {
+            for item in items {
+                let Yaml::Scalar(item) = item else {
+                    return Err(Error::config("build.args entries must be strings"));
+                };
+                let item = interpolate(item, env)?;
+                match item.split_once('=') {
+                    Some((key, value)) => {
+                        out.args.insert(key.to_string(), value.to_string());
+                    }
+                    None => {
+                        if let Some(value) = env.get(&item) {
+                            out.args.insert(item, value.clone());
+                        }
+                    }
+                }
+            }
+        }
+        None | Some(Yaml::Null) => {}
+        Some(_) => return Err(Error::config("build.args must be a map or a list")),
+    }
+    Ok(out)
+}
+
+fn interpolate(input: &str, env: &BTreeMap<String, String>) -> Result<String, Error> {
+    let mut out = String::with_capacity(input.len());
+    let mut rest = input;
+    while let Some(index) = rest.find('$') {
+        out.push_str(&rest[..index]);
+        let after = &rest[index + 1..];
+        if let Some(tail) = after.strip_prefix('$') {
+            out.push('$');
+            rest = tail;
+        } else if let Some(body) = after.strip_prefix('{') {
+            let end = closing_brace(body)
+                .ok_or_else(|| Error::config(format!("unterminated interpolation in '{input}'")))?;
+            out.push_str(&expand(&body[..end], env)?);
+            rest = &body[end + 1..];
+        } else {
+            let len = name_len(after);
+            if len == 0 {
+                out.push('$');
+            } else {
+                out.push_str(env.get(&after[..len]).map_or("", String::as_str));
+            }
+            rest = &after[len..];
+        }
+    }
+    out.push_str(rest);
+    Ok(out)
+}
+
+fn expand(body: &str, env: &BTreeMap<String, String>) -> Result<String, Error> {
+    let (name, modifier) = body.split_at(name_len(body));
+    if name.is_empty() {
+        return Err(Error::config(format!(
+            "invalid interpolation '${{{body}}}'"
+        )));
+    }
+    let value = env.get(name);
+    let (colon, modifier) = match modifier.strip_prefix(':') {
+        Some(modifier) => (true, modifier),
+        None => (false, modifier),
+    };
+    let set = value.is_some_and(|value| !colon || !value.is_empty());
+    let mut chars = modifier.chars();
+    match chars.next() {
+        None if !colon => Ok(value.cloned().unwrap_or_default()),
+        Some('-') if set => Ok(value.cloned().unwrap_or_default()),
+        Some('-') => interpolate(chars.as_str(), env),
+        Some('+') if set => interpolate(chars.as_str(), env),
+        Some('+') => Ok(String::new()),
+        Some('?') if set => Ok(value.cloned().unwrap_or_default()),
+        Some('?') => Err(Error::config(format!(
+            "required variable {name} is missing a value: {}",
+            interpolate(chars.as_str(), env)?
+        ))),
+        _ => Err(Error::config(format!(
+            "invalid interpolation '${{{body}}}'"
+        ))),
+    }
+}
+
+fn closing_brace(body: &str) -> Option<usize> {
+    let mut depth = 0usize;
+    for (index, ch) in body.char_indices() {
+        match ch {
+            '{' => depth += 1,
+            '}' if depth == 0 => return Some(index),
+            '}' => depth -= 1,
+            _ => {}
+        }
+    }
+    None
+}
+
+fn name_len(input: &str) -> usize {
+    input
+        .find(|ch: char| !(ch.is_ascii_alphanumeric() || ch == '_'))
+        .unwrap_or(input.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+    use std::path::Path;
+
+    use crate::compose::load_compose;
+
+    const COMPOSE: &str = "services:\n  \
+                           db:\n    image: postgres:16\n  \
+                           api:\n    build:\n      context: ./api\n      \
+                           dockerfile: docker/${DOCKERFILE:-Dockerfile.prod}\n      \
+                           target: runtime\n      platforms: [\"linux/arm64\"]\n      \
+                           args:\n        VERSION: ${VERSION:?set VERSION}\n        TOKEN:\n        \
+                           PRICE: \"$$5\"\n  \
+                           worker:\n    build: ./worker\n";
+
+    fn env() -> BTreeMap<String, String> {
+        BTreeMap::from([
+            ("VERSION".to_string(), "1.4".to_string()),
+            ("TOKEN".to_string(), "secret".to_string()),
+        ])
+    }
+
+    #[test]
+    fn reads_the_build_section_with_interpolation() {
+        let build = load_compose(COMPOSE, Some("api"), &env()).expect("compose should load");
+        assert_eq!(
+            build.dockerfile_path(Path::new("/srv")).to_str(),
+            Some("/srv/./api/docker/Dockerfile.prod")
+        );
+        assert_eq!(build.target.as_deref(), Some("runtime"));
+        assert_eq!(build.platforms, vec!["linux/arm64"]);
+        assert_eq!(
+            build.args,
+            BTreeMap::from([
+                ("PRICE".to_string(), "$5".to_string()),
+                ("TOKEN".to_string(), "secret".to_string()),
+                ("VERSION".to_string(), "1.4".to_string()),
+            ])
+        );
+
+        let build = load_compose(COMPOSE, Some("worker"), &env()).expect("compose should load");
+        assert_eq!(
+            build.dockerfile_path(Path::new("")).to_str(),
+            Some("./worker/Dockerfile")
+        );
+    }
+
+    #[test]
+    fn accepts_list_args() {
+        let compose = "services:\n  app:\n    build:\n      args:\n        - VERSION=${VERSION}\n        - TOKEN\n        - UNSET\n";
+        let build = load_compose(compose, None, &env()).expect("compose should load");
+        assert_eq!(build.context, ".");
+        assert_eq!(build.args.len(), 2);
+        assert_eq!(build.args["VERSION"], "1.4");
+        assert_eq!(build.args["TOKEN"], "secret");
+    }
+
+    #[test]
+    fn reports_missing_services_and_required_variables() {
+        let error = load_compose(COMPOSE, None, &env()).expect_err("two services build");
+        assert_eq!(
+            error.to_string(),
+            "config error: compose file has 2 services with a build section (api, worker); select one with --service"
+        );
+
+        let error = load_compose(COMPOSE, Some("db"), &env()).expect_err("db has no build");
+        assert_eq!(
+            error.to_string(),
+            "config error: compose service 'db' has no build section"
+        );
+
+        let error =
+            load_compose(COMPOSE, Some("api"), &BTreeMap::new()).expect_err("VERSION is required");
+        assert_eq!(
+            error.to_string(),
+            "config error: required variable VERSION is missing a value: set VERSION"
+        );
+    }
+}
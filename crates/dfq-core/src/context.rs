@@ -0,0 +1,188 @@
+use std::collections::BTreeSet;
+
+use crate::dockerignore::DockerIgnore;
+use crate::glob::glob_match_or_parent;
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct BuildContext {
+    files: Vec<String>,
+    ignore: DockerIgnore,
+}
+
+impl BuildContext {
+    pub fn new(files: impl IntoIterator<Item = String>, ignore: DockerIgnore) -> Self {
+        let files: BTreeSet<String> = files
+            .into_iter()
+            .map(|file| normalize(&file))
+            .filter(|file| !file.is_empty() && !ignore.is_ignored(file))
+            .collect();
+        Self {
+            files: files.into_iter().collect(),
+            ignore,
+        }
+    }
+
+    pub fn files(&self) -> &[String] {
+        &self.files
+    }
+
+    pub fn ignore(&self) -> &DockerIgnore {
+        &self.ignore
+    }
+
+    pub fn matching(&self, source: &str) -> Vec<String> {
+        let source = normalize(source);
+        self.files
+            .iter()
+            .filter(|file| source.is_empty() || glob_match_or_parent(&source, file))
+            .cloned()
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub(crate) struct CopySpec {
+    pub sources: Vec<String>,
+    pub dest: Option<String>,
+    pub from: Option<String>,
+    pub excludes: Vec<String>,
+}
+
+pub(crate) fn parse_copy(raw: &str) -> CopySpec {
+    let body = raw.replace("\\\r\n", " ").replace("\\\n", " ");
+    let body = body.trim_start();
+    let body = body
+        .find(char::is_whitespace)
+        .map_or("", |index| body[index..].trim());
+
+    let mut spec = CopySpec::default();
+    let mut rest = body;
+    while let Some(flag) = rest.strip_prefix("--") {
+        let end = flag.find(char::is_whitespace).unwrap_or(flag.len());
+        match flag[..end].split_once('=') {
+            Some(("from", value)) => spec.from = Some(value.to_string()),
+            Some(("exclude", value)) => spec.excludes.push(value.to_string()),
+            _ => {}
+        }
+        rest = flag[end..].trim_start();
+    }
+
+    let first_line = rest.lines().next().unwrap_or_default();
+    if first_line.contains("<<") {
+        spec.dest = first_line
+            .split_whitespace()
+            .rfind(|arg| !arg.starts_with("<<"))
+            .map(str::to_string);
+        return spec;
+    }
+    let mut args =
+        json_array(rest).unwrap_or_else(|| rest.split_whitespace().map(str::to_string).collect());
+    spec.dest = args.pop();
+    spec.sources = args;
+    spec
+}
+
+pub(crate) fn is_remote(source: &str) -> bool {
+    source.starts_with("http://")
+        || source.starts_with("https://")
+        || source.starts_with("git@")
+        || source.starts_with("git://")
+}
+
+fn json_array(input: &str) -> Option<Vec<String>> {
+    let mut rest = input.strip_prefix('[')?.trim_start();
+    let mut out = Vec::new();
+    loop {
+        if let Some(after) = rest.strip_prefix(']') {
+            return after.trim().is_empty().then_some(out);
+        }
+        let mut chars = rest.strip_prefix('"')?.char_indices();
+        let mut item = String::new();
+        let end = loop {
+            match chars.next()? {
+                (index, '"') => break index,
+                (_, '\\') => item.push(chars.next()?.1),
+                (_, ch) => item.push(ch),
+            }
+        };
+        out.push(item);
+        rest = rest[end + 2..].trim_start();
+        if let Some(after) = rest.strip_prefix(',') {
+            rest = after.trim_start();
+        }
+    }
+}
+
+fn normalize(path: &str) -> String {
+    path.split('/')
+        .filter(|part| !part.is_empty() && *part != ".")
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::context::{BuildContext, parse_copy};
+    use crate::dockerignore::DockerIgnore;
+
+    fn context(ignore: &str) -> BuildContext {
+        BuildContext::new(
+            [
+                "./app/main.go",
+                "app/go.mod",
+                "app/vendor/lib.go",
+                "docs/README.md",
+                "node_modules/pkg/index.js",
+                ".env",
+            ]
+            .map(str::to_string),
+            DockerIgnore::parse(ignore).expect("dockerignore should parse"),
+        )
+    }
+
+    #[test]
+    fn applies_dockerignore_with_exceptions() {
+        let context =
+            context("# comment\nnode_modules\n*.md\n/.env\napp/vendor\n!app/vendor/lib.go\n");
+        assert_eq!(
+            context.files(),
+            [
+                "app/go.mod",
+                "app/main.go",
+                "app/vendor/lib.go",
+                "docs/README.md"
+            ]
+        );
+    }
+
+    #[test]
+    fn matches_copy_sources_against_files_and_directories() {
+        let context = context("");
+        assert_eq!(context.matching("app/*.go"), ["app/main.go"]);
+        assert_eq!(
+            context.matching("./app/"),
+            ["app/go.mod", "app/main.go", "app/vendor/lib.go"]
+        );
+        assert_eq!(context.matching(".").len(), 6);
+        assert!(context.matching("src").is_empty());
+    }
+
+    #[test]
+    fn parses_copy_flags_and_forms() {
+        let spec = parse_copy("COPY --chown=app:app --from=build \\\n  /out/bin ./lib /usr/local/");
+        assert_eq!(spec.from.as_deref(), Some("build"));
+        assert_eq!(spec.sources, ["/out/bin", "./lib"]);
+        assert_eq!(spec.dest.as_deref(), Some("/usr/local/"));
+
+        let spec = parse_copy("ADD [\"my file.txt\", \"/data/\"]");
+        assert_eq!(spec.sources, ["my file.txt"]);
+        assert_eq!(spec.dest.as_deref(), Some("/data/"));
+
+        let spec = parse_copy("COPY --exclude=*.md docs /docs");
+        assert_eq!(spec.excludes, ["*.md"]);
+
+        let spec = parse_copy("COPY <<EOF /etc/motd\nhello\nEOF");
+        assert!(spec.sources.is_empty());
+        assert_eq!(spec.dest.as_deref(), Some("/etc/motd"));
+    }
+}
@@ -0,0 +1,180 @@
+use std::path::{Path, PathBuf};
+
+use crate::error::Error;
+use crate::glob::glob_match_or_parent;
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct DockerIgnore {
+    rules: Vec<Rule>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Rule {
+    pattern: String,
+    exclusion: bool,
+}
+
+impl DockerIgnore {
+    pub fn parse(input: &str) -> Result<Self, Error> {
+        let mut rules = Vec::new();
+        for (index, line) in input.lines().enumerate() {
+            let line = if index == 0 {
+                line.trim_start_matches('\u{feff}')
+            } else {
+                line
+            };
+            if line.starts_with('#') {
+                continue;
+            }
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            let (exclusion, pattern) = match line.strip_prefix('!') {
+                Some(pattern) => (true, pattern.trim()),
+                None => (false, line),
+            };
+            if pattern.is_empty() {
+                return Err(Error::config(format!(
+                    "line {}: illegal exclusion pattern \"!\"",
+                    index + 1
+                )));
+            }
+            rules.push(Rule {
+                pattern: clean(pattern),
+                exclusion,
+            });
+        }
+        Ok(Self { rules })
+    }
+
+    pub fn patterns(&self) -> impl Iterator<Item = String> + '_ {
+        self.rules.iter().map(|rule| {
+            if rule.exclusion {
+                format!("!{}", rule.pattern)
+            } else {
+                rule.pattern.clone()
+            }
+        })
+    }
+
+    pub fn is_ignored(&self, path: &str) -> bool {
+        let path = clean(path);
+        let mut ignored = false;
+        for rule in &self.rules {
+            if rule.exclusion != ignored {
+                continue;
+            }
+            if glob_match_or_parent(&rule.pattern, &path) {
+                ignored = !rule.exclusion;
+            }
+        }
+        ignored
+    }
+
+    pub fn skips_dir(&self, dir: &str) -> bool {
+        self.rules.iter().all(|rule| !rule.exclusion) && self.is_ignored(dir)
+    }
+}
+
+pub fn ignore_files(context: &Path, dockerfile: Option<&Path>) -> Vec<PathBuf> {
+    let mut out = Vec::new();
+    if let Some(dockerfile) = dockerfile {
+        let mut name = dockerfile.as_os_str().to_owned();
+        name.push(".dockerignore");
+        out.push(PathBuf::from(name));
+    }
+    out.push(context.join(".dockerignore"));
+    out
+}
+
+fn clean(path: &str) -> String {
+    let absolute = path.starts_with('/');
+    let mut parts: Vec<&str> = Vec::new();
+    for part in path.split('/') {
+        match part {
+            "" | "." => {}
+            ".." if parts.last().is_some_and(|last| *last != "..") => {
+                parts.pop();
+            }
+            ".." if absolute => {}
+            part => parts.push(part),
+        }
+    }
+    match parts.join("/") {
+        cleaned if cleaned.is_empty() && !absolute => ".".to_string(),
+        cleaned => cleaned,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use crate::dockerignore::{DockerIgnore, ignore_files};
+
+    fn ignore(input: &str) -> DockerIgnore {
+        DockerIgnore::parse(input).expect("dockerignore should parse")
+    }
+
+    #[test]
+    fn follows_buildkit_pattern_semantics() {
+        let rules = ignore("# comment\n  *.md  \n/tmp/\nbuild/**\n**/*.log\nsrc/**/test\n");
+        assert!(rules.is_ignored("README.md"));
+        assert!(!rules.is_ignored("docs/README.md"));
+        assert!(rules.is_ignored("tmp/cache/x"));
+        assert!(rules.is_ignored("build/out/app"));
+        assert!(!rules.is_ignored("build"));
+        assert!(rules.is_ignored("app.log"));
+        assert!(rules.is_ignored("var/log/app.log"));
+        assert!(rules.is_ignored("src/test/fixture.json"));
+        assert!(rules.is_ignored("src/a/b/test"));
+        assert!(!rules.is_ignored("src/a/b/testing"));
+        assert!(!rules.is_ignored("# comment"));
+    }
+
+    #[test]
+    fn later_exceptions_reinclude_files() {
+        let rules = ignore("docs\n!docs/public/**\ndocs/public/draft.md\n*.txt\n!keep.txt\n");
+        assert!(rules.is_ignored("docs/internal/a.md"));
+        assert!(!rules.is_ignored("docs/public/index.md"));
+        assert!(rules.is_ignored("docs/public/draft.md"));
+        assert!(rules.is_ignored("notes.txt"));
+        assert!(!rules.is_ignored("keep.txt"));
+        assert!(rules.is_ignored("./notes.txt"));
+        assert!(!rules.skips_dir("docs"));
+        assert!(ignore("node_modules\n").skips_dir("node_modules"));
+    }
+
+    #[test]
+    fn cleans_patterns_and_supports_classes() {
+        let rules = ignore("./a/../b/*.[ch]\nlog[^s]\n\\*literal\n");
+        assert_eq!(
+            rules.patterns().collect::<Vec<_>>(),
+            ["b/*.[ch]", "log[^s]", "\\*literal"]
+        );
+        assert!(rules.is_ignored("b/main.c"));
+        assert!(!rules.is_ignored("b/main.rs"));
+        assert!(rules.is_ignored("log1"));
+        assert!(!rules.is_ignored("logs"));
+        assert!(rules.is_ignored("*literal"));
+        assert!(!rules.is_ignored("xliteral"));
+
+        let error = DockerIgnore::parse("node_modules\n!\n").expect_err("bare ! is illegal");
+        assert_eq!(
+            error.to_string(),
+            "config error: line 2: illegal exclusion pattern \"!\""
+        );
+    }
+
+    #[test]
+    fn prefers_the_dockerfile_specific_ignore_file() {
+        assert_eq!(
+            ignore_files(Path::new("ctx"), Some(Path::new("ctx/app.Dockerfile"))),
+            [
+                Path::new("ctx/app.Dockerfile.dockerignore"),
+                Path::new("ctx/.dockerignore")
+            ]
+        );
+    }
+}
@@ -12,13 +12,54 @@ impl Span {
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Lookup {
+    Field,
+    Index,
+    Stage,
+    Arg,
+}
+
+impl Lookup {
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Field => "unknown field",
+            Self::Index => "index out of range",
+            Self::Stage => "unknown stage name",
+            Self::Arg => "unset arg",
+        }
+    }
+}
+
 #[derive(Debug)]
 pub enum Error {
-    QueryParse { msg: String, span: Option<Span> },
-    DockerfileParse { msg: String },
-    NotFound { path: String },
-    Eval { msg: String },
-    Io { source: std::io::Error },
+    QueryParse {
+        msg: String,
+        span: Option<Span>,
+        query: Option<String>,
+    },
+    DockerfileParse {
+        msg: String,
+    },
+    NotFound {
+        path: String,
+        suggestion: Option<String>,
+        lookup: Lookup,
+        segment: Option<usize>,
+        span: Option<Span>,
+    },
+    Eval {
+        msg: String,
+    },
+    Template {
+        msg: String,
+    },
+    Config {
+        msg: String,
+    },
+    Io {
+        source: std::io::Error,
+    },
 }
 
 impl Error {
@@ -26,6 +67,7 @@ impl Error {
         Self::QueryParse {
             msg: msg.into(),
             span,
+            query: None,
         }
     }
 
@@ -34,40 +76,142 @@ impl Error {
     }
 
     pub fn not_found(path: impl Into<String>) -> Self {
-        Self::NotFound { path: path.into() }
+        Self::NotFound {
+            path: path.into(),
+            suggestion: None,
+            lookup: Lookup::Field,
+            segment: None,
+            span: None,
+        }
+    }
+
+    pub fn with_lookup(mut self, kind: Lookup) -> Self {
+        if let Self::NotFound { lookup, .. } = &mut self {
+            *lookup = kind;
+        }
+        self
+    }
+
+    pub fn with_segment(mut self, index: Option<usize>, location: Option<Span>) -> Self {
+        if let Self::NotFound { segment, span, .. } = &mut self {
+            *segment = index;
+            *span = location;
+        }
+        self
+    }
+
+    pub fn with_query(mut self, source: &str) -> Self {
+        if let Self::QueryParse { query, .. } = &mut self {
+            *query = Some(source.to_string());
+        }
+        self
+    }
+
+    pub fn with_suggestion(mut self, candidate: Option<String>) -> Self {
+        if let Self::NotFound { suggestion, .. } = &mut self {
+            *suggestion = candidate;
+        }
+        self
     }
 
     pub fn eval(msg: impl Into<String>) -> Self {
         Self::Eval { msg: msg.into() }
     }
 
+    pub fn template(msg: impl Into<String>) -> Self {
+        Self::Template { msg: msg.into() }
+    }
+
+    pub fn config(msg: impl Into<String>) -> Self {
+        Self::Config { msg: msg.into() }
+    }
+
     pub fn io(source: std::io::Error) -> Self {
         Self::Io { source }
     }
+
+    pub const fn kind(&self) -> &'static str {
+        match self {
+            Self::QueryParse { .. } => "query_parse",
+            Self::DockerfileParse { .. } => "dockerfile_parse",
+            Self::NotFound { .. } => "not_found",
+            Self::Eval { .. } => "eval",
+            Self::Template { .. } => "template",
+            Self::Config { .. } => "config",
+            Self::Io { .. } => "io",
+        }
+    }
+
+    pub const fn span(&self) -> Option<Span> {
+        match self {
+            Self::QueryParse { span, .. } | Self::NotFound { span, .. } => *span,
+            _ => None,
+        }
+    }
+
+    pub fn path(&self) -> Option<&str> {
+        match self {
+            Self::NotFound { path, .. } => Some(path),
+            _ => None,
+        }
+    }
 }
 
 impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            Self::QueryParse { msg, span } => {
-                if let Some(span) = span {
-                    write!(
-                        f,
-                        "query parse error at {}..{}: {msg}",
-                        span.start, span.end
-                    )
-                } else {
-                    write!(f, "query parse error: {msg}")
+            Self::QueryParse { msg, span, query } => {
+                let Some(span) = span else {
+                    return write!(f, "query parse error: {msg}");
+                };
+                write!(
+                    f,
+                    "query parse error at {}..{}: {msg}",
+                    span.start, span.end
+                )?;
+                if let Some(query) = query {
+                    write!(f, "\n  {query}\n  {}", caret_line(query, *span))?;
                 }
+                Ok(())
             }
             Self::DockerfileParse { msg } => write!(f, "dockerfile parse error: {msg}"),
-            Self::NotFound { path } => write!(f, "not found: {path}"),
+            Self::NotFound {
+                path,
+                suggestion,
+                lookup,
+                segment,
+                ..
+            } => {
+                write!(f, "not found: {path}")?;
+                if let Some(segment) = segment {
+                    write!(f, ": {} at segment {segment}", lookup.as_str())?;
+                }
+                if let Some(suggestion) = suggestion {
+                    write!(f, " (did you mean '{suggestion}'?)")?;
+                }
+                Ok(())
+            }
             Self::Eval { msg } => write!(f, "evaluation error: {msg}"),
+            Self::Template { msg } => write!(f, "template error: {msg}"),
+            Self::Config { msg } => write!(f, "config error: {msg}"),
             Self::Io { source } => write!(f, "io error: {source}"),
         }
     }
 }
 
+fn caret_line(query: &str, span: Span) -> String {
+    let prefix_end = span.start.min(query.len());
+    let end = span.end.min(query.len()).max(prefix_end);
+    let offset = query
+        .get(..prefix_end)
+        .map_or(prefix_end, |prefix| prefix.chars().count());
+    let width = query
+        .get(prefix_end..end)
+        .map_or(1, |range| range.chars().count())
+        .max(1);
+    format!("{}{}", " ".repeat(offset), "^".repeat(width))
+}
+
 impl std::error::Error for Error {
     fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
         if let Self::Io { source } = self {
@@ -83,3 +227,52 @@ impl From<std::io::Error> for Error {
         Self::io(value)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::error::{Error, Lookup, Span};
+
+    #[test]
+    fn renders_query_parse_errors_with_caret() {
+        let error =
+            Error::query_parse("expected identifier", Some(Span::new(4, 5))).with_query("ARG.");
+        assert_eq!(
+            error.to_string(),
+            "query parse error at 4..5: expected identifier\n  ARG.\n      ^"
+        );
+    }
+
+    #[test]
+    fn exposes_kind_span_and_path() {
+        let error = Error::query_parse("bad", Some(Span::new(1, 2)));
+        assert_eq!(error.kind(), "query_parse");
+        assert_eq!(error.span(), Some(Span::new(1, 2)));
+        assert_eq!(error.path(), None);
+
+        let error = Error::not_found("ARG.NOPE");
+        assert_eq!(error.kind(), "not_found");
+        assert_eq!(error.span(), None);
+        assert_eq!(error.path(), Some("ARG.NOPE"));
+    }
+
+    #[test]
+    fn renders_not_found_lookup_and_segment() {
+        let error = Error::not_found("STAGE[\"builder\"].ARG.VERSON.DEFAULT")
+            .with_lookup(Lookup::Arg)
+            .with_segment(Some(2), Some(Span::new(21, 27)));
+        assert_eq!(error.span(), Some(Span::new(21, 27)));
+        assert_eq!(
+            error.to_string(),
+            "not found: STAGE[\"builder\"].ARG.VERSON.DEFAULT: unset arg at segment 2"
+        );
+    }
+
+    #[test]
+    fn renders_not_found_suggestions() {
+        let error = Error::not_found("STAGE[0].PARNET").with_suggestion(Some("PARENT".to_string()));
+        assert_eq!(
+            error.to_string(),
+            "not found: STAGE[0].PARNET (did you mean 'PARENT'?)"
+        );
+    }
+}
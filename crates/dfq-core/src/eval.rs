@@ -1,7 +1,11 @@
+use std::cell::Cell;
 use std::collections::{BTreeMap, BTreeSet};
 
-use crate::error::Error;
-use crate::model::{DockerfileModel, Instruction, Parent, Stage};
+use crate::context::{BuildContext, CopySpec, is_remote, parse_copy};
+use crate::error::{Error, Lookup, Span};
+use crate::glob::glob_match;
+use crate::model::{Comment, DockerfileModel, Instruction, Parent, Stage};
+use crate::packages::{Package, parse_packages};
 use crate::query::{Arg, Index, Query, Segment};
 use crate::value::Value;
 
@@ -76,6 +80,12 @@ impl EvalMeta {
 
         Value::Object(map)
     }
+
+    pub fn merge(&mut self, other: Self) {
+        self.missing_vars.extend(other.missing_vars);
+        self.used_vars.extend(other.used_vars);
+        self.missing_paths.extend(other.missing_paths);
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -88,6 +98,9 @@ pub struct Evaluator<'a> {
     model: &'a DockerfileModel,
     overrides: &'a BTreeMap<String, String>,
     strict: bool,
+    context: Option<&'a BuildContext>,
+    query: Option<&'a Query>,
+    path_len: Cell<usize>,
 }
 
 impl<'a> Evaluator<'a> {
@@ -100,56 +113,296 @@ impl<'a> Evaluator<'a> {
             model,
             overrides,
             strict,
+            context: None,
+            query: None,
+            path_len: Cell::new(0),
         }
     }
 
+    pub fn with_context(mut self, context: Option<&'a BuildContext>) -> Self {
+        self.context = context;
+        self
+    }
+
     pub fn evaluate(&self, query: &Query) -> Result<EvalResult, Error> {
         if query.segments.is_empty() {
             return Err(Error::query_parse("empty query", None));
         }
 
-        match &query.segments[0] {
-            Segment::Ident(ident) if ident == "ARG" => {
-                self.eval_arg(&query.segments[1..], &query.source)
-            }
-            Segment::Ident(ident) if ident == "FROM" => {
-                self.eval_from_root(&query.segments[1..], &query.source)
+        let evaluator = Evaluator {
+            model: self.model,
+            overrides: self.overrides,
+            strict: self.strict,
+            context: self.context,
+            query: Some(query),
+            path_len: Cell::new(query.segments.len()),
+        };
+        evaluator
+            .eval_segments(&query.segments, &query.source)
+            .map_err(|error| locate(error, query))
+    }
+
+    fn not_found_at(&self, path: &str, remaining: usize, lookup: Lookup) -> Error {
+        Error::not_found(path.to_string())
+            .with_lookup(lookup)
+            .with_segment(self.path_len.get().checked_sub(remaining), None)
+    }
+
+    fn not_found_near<S: AsRef<str>>(
+        &self,
+        path: &str,
+        at: &[Segment],
+        lookup: Lookup,
+        candidates: impl IntoIterator<Item = S>,
+    ) -> Error {
+        self.not_found_at(path, at.len(), lookup)
+            .with_suggestion(segment_suggestion(&at[0], candidates))
+    }
+
+    fn eval_composite(
+        &self,
+        rest: &[Segment],
+        path: &str,
+        mut eval_member: impl FnMut(&[Segment]) -> Result<Value, Error>,
+    ) -> Result<Option<Value>, Error> {
+        let segment = self.path_len.get().checked_sub(rest.len());
+        eval_composite(rest, path, |member| {
+            let outer = self.path_len.replace(member.len());
+            let result = eval_member(member);
+            self.path_len.set(outer);
+            result.map_err(|error| self.locate_in(error, member))
+        })
+        .map_err(|error| match error {
+            Error::NotFound { span: None, .. } => error.with_segment(segment, None),
+            other => other,
+        })
+    }
+
+    /// Pins a not-found error raised while evaluating `member` to the failing
+    /// segment's position in the whole query, so it survives the enclosing
+    /// composite.
+    fn locate_in(&self, error: Error, member: &[Segment]) -> Error {
+        let Error::NotFound {
+            segment: Some(index),
+            span: None,
+            ..
+        } = error
+        else {
+            return error;
+        };
+        match member
+            .get(index)
+            .and_then(|segment| self.segment_location(segment))
+        {
+            Some((index, span)) => error.with_segment(Some(index), Some(span)),
+            None => error,
+        }
+    }
+
+    fn segment_location(&self, target: &Segment) -> Option<(usize, Span)> {
+        fn walk<'q>(segments: &'q [Segment], order: &mut Vec<&'q Segment>) {
+            for segment in segments {
+                order.push(segment);
+                match segment {
+                    Segment::Object(fields) => {
+                        for (_, member) in fields {
+                            walk(member, order);
+                        }
+                    }
+                    Segment::Array(members) | Segment::Alternative(members) => {
+                        for member in members {
+                            walk(member, order);
+                        }
+                    }
+                    _ => {}
+                }
             }
+        }
+
+        let query = self.query?;
+        let mut order = Vec::new();
+        walk(&query.segments, &mut order);
+        let index = order
+            .iter()
+            .position(|segment| std::ptr::eq(*segment, target))?;
+        Some((index, *query.spans.get(index)?))
+    }
+
+    fn eval_segments(&self, segments: &[Segment], path: &str) -> Result<EvalResult, Error> {
+        let mut meta = EvalMeta::new(Scope::Global);
+        if let Some(value) = self.eval_composite(segments, path, |member| {
+            let result = self.eval_segments(member, path)?;
+            meta.merge(result.meta);
+            Ok(result.value)
+        })? {
+            return Ok(EvalResult { value, meta });
+        }
+
+        let Some(first) = segments.first() else {
+            return Err(self.not_found_at(path, segments.len(), Lookup::Field));
+        };
+        if let [Segment::Ident(root), check] = segments
+            && COLLECTION_ROOTS.contains(&root.as_str())
+            && let Some(member) = existence_member(check)?
+        {
+            let target: Vec<Segment> = std::iter::once(first.clone()).chain(member).collect();
+            let value = exists(self.eval_segments(&target, path).map(|result| result.value))?;
+            return Ok(EvalResult {
+                value,
+                meta: EvalMeta::new(Scope::Global),
+            });
+        }
+        match first {
+            Segment::Ident(ident) if ident == "ARG" => self.eval_arg(&segments[1..], path),
+            Segment::Ident(ident) if ident == "FROM" => self.eval_from_root(&segments[1..], path),
             Segment::Indexed { ident, index } if ident == "FROM" => {
-                self.eval_from_index(index, &query.segments[1..], &query.source)
-            }
-            Segment::Ident(ident) if ident == "RUN" => {
-                self.eval_run_root(&query.segments[1..], &query.source)
+                self.eval_from_index(index, &segments[1..], path)
             }
+            Segment::Ident(ident) if ident == "RUN" => self.eval_run_root(&segments[1..], path),
             Segment::Indexed { ident, index } if ident == "RUN" => {
-                self.eval_run_index(index, &query.segments[1..], &query.source)
+                self.eval_run_index(index, &segments[1..], path)
+            }
+            Segment::Ident(ident) if ident == "COPY" => self.eval_copy_root(&segments[1..], path),
+            Segment::Indexed { ident, index } if ident == "COPY" => {
+                self.eval_copy_index(index, &segments[1..], path)
             }
-            Segment::Ident(ident) if ident == "STAGE" => {
-                self.eval_stage_root(&query.segments[1..], &query.source)
+            Segment::Ident(ident) if ident == "CONTEXT" => {
+                let value = self.eval_context(&segments[1..], path)?;
+                Ok(EvalResult {
+                    value,
+                    meta: EvalMeta::new(Scope::Global),
+                })
             }
+            Segment::Ident(ident) if ident == "PACKAGES" => {
+                self.eval_packages_index(&Index::Wildcard, &segments[1..], path)
+            }
+            Segment::Indexed { ident, index } if ident == "PACKAGES" => {
+                self.eval_packages_index(index, &segments[1..], path)
+            }
+            Segment::Ident(ident) if ident == "INSTRUCTION" => {
+                self.eval_instruction_index(&Index::Wildcard, &segments[1..], path)
+            }
+            Segment::Indexed { ident, index } if ident == "INSTRUCTION" => {
+                self.eval_instruction_index(index, &segments[1..], path)
+            }
+            Segment::Ident(ident) if ident == "COMMENTS" => {
+                self.eval_comments_index(&Index::Wildcard, &segments[1..], path)
+            }
+            Segment::Indexed { ident, index } if ident == "COMMENTS" => {
+                self.eval_comments_index(index, &segments[1..], path)
+            }
+            Segment::Ident(ident) if ident == "DIRECTIVES" => {
+                let value = self.eval_directives(&segments[1..], path)?;
+                Ok(EvalResult {
+                    value,
+                    meta: EvalMeta::new(Scope::Global),
+                })
+            }
+            Segment::Ident(ident) if ident == "STAGE" => self.eval_stage_root(&segments[1..], path),
             Segment::Indexed { ident, index } if ident == "STAGE" => {
-                self.eval_stage_index(index, &query.segments[1..], &query.source)
+                self.eval_stage_index(index, &segments[1..], path)
             }
             Segment::Function { ident, args } if ident == "RESOLVE" => {
-                if query.segments.len() > 1 {
-                    return Err(Error::not_found(query.source.clone()));
+                if segments.len() > 1 {
+                    return Err(self.not_found_at(path, segments.len() - 1, Lookup::Field));
                 }
                 self.eval_resolve(args, ResolveScope::Global, Scope::Global)
             }
-            _ => Err(Error::not_found(query.source.clone())),
+            Segment::Function { ident, args } if ident == "IGNORED" => {
+                if segments.len() > 1 {
+                    return Err(self.not_found_at(path, segments.len() - 1, Lookup::Field));
+                }
+                let file = function_single_string_arg(args, "IGNORED")?;
+                Ok(EvalResult {
+                    value: Value::Bool(self.build_context("IGNORED")?.ignore().is_ignored(file)),
+                    meta: EvalMeta::new(Scope::Global),
+                })
+            }
+            _ => Err(self.not_found_near(path, segments, Lookup::Field, ROOT_FIELDS)),
         }
     }
 
+    /// Line span of the instruction behind a query result, or behind item
+    /// `item` of a wildcard result, when the query root maps to one.
+    pub fn instruction_lines(&self, query: &Query, item: Option<usize>) -> Option<(usize, usize)> {
+        let (root, index) = match query.segments.first()? {
+            Segment::Ident(ident) => (ident.as_str(), &Index::Wildcard),
+            Segment::Indexed { ident, index } => (ident.as_str(), index),
+            _ => return None,
+        };
+        let position = match index {
+            Index::Position(position) => Some(*position),
+            Index::Wildcard => item,
+            Index::Key(_) => None,
+        };
+        let stage_from = |stage: usize| {
+            self.model
+                .raw_instructions
+                .iter()
+                .filter(|instruction| instruction.keyword == "FROM")
+                .nth(stage)
+        };
+        let instruction = match root {
+            "FROM" | "STAGE" => match index {
+                Index::Key(_) => {
+                    let stages = self.select_stage_indices(index, &[], &query.source).ok()?;
+                    stage_from(*stages.first()?)?
+                }
+                _ => stage_from(position?)?,
+            },
+            "RUN" => self.collect_entries(&["RUN"]).get(position?)?.instruction,
+            "COPY" => {
+                self.collect_entries(&["COPY", "ADD"])
+                    .get(position?)?
+                    .instruction
+            }
+            "PACKAGES" => {
+                self.collect_entries(&["RUN"])
+                    .into_iter()
+                    .flat_map(|entry| {
+                        parse_packages(&entry.instruction.raw)
+                            .into_iter()
+                            .map(move |_| entry)
+                    })
+                    .nth(position?)?
+                    .instruction
+            }
+            "INSTRUCTION" => self.model.raw_instructions.get(position?)?,
+            "COMMENTS" => {
+                let comment = self.model.comments.get(position?)?;
+                return Some((comment.line, comment.line));
+            }
+            "ARG" => {
+                let Some(Segment::Ident(name)) = query.segments.get(1) else {
+                    return None;
+                };
+                self.model
+                    .raw_instructions
+                    .iter()
+                    .take_while(|instruction| instruction.keyword != "FROM")
+                    .filter(|instruction| instruction.keyword == "ARG")
+                    .find(|instruction| {
+                        instruction
+                            .raw
+                            .split_whitespace()
+                            .skip(1)
+                            .any(|token| token.split('=').next() == Some(name.as_str()))
+                    })?
+            }
+            _ => return None,
+        };
+        Some((instruction.start_line, instruction.end_line))
+    }
+
     fn eval_arg(&self, rest: &[Segment], path: &str) -> Result<EvalResult, Error> {
         let mut meta = EvalMeta::new(Scope::Global);
 
         if rest.is_empty() {
             let mut out = BTreeMap::new();
-            for (key, value) in &self.model.global_args {
-                let mapped = value
-                    .as_ref()
-                    .map_or(Value::Null, |value| Value::String(value.clone()));
-                out.insert(key.clone(), mapped);
+            for key in self.model.global_args.keys() {
+                let value =
+                    self.resolve_var(ResolveScope::Global, key, &mut meta, &mut Vec::new())?;
+                out.insert(key.clone(), value.map_or(Value::Null, Value::String));
             }
             return Ok(EvalResult {
                 value: Value::Object(out),
@@ -157,9 +410,17 @@ impl<'a> Evaluator<'a> {
             });
         }
 
+        if let Some(value) = self.eval_composite(rest, path, |member| {
+            let result = self.eval_arg(member, path)?;
+            meta.merge(result.meta);
+            Ok(result.value)
+        })? {
+            return Ok(EvalResult { value, meta });
+        }
+
         let name = match &rest[0] {
             Segment::Ident(name) => name.clone(),
-            _ => return Err(Error::not_found(path.to_string())),
+            _ => return Err(self.not_found_at(path, rest.len(), Lookup::Field)),
         };
 
         if rest.len() == 1 {
@@ -170,7 +431,9 @@ impl<'a> Evaluator<'a> {
                     value: Value::String(value),
                     meta,
                 })
-                .ok_or_else(|| Error::not_found(path.to_string()));
+                .ok_or_else(|| {
+                    self.not_found_near(path, rest, Lookup::Arg, self.global_arg_names())
+                });
         }
 
         if rest.len() == 2
@@ -178,11 +441,9 @@ impl<'a> Evaluator<'a> {
         {
             return match field.as_str() {
                 "DEFAULT" => {
-                    let default = self
-                        .model
-                        .global_args
-                        .get(&name)
-                        .ok_or_else(|| Error::not_found(path.to_string()))?;
+                    let default = self.model.global_args.get(&name).ok_or_else(|| {
+                        self.not_found_near(path, rest, Lookup::Arg, self.global_arg_names())
+                    })?;
                     let value = default
                         .as_ref()
                         .map_or(Value::Null, |value| Value::String(value.clone()));
@@ -198,16 +459,16 @@ impl<'a> Evaluator<'a> {
                         meta,
                     })
                 }
-                _ => Err(Error::not_found(path.to_string())),
+                _ => Err(self.not_found_near(path, &rest[1..], Lookup::Field, ARG_FIELDS)),
             };
         }
 
-        Err(Error::not_found(path.to_string()))
+        Err(self.not_found_at(path, trailing_len(&rest[1..], ARG_FIELDS), Lookup::Field))
     }
 
     fn eval_from_root(&self, rest: &[Segment], path: &str) -> Result<EvalResult, Error> {
         if !rest.is_empty() {
-            return Err(Error::not_found(path.to_string()));
+            return self.eval_stages_field(rest, path);
         }
 
         let mut meta = EvalMeta::new(Scope::Global);
@@ -231,7 +492,7 @@ impl<'a> Evaluator<'a> {
         match index {
             Index::Position(position) => {
                 let Some(stage) = self.model.stages.get(*position) else {
-                    return Err(Error::not_found(path.to_string()));
+                    return Err(self.not_found_at(path, rest.len() + 1, Lookup::Index));
                 };
                 let mut meta = EvalMeta::new(Scope::Global);
                 let value = self.eval_from_stage(*position, stage, rest, &mut meta, path)?;
@@ -248,7 +509,7 @@ impl<'a> Evaluator<'a> {
                     meta,
                 })
             }
-            Index::Key(_) => Err(Error::not_found(path.to_string())),
+            Index::Key(_) => Err(self.not_found_at(path, rest.len() + 1, Lookup::Index)),
         }
     }
 
@@ -264,28 +525,40 @@ impl<'a> Evaluator<'a> {
             return self.resolve_parent(stage, meta).map(Value::String);
         }
 
+        if let Some(value) = self.eval_composite(rest, path, |member| {
+            self.eval_from_stage(_stage_index, stage, member, meta, path)
+        })? {
+            return Ok(value);
+        }
+
         if rest.len() != 1 {
-            return Err(Error::not_found(path.to_string()));
+            return Err(self.not_found_at(path, trailing_len(rest, PARENT_FIELDS), Lookup::Field));
         }
 
         let Segment::Ident(field) = &rest[0] else {
-            return Err(Error::not_found(path.to_string()));
+            return Err(self.not_found_at(path, rest.len(), Lookup::Field));
         };
         match field.as_str() {
             "RAW" => Ok(Value::String(parent_raw(&stage.parent))),
             "RESOLVED" => self.resolve_parent(stage, meta).map(Value::String),
             "KIND" => Ok(Value::String(parent_kind(&stage.parent).to_string())),
+            "REGISTRY" => match &stage.parent {
+                Parent::Image(_) => self
+                    .resolve_parent(stage, meta)
+                    .map(|image| Value::String(image_registry(&image).to_string())),
+                _ => Ok(Value::Null),
+            },
             "STAGE" => match &stage.parent {
                 Parent::StageRef(target) => Ok(Value::String(target.clone())),
-                _ => Err(Error::not_found(path.to_string())),
+                _ => Err(self.not_found_at(path, rest.len(), Lookup::Field)),
             },
-            _ => Err(Error::not_found(path.to_string())),
+            _ => Err(self.not_found_near(path, rest, Lookup::Field, parent_fields(&stage.parent))),
         }
     }
 
     fn eval_run_root(&self, rest: &[Segment], path: &str) -> Result<EvalResult, Error> {
         let mut meta = EvalMeta::new(Scope::Global);
-        let entries = self.collect_run_entries();
+        let entries = self.collect_entries(&["RUN"]);
         let value = self.eval_run_collection(&entries, rest, path, &mut meta)?;
         Ok(EvalResult { value, meta })
     }
@@ -296,12 +569,12 @@ impl<'a> Evaluator<'a> {
         rest: &[Segment],
         path: &str,
     ) -> Result<EvalResult, Error> {
-        let entries = self.collect_run_entries();
+        let entries = self.collect_entries(&["RUN"]);
 
         match index {
             Index::Position(position) => {
                 let Some(entry) = entries.get(*position) else {
-                    return Err(Error::not_found(path.to_string()));
+                    return Err(self.not_found_at(path, rest.len() + 1, Lookup::Index));
                 };
                 let meta = EvalMeta::new(Scope::Global);
                 let value = self.eval_run_entry(entry, rest, path)?;
@@ -312,13 +585,13 @@ impl<'a> Evaluator<'a> {
                 let value = self.eval_run_collection(&entries, rest, path, &mut meta)?;
                 Ok(EvalResult { value, meta })
             }
-            Index::Key(_) => Err(Error::not_found(path.to_string())),
+            Index::Key(_) => Err(self.not_found_at(path, rest.len() + 1, Lookup::Index)),
         }
     }
 
     fn eval_run_collection(
         &self,
-        entries: &[RunEntry<'a>],
+        entries: &[InstructionEntry<'a>],
         rest: &[Segment],
         path: &str,
         meta: &mut EvalMeta,
@@ -350,7 +623,7 @@ impl<'a> Evaluator<'a> {
             match ident.as_str() {
                 "GREP" => {
                     let needle = function_single_string_arg(args, "GREP")?;
-                    let filtered: Vec<RunEntry<'a>> = entries
+                    let filtered: Vec<InstructionEntry<'a>> = entries
                         .iter()
                         .filter(|entry| entry.instruction.raw.contains(needle))
                         .copied()
@@ -359,7 +632,7 @@ impl<'a> Evaluator<'a> {
                 }
                 "CONTAINS" => {
                     if rest.len() != 1 {
-                        return Err(Error::not_found(path.to_string()));
+                        return Err(self.not_found_at(path, rest.len() - 1, Lookup::Field));
                     }
                     let needle = function_single_string_arg(args, "CONTAINS")?;
                     let contains = entries
@@ -367,7 +640,15 @@ impl<'a> Evaluator<'a> {
                         .any(|entry| entry.instruction.raw.contains(needle));
                     return Ok(Value::Bool(contains));
                 }
-                _ => return Err(Error::not_found(path.to_string())),
+                "EXISTS" | "HAS" => {}
+                _ => {
+                    return Err(self.not_found_near(
+                        path,
+                        rest,
+                        Lookup::Field,
+                        RUN_COLLECTION_FIELDS,
+                    ));
+                }
             }
         }
 
@@ -375,17 +656,26 @@ impl<'a> Evaluator<'a> {
             return Ok(Value::Array(Vec::new()));
         }
 
-        let probe_path = run_path(entries[0].run_index, rest);
-        if matches!(
-            self.eval_run_entry(&entries[0], rest, &probe_path),
-            Err(Error::NotFound { .. })
-        ) {
-            return Err(Error::not_found(path.to_string()));
+        let probe_path = entry_path("RUN", entries[0].index, rest);
+        if let Err(Error::NotFound {
+            suggestion,
+            lookup,
+            segment,
+            span,
+            ..
+        }) = self.eval_run_entry(&entries[0], rest, &probe_path)
+        {
+            let suggestion =
+                suggestion.or_else(|| segment_suggestion(&rest[0], RUN_COLLECTION_FIELDS));
+            return Err(Error::not_found(path.to_string())
+                .with_lookup(lookup)
+                .with_segment(segment, span)
+                .with_suggestion(suggestion));
         }
 
         let mut values = Vec::with_capacity(entries.len());
         for entry in entries {
-            let item_path = run_path(entry.run_index, rest);
+            let item_path = entry_path("RUN", entry.index, rest);
             match self.eval_run_entry(entry, rest, &item_path) {
                 Ok(value) => values.push(value),
                 Err(Error::NotFound { .. }) => {
@@ -400,15 +690,20 @@ impl<'a> Evaluator<'a> {
 
     fn eval_run_entry(
         &self,
-        entry: &RunEntry<'a>,
+        entry: &InstructionEntry<'a>,
         rest: &[Segment],
         path: &str,
     ) -> Result<Value, Error> {
         if rest.is_empty() {
             return Ok(Value::String(entry.instruction.raw.clone()));
         }
+        if let Some(value) = self.eval_composite(rest, path, |member| {
+            self.eval_run_entry(entry, member, path)
+        })? {
+            return Ok(value);
+        }
         if rest.len() != 1 {
-            return Err(Error::not_found(path.to_string()));
+            return Err(self.not_found_at(path, trailing_len(rest, RUN_FIELDS), Lookup::Field));
         }
 
         match &rest[0] {
@@ -416,32 +711,493 @@ impl<'a> Evaluator<'a> {
                 "RAW" => Ok(Value::String(entry.instruction.raw.clone())),
                 "COMMAND" => Ok(Value::String(run_command(&entry.instruction.raw))),
                 "KEYWORD" => Ok(Value::String("RUN".to_string())),
-                "INDEX" => Ok(Value::Number(entry.run_index as i64)),
+                "INDEX" => Ok(Value::Number(entry.index as i64)),
                 "STAGE" => Ok(Value::Number(entry.stage_index as i64)),
                 "STAGE_NAME" => Ok(entry
                     .stage_name
                     .map_or(Value::Null, |name| Value::String(name.to_string()))),
-                "SPAN" => {
-                    let mut span = BTreeMap::new();
-                    span.insert(
-                        "start".to_string(),
-                        Value::Number(entry.instruction.start_line as i64),
-                    );
-                    span.insert(
-                        "end".to_string(),
-                        Value::Number(entry.instruction.end_line as i64),
-                    );
-                    Ok(Value::Object(span))
+                "SPAN" => Ok(span_value(entry.instruction)),
+                _ => Err(self.not_found_near(path, rest, Lookup::Field, RUN_FIELDS)),
+            },
+            _ => Err(self.not_found_at(path, rest.len(), Lookup::Field)),
+        }
+    }
+
+    fn eval_copy_root(&self, rest: &[Segment], path: &str) -> Result<EvalResult, Error> {
+        let mut result = self.eval_copy_index(&Index::Wildcard, rest, path)?;
+        if let [Segment::Ident(field)] = rest
+            && (field == "SOURCES_MATCHED" || field == "MISSING")
+            && let Value::Array(lists) = result.value
+        {
+            let mut seen = BTreeSet::new();
+            result.value = Value::Array(
+                lists
+                    .into_iter()
+                    .flat_map(|list| match list {
+                        Value::Array(items) => items,
+                        other => vec![other],
+                    })
+                    .filter(|item| seen.insert(item.to_json_string()))
+                    .collect(),
+            );
+        }
+        Ok(result)
+    }
+
+    fn eval_copy_index(
+        &self,
+        index: &Index,
+        rest: &[Segment],
+        path: &str,
+    ) -> Result<EvalResult, Error> {
+        let entries = self.collect_entries(&["COPY", "ADD"]);
+        let mut meta = EvalMeta::new(Scope::Global);
+        let value = match (index, rest) {
+            (Index::Position(position), _) => {
+                let Some(entry) = entries.get(*position) else {
+                    return Err(self.not_found_at(path, rest.len() + 1, Lookup::Index));
+                };
+                self.eval_copy_entry(entry, rest, path, &mut meta)?
+            }
+            (Index::Wildcard, []) => Value::Array(
+                entries
+                    .iter()
+                    .map(|entry| Value::String(entry.instruction.raw.clone()))
+                    .collect(),
+            ),
+            (Index::Wildcard, [Segment::Ident(field)]) if field == "COUNT" => {
+                Value::Number(entries.len() as i64)
+            }
+            (Index::Wildcard, _) => {
+                let mut values = Vec::with_capacity(entries.len());
+                for entry in &entries {
+                    values.push(self.eval_copy_entry(entry, rest, path, &mut meta)?);
                 }
-                _ => Err(Error::not_found(path.to_string())),
+                Value::Array(values)
+            }
+            (Index::Key(_), _) => {
+                return Err(self.not_found_at(path, rest.len() + 1, Lookup::Index));
+            }
+        };
+        Ok(EvalResult { value, meta })
+    }
+
+    fn eval_copy_entry(
+        &self,
+        entry: &InstructionEntry<'a>,
+        rest: &[Segment],
+        path: &str,
+        meta: &mut EvalMeta,
+    ) -> Result<Value, Error> {
+        if rest.is_empty() {
+            return Ok(Value::String(entry.instruction.raw.clone()));
+        }
+        if let Some(value) = self.eval_composite(rest, path, |member| {
+            self.eval_copy_entry(entry, member, path, meta)
+        })? {
+            return Ok(value);
+        }
+        if rest.len() != 1 {
+            return Err(self.not_found_at(path, trailing_len(rest, COPY_FIELDS), Lookup::Field));
+        }
+        let Segment::Ident(field) = &rest[0] else {
+            return Err(self.not_found_at(path, rest.len(), Lookup::Field));
+        };
+
+        let spec = parse_copy(&entry.instruction.raw);
+        let scope = ResolveScope::Stage(entry.stage_index);
+        let strings =
+            |items: Vec<String>| Value::Array(items.into_iter().map(Value::String).collect());
+        match field.as_str() {
+            "RAW" => Ok(Value::String(entry.instruction.raw.clone())),
+            "KEYWORD" => Ok(Value::String(entry.instruction.keyword.clone())),
+            "SOURCES" => Ok(strings(self.copy_sources(&spec, scope, meta)?)),
+            "DEST" => match &spec.dest {
+                Some(dest) => Ok(Value::String(self.resolve_text(
+                    scope,
+                    dest,
+                    meta,
+                    &mut Vec::new(),
+                )?)),
+                None => Ok(Value::Null),
             },
-            _ => Err(Error::not_found(path.to_string())),
+            "FROM" => Ok(spec.from.map_or(Value::Null, Value::String)),
+            "SOURCES_MATCHED" => {
+                let matches = self.copy_matches(entry, &spec, field, meta)?;
+                let files: BTreeSet<String> =
+                    matches.into_iter().flat_map(|(_, files)| files).collect();
+                Ok(strings(files.into_iter().collect()))
+            }
+            "MISSING" => {
+                let matches = self.copy_matches(entry, &spec, field, meta)?;
+                Ok(strings(
+                    matches
+                        .into_iter()
+                        .filter(|(_, files)| files.is_empty())
+                        .map(|(source, _)| source)
+                        .collect(),
+                ))
+            }
+            "INDEX" => Ok(Value::Number(entry.index as i64)),
+            "STAGE" => Ok(Value::Number(entry.stage_index as i64)),
+            "STAGE_NAME" => Ok(entry
+                .stage_name
+                .map_or(Value::Null, |name| Value::String(name.to_string()))),
+            "SPAN" => Ok(span_value(entry.instruction)),
+            _ => Err(self.not_found_near(path, rest, Lookup::Field, COPY_FIELDS)),
+        }
+    }
+
+    fn copy_sources(
+        &self,
+        spec: &CopySpec,
+        scope: ResolveScope,
+        meta: &mut EvalMeta,
+    ) -> Result<Vec<String>, Error> {
+        spec.sources
+            .iter()
+            .map(|source| self.resolve_text(scope, source, meta, &mut Vec::new()))
+            .collect()
+    }
+
+    fn copy_matches(
+        &self,
+        entry: &InstructionEntry<'a>,
+        spec: &CopySpec,
+        field: &str,
+        meta: &mut EvalMeta,
+    ) -> Result<Vec<(String, Vec<String>)>, Error> {
+        let context = self.build_context(field)?;
+        if spec.from.is_some() {
+            return Ok(Vec::new());
+        }
+        let mut out = Vec::new();
+        for source in self.copy_sources(spec, ResolveScope::Stage(entry.stage_index), meta)? {
+            if entry.instruction.keyword == "ADD" && is_remote(&source) {
+                continue;
+            }
+            let mut files = context.matching(&source);
+            files.retain(|file| {
+                !spec.excludes.iter().any(|pattern| {
+                    glob_match(pattern, file) || glob_match(&format!("**/{pattern}"), file)
+                })
+            });
+            out.push((source, files));
+        }
+        Ok(out)
+    }
+
+    fn eval_context(&self, rest: &[Segment], path: &str) -> Result<Value, Error> {
+        if let Some(value) =
+            self.eval_composite(rest, path, |member| self.eval_context(member, path))?
+        {
+            return Ok(value);
+        }
+        let files = |context: &BuildContext| {
+            Value::Array(context.files().iter().cloned().map(Value::String).collect())
+        };
+        match rest {
+            [] => Ok(Value::Object(BTreeMap::from([(
+                "files".to_string(),
+                files(self.build_context("CONTEXT")?),
+            )]))),
+            [Segment::Ident(field)] if field == "FILES" => Ok(files(self.build_context(field)?)),
+            _ => Err(self.not_found_near(path, rest, Lookup::Field, CONTEXT_FIELDS)),
+        }
+    }
+
+    fn eval_packages_index(
+        &self,
+        index: &Index,
+        rest: &[Segment],
+        path: &str,
+    ) -> Result<EvalResult, Error> {
+        let packages: Vec<(InstructionEntry<'a>, Package)> = self
+            .collect_entries(&["RUN"])
+            .into_iter()
+            .flat_map(|entry| {
+                parse_packages(&entry.instruction.raw)
+                    .into_iter()
+                    .map(move |package| (entry, package))
+            })
+            .collect();
+        let value = match (index, rest) {
+            (Index::Position(position), _) => {
+                let Some((entry, package)) = packages.get(*position) else {
+                    return Err(self.not_found_at(path, rest.len() + 1, Lookup::Index));
+                };
+                self.eval_package(entry, package, rest, path)?
+            }
+            (Index::Wildcard, [Segment::Ident(field)]) if field == "COUNT" => {
+                Value::Number(packages.len() as i64)
+            }
+            (Index::Wildcard, _) => Value::Array(
+                packages
+                    .iter()
+                    .map(|(entry, package)| self.eval_package(entry, package, rest, path))
+                    .collect::<Result<_, _>>()?,
+            ),
+            (Index::Key(_), _) => {
+                return Err(self.not_found_at(path, rest.len() + 1, Lookup::Index));
+            }
+        };
+        Ok(EvalResult {
+            value,
+            meta: EvalMeta::new(Scope::Global),
+        })
+    }
+
+    fn eval_package(
+        &self,
+        entry: &InstructionEntry<'a>,
+        package: &Package,
+        rest: &[Segment],
+        path: &str,
+    ) -> Result<Value, Error> {
+        if let Some(value) = self.eval_composite(rest, path, |member| {
+            self.eval_package(entry, package, member, path)
+        })? {
+            return Ok(value);
+        }
+        let field = |name: &str| match name {
+            "MANAGER" => Some(Value::String(package.manager.to_string())),
+            "NAME" => Some(Value::String(package.name.clone())),
+            "VERSION" => Some(package.version.clone().map_or(Value::Null, Value::String)),
+            "STAGE" => Some(Value::Number(entry.stage_index as i64)),
+            "RUN_INDEX" => Some(Value::Number(entry.index as i64)),
+            _ => None,
+        };
+        match rest {
+            [] => Ok(Value::Object(
+                PACKAGE_FIELDS
+                    .iter()
+                    .filter_map(|name| Some((name.to_ascii_lowercase(), field(name)?)))
+                    .collect(),
+            )),
+            [Segment::Ident(name)] => field(name)
+                .ok_or_else(|| self.not_found_near(path, rest, Lookup::Field, PACKAGE_FIELDS)),
+            _ => Err(self.not_found_at(path, trailing_len(rest, PACKAGE_FIELDS), Lookup::Field)),
+        }
+    }
+
+    fn eval_instruction_index(
+        &self,
+        index: &Index,
+        rest: &[Segment],
+        path: &str,
+    ) -> Result<EvalResult, Error> {
+        let instructions = &self.model.raw_instructions;
+        let value = match (index, rest) {
+            (Index::Position(position), _) => {
+                let Some(instruction) = instructions.get(*position) else {
+                    return Err(self.not_found_at(path, rest.len() + 1, Lookup::Index));
+                };
+                self.eval_instruction(instruction, rest, path)?
+            }
+            (Index::Wildcard, []) => Value::Array(
+                instructions
+                    .iter()
+                    .map(|instruction| Value::String(instruction.raw.clone()))
+                    .collect(),
+            ),
+            (Index::Wildcard, [Segment::Ident(field)]) if field == "COUNT" => {
+                Value::Number(instructions.len() as i64)
+            }
+            (Index::Wildcard, _) => Value::Array(
+                instructions
+                    .iter()
+                    .map(|instruction| self.eval_instruction(instruction, rest, path))
+                    .collect::<Result<_, _>>()?,
+            ),
+            (Index::Key(_), _) => {
+                return Err(self.not_found_at(path, rest.len() + 1, Lookup::Index));
+            }
+        };
+        Ok(EvalResult {
+            value,
+            meta: EvalMeta::new(Scope::Global),
+        })
+    }
+
+    fn eval_instruction(
+        &self,
+        instruction: &Instruction,
+        rest: &[Segment],
+        path: &str,
+    ) -> Result<Value, Error> {
+        if rest.is_empty() {
+            return Ok(Value::String(instruction.raw.clone()));
+        }
+        if let Some(value) = self.eval_composite(rest, path, |member| {
+            self.eval_instruction(instruction, member, path)
+        })? {
+            return Ok(value);
+        }
+        if rest.len() != 1 {
+            return Err(self.not_found_at(
+                path,
+                trailing_len(rest, INSTRUCTION_FIELDS),
+                Lookup::Field,
+            ));
+        }
+        let Segment::Ident(field) = &rest[0] else {
+            return Err(self.not_found_at(path, rest.len(), Lookup::Field));
+        };
+        self.instruction_field(instruction, field)
+            .ok_or_else(|| self.not_found_near(path, rest, Lookup::Field, INSTRUCTION_FIELDS))
+    }
+
+    fn instruction_field(&self, instruction: &Instruction, field: &str) -> Option<Value> {
+        match field {
+            "RAW" => Some(Value::String(instruction.raw.clone())),
+            "KEYWORD" => Some(Value::String(instruction.keyword.clone())),
+            "SPAN" => Some(span_value(instruction)),
+            "COMMENT" => Some(self.instruction_comment(instruction)),
+            _ => None,
+        }
+    }
+
+    fn instruction_comment(&self, instruction: &Instruction) -> Value {
+        let mut expected = instruction.start_line;
+        let mut texts: Vec<&str> = self
+            .model
+            .comments
+            .iter()
+            .rev()
+            .skip_while(|comment| comment.line >= instruction.start_line)
+            .take_while(|comment| {
+                let adjacent = comment.line + 1 == expected;
+                expected = comment.line;
+                adjacent
+            })
+            .map(|comment| comment.text.as_str())
+            .collect();
+        if texts.is_empty() {
+            return Value::Null;
+        }
+        texts.reverse();
+        Value::String(texts.join("\n"))
+    }
+
+    fn eval_comments_index(
+        &self,
+        index: &Index,
+        rest: &[Segment],
+        path: &str,
+    ) -> Result<EvalResult, Error> {
+        let comments = &self.model.comments;
+        let value = match (index, rest) {
+            (Index::Position(position), _) => {
+                let Some(comment) = comments.get(*position) else {
+                    return Err(self.not_found_at(path, rest.len() + 1, Lookup::Index));
+                };
+                self.eval_comment(comment, rest, path)?
+            }
+            (Index::Wildcard, []) => Value::Array(
+                comments
+                    .iter()
+                    .map(|comment| Value::String(comment.text.clone()))
+                    .collect(),
+            ),
+            (Index::Wildcard, [Segment::Ident(field)]) if field == "COUNT" => {
+                Value::Number(comments.len() as i64)
+            }
+            (Index::Wildcard, _) => Value::Array(
+                comments
+                    .iter()
+                    .map(|comment| self.eval_comment(comment, rest, path))
+                    .collect::<Result<_, _>>()?,
+            ),
+            (Index::Key(_), _) => {
+                return Err(self.not_found_at(path, rest.len() + 1, Lookup::Index));
+            }
+        };
+        Ok(EvalResult {
+            value,
+            meta: EvalMeta::new(Scope::Global),
+        })
+    }
+
+    fn eval_comment(
+        &self,
+        comment: &Comment,
+        rest: &[Segment],
+        path: &str,
+    ) -> Result<Value, Error> {
+        if rest.is_empty() {
+            return Ok(Value::String(comment.text.clone()));
+        }
+        if let Some(value) = self.eval_composite(rest, path, |member| {
+            self.eval_comment(comment, member, path)
+        })? {
+            return Ok(value);
+        }
+        if rest.len() != 1 {
+            return Err(self.not_found_at(path, trailing_len(rest, COMMENT_FIELDS), Lookup::Field));
+        }
+        let Segment::Ident(field) = &rest[0] else {
+            return Err(self.not_found_at(path, rest.len(), Lookup::Field));
+        };
+        match field.as_str() {
+            "TEXT" => Ok(Value::String(comment.text.clone())),
+            "LINE" => Ok(Value::Number(comment.line as i64)),
+            "INSTRUCTION" => Ok(self
+                .model
+                .raw_instructions
+                .iter()
+                .find(|instruction| instruction.start_line > comment.line)
+                .map_or(Value::Null, |instruction| {
+                    Value::String(instruction.raw.clone())
+                })),
+            _ => Err(self.not_found_near(path, rest, Lookup::Field, COMMENT_FIELDS)),
+        }
+    }
+
+    fn eval_directives(&self, rest: &[Segment], path: &str) -> Result<Value, Error> {
+        if let Some(value) =
+            self.eval_composite(rest, path, |member| self.eval_directives(member, path))?
+        {
+            return Ok(value);
+        }
+        match rest {
+            [] => Ok(Value::Object(
+                self.model
+                    .directives
+                    .iter()
+                    .map(|(name, value)| (name.clone(), Value::String(value.clone())))
+                    .collect(),
+            )),
+            [Segment::Ident(field)]
+                if DIRECTIVE_FIELDS.contains(&field.to_ascii_uppercase().as_str()) =>
+            {
+                Ok(self
+                    .model
+                    .directives
+                    .get(&field.to_ascii_lowercase())
+                    .map_or(Value::Null, |value| Value::String(value.clone())))
+            }
+            _ => Err(self.not_found_near(path, rest, Lookup::Field, DIRECTIVE_FIELDS)),
+        }
+    }
+
+    fn build_context(&self, field: &str) -> Result<&'a BuildContext, Error> {
+        self.context
+            .ok_or_else(|| Error::eval(format!("{field} requires a build context")))
+    }
+
+    fn eval_stages_field(&self, rest: &[Segment], path: &str) -> Result<EvalResult, Error> {
+        match rest {
+            [Segment::Ident(field)] if field == "COUNT" => Ok(EvalResult {
+                value: Value::Number(self.model.stages.len() as i64),
+                meta: EvalMeta::new(Scope::Global),
+            }),
+            _ => Err(self.not_found_near(path, rest, Lookup::Field, STAGES_FIELDS)),
         }
     }
 
     fn eval_stage_root(&self, rest: &[Segment], path: &str) -> Result<EvalResult, Error> {
         if !rest.is_empty() {
-            return Err(Error::not_found(path.to_string()));
+            return self.eval_stages_field(rest, path);
         }
 
         let mut meta = EvalMeta::new(Scope::Global);
@@ -462,7 +1218,7 @@ impl<'a> Evaluator<'a> {
         rest: &[Segment],
         path: &str,
     ) -> Result<EvalResult, Error> {
-        let selected = self.select_stage_indices(index, path)?;
+        let selected = self.select_stage_indices(index, rest, path)?;
 
         match index {
             Index::Wildcard => {
@@ -508,17 +1264,23 @@ impl<'a> Evaluator<'a> {
         meta: &mut EvalMeta,
     ) -> Result<Value, Error> {
         let Some(stage) = self.model.stages.get(stage_index) else {
-            return Err(Error::not_found(path.to_string()));
+            return Err(self.not_found_at(path, rest.len() + 1, Lookup::Index));
         };
 
         if rest.is_empty() {
             return self.stage_object(stage_index, stage, meta);
         }
 
+        if let Some(value) = self.eval_composite(rest, path, |member| {
+            self.eval_stage_path(stage_index, member, path, meta)
+        })? {
+            return Ok(value);
+        }
+
         match &rest[0] {
             Segment::Ident(field) if field == "NAME" => {
                 if rest.len() != 1 {
-                    return Err(Error::not_found(path.to_string()));
+                    return Err(self.not_found_at(path, rest.len() - 1, Lookup::Field));
                 }
                 Ok(stage.name.clone().map_or(Value::Null, Value::String))
             }
@@ -526,22 +1288,7 @@ impl<'a> Evaluator<'a> {
                 self.eval_stage_arg(stage_index, stage, &rest[1..], path, meta)
             }
             Segment::Ident(field) if field == "PARENT" => {
-                if rest.len() != 2 {
-                    return Err(Error::not_found(path.to_string()));
-                }
-                let Segment::Ident(parent_field) = &rest[1] else {
-                    return Err(Error::not_found(path.to_string()));
-                };
-                match parent_field.as_str() {
-                    "RAW" => Ok(Value::String(parent_raw(&stage.parent))),
-                    "RESOLVED" => self.resolve_parent(stage, meta).map(Value::String),
-                    "KIND" => Ok(Value::String(parent_kind(&stage.parent).to_string())),
-                    "STAGE" => match &stage.parent {
-                        Parent::StageRef(target) => Ok(Value::String(target.clone())),
-                        _ => Err(Error::not_found(path.to_string())),
-                    },
-                    _ => Err(Error::not_found(path.to_string())),
-                }
+                self.eval_stage_parent(stage, &rest[1..], path, meta)
             }
             Segment::Ident(field) if field == "INSTRUCTIONS" => {
                 self.eval_stage_instructions(stage, &rest[1..], path)
@@ -551,7 +1298,7 @@ impl<'a> Evaluator<'a> {
             }
             Segment::Function { ident, args } if ident == "RESOLVE" => {
                 if rest.len() != 1 {
-                    return Err(Error::not_found(path.to_string()));
+                    return Err(self.not_found_at(path, rest.len() - 1, Lookup::Field));
                 }
                 if args.len() != 1 {
                     return Err(Error::eval("RESOLVE requires exactly one argument"));
@@ -565,7 +1312,44 @@ impl<'a> Evaluator<'a> {
                     self.resolve_text(ResolveScope::Stage(stage_index), input, meta, &mut stack)?;
                 Ok(Value::String(resolved))
             }
-            _ => Err(Error::not_found(path.to_string())),
+            _ => Err(self.not_found_near(path, rest, Lookup::Field, STAGE_FIELDS)),
+        }
+    }
+
+    fn eval_stage_parent(
+        &self,
+        stage: &Stage,
+        rest: &[Segment],
+        path: &str,
+        meta: &mut EvalMeta,
+    ) -> Result<Value, Error> {
+        if let Some(value) = self.eval_composite(rest, path, |member| {
+            self.eval_stage_parent(stage, member, path, meta)
+        })? {
+            return Ok(value);
+        }
+
+        if rest.len() != 1 {
+            return Err(self.not_found_at(path, trailing_len(rest, PARENT_FIELDS), Lookup::Field));
+        }
+        let Segment::Ident(field) = &rest[0] else {
+            return Err(self.not_found_at(path, rest.len(), Lookup::Field));
+        };
+        match field.as_str() {
+            "RAW" => Ok(Value::String(parent_raw(&stage.parent))),
+            "RESOLVED" => self.resolve_parent(stage, meta).map(Value::String),
+            "KIND" => Ok(Value::String(parent_kind(&stage.parent).to_string())),
+            "REGISTRY" => match &stage.parent {
+                Parent::Image(_) => self
+                    .resolve_parent(stage, meta)
+                    .map(|image| Value::String(image_registry(&image).to_string())),
+                _ => Ok(Value::Null),
+            },
+            "STAGE" => match &stage.parent {
+                Parent::StageRef(target) => Ok(Value::String(target.clone())),
+                _ => Err(self.not_found_at(path, rest.len(), Lookup::Field)),
+            },
+            _ => Err(self.not_found_near(path, rest, Lookup::Field, parent_fields(&stage.parent))),
         }
     }
 
@@ -579,29 +1363,32 @@ impl<'a> Evaluator<'a> {
     ) -> Result<Value, Error> {
         if rest.is_empty() {
             let mut out = BTreeMap::new();
-            for (key, value) in &stage.arg_defaults {
-                out.insert(
-                    key.clone(),
-                    value
-                        .as_ref()
-                        .map_or(Value::Null, |value| Value::String(value.clone())),
-                );
+            for key in stage.arg_defaults.keys() {
+                let value =
+                    self.resolve_var(ResolveScope::Stage(stage_index), key, meta, &mut Vec::new())?;
+                out.insert(key.clone(), value.map_or(Value::Null, Value::String));
             }
             return Ok(Value::Object(out));
         }
 
+        if let Some(value) = self.eval_composite(rest, path, |member| {
+            self.eval_stage_arg(stage_index, stage, member, path, meta)
+        })? {
+            return Ok(value);
+        }
+
         let name = match &rest[0] {
             Segment::Ident(name) => name.clone(),
-            _ => return Err(Error::not_found(path.to_string())),
+            _ => return Err(self.not_found_at(path, rest.len(), Lookup::Field)),
         };
 
         if rest.len() == 1 {
             let mut stack = Vec::new();
             let value =
                 self.resolve_var(ResolveScope::Stage(stage_index), &name, meta, &mut stack)?;
-            return value
-                .map(Value::String)
-                .ok_or_else(|| Error::not_found(path.to_string()));
+            return value.map(Value::String).ok_or_else(|| {
+                self.not_found_near(path, rest, Lookup::Arg, self.stage_arg_names(stage))
+            });
         }
 
         if rest.len() == 2
@@ -609,10 +1396,9 @@ impl<'a> Evaluator<'a> {
         {
             return match field.as_str() {
                 "DEFAULT" => {
-                    let default = stage
-                        .arg_defaults
-                        .get(&name)
-                        .ok_or_else(|| Error::not_found(path.to_string()))?;
+                    let default = stage.arg_defaults.get(&name).ok_or_else(|| {
+                        self.not_found_near(path, rest, Lookup::Arg, self.stage_arg_names(stage))
+                    })?;
                     Ok(default
                         .as_ref()
                         .map_or(Value::Null, |value| Value::String(value.clone())))
@@ -624,11 +1410,11 @@ impl<'a> Evaluator<'a> {
                         .is_some();
                     Ok(Value::Bool(set))
                 }
-                _ => Err(Error::not_found(path.to_string())),
+                _ => Err(self.not_found_near(path, &rest[1..], Lookup::Field, ARG_FIELDS)),
             };
         }
 
-        Err(Error::not_found(path.to_string()))
+        Err(self.not_found_at(path, trailing_len(&rest[1..], ARG_FIELDS), Lookup::Field))
     }
 
     fn eval_stage_instructions(
@@ -637,19 +1423,29 @@ impl<'a> Evaluator<'a> {
         rest: &[Segment],
         path: &str,
     ) -> Result<Value, Error> {
+        if let Some(value) = self.eval_composite(rest, path, |member| {
+            self.eval_stage_instructions(stage, member, path)
+        })? {
+            return Ok(value);
+        }
+
         if rest.len() != 1 {
-            return Err(Error::not_found(path.to_string()));
+            return Err(self.not_found_at(
+                path,
+                trailing_len(rest, INSTRUCTIONS_FIELDS),
+                Lookup::Field,
+            ));
         }
 
         let Segment::Ident(field) = &rest[0] else {
-            return Err(Error::not_found(path.to_string()));
+            return Err(self.not_found_at(path, rest.len(), Lookup::Field));
         };
 
         if field == "COUNT" {
             return Ok(Value::Number(stage.instructions.len() as i64));
         }
 
-        Err(Error::not_found(path.to_string()))
+        Err(self.not_found_near(path, rest, Lookup::Field, INSTRUCTIONS_FIELDS))
     }
 
     fn eval_stage_instruction_index(
@@ -661,36 +1457,31 @@ impl<'a> Evaluator<'a> {
     ) -> Result<Value, Error> {
         let instruction_index = match index {
             Index::Position(index) => *index,
-            _ => return Err(Error::not_found(path.to_string())),
+            _ => return Err(self.not_found_at(path, rest.len() + 1, Lookup::Index)),
         };
         let Some(instruction) = stage.instructions.get(instruction_index) else {
-            return Err(Error::not_found(path.to_string()));
+            return Err(self.not_found_at(path, rest.len() + 1, Lookup::Index));
         };
 
+        if let Some(value) = self.eval_composite(rest, path, |member| {
+            self.eval_stage_instruction_index(stage, index, member, path)
+        })? {
+            return Ok(value);
+        }
+
         if rest.len() != 1 {
-            return Err(Error::not_found(path.to_string()));
+            return Err(self.not_found_at(
+                path,
+                trailing_len(rest, INSTRUCTION_FIELDS),
+                Lookup::Field,
+            ));
         }
 
         let Segment::Ident(field) = &rest[0] else {
-            return Err(Error::not_found(path.to_string()));
+            return Err(self.not_found_at(path, rest.len(), Lookup::Field));
         };
-        match field.as_str() {
-            "RAW" => Ok(Value::String(instruction.raw.clone())),
-            "KEYWORD" => Ok(Value::String(instruction.keyword.clone())),
-            "SPAN" => {
-                let mut span = BTreeMap::new();
-                span.insert(
-                    "start".to_string(),
-                    Value::Number(instruction.start_line as i64),
-                );
-                span.insert(
-                    "end".to_string(),
-                    Value::Number(instruction.end_line as i64),
-                );
-                Ok(Value::Object(span))
-            }
-            _ => Err(Error::not_found(path.to_string())),
-        }
+        self.instruction_field(instruction, field)
+            .ok_or_else(|| self.not_found_near(path, rest, Lookup::Field, INSTRUCTION_FIELDS))
     }
 
     fn eval_resolve(
@@ -898,14 +1689,29 @@ impl<'a> Evaluator<'a> {
         Ok(None)
     }
 
-    fn collect_run_entries(&self) -> Vec<RunEntry<'a>> {
+    fn global_arg_names(&self) -> impl Iterator<Item = &str> {
+        self.model
+            .global_args
+            .keys()
+            .chain(self.overrides.keys())
+            .map(String::as_str)
+    }
+
+    fn stage_arg_names<'s>(&'s self, stage: &'s Stage) -> impl Iterator<Item = &'s str> {
+        stage
+            .arg_defaults
+            .keys()
+            .map(String::as_str)
+            .chain(self.global_arg_names())
+    }
+
+    fn collect_entries(&self, keywords: &[&str]) -> Vec<InstructionEntry<'a>> {
         let mut entries = Vec::new();
         for (stage_index, stage) in self.model.stages.iter().enumerate() {
             for instruction in &stage.instructions {
-                if instruction.keyword == "RUN" {
-                    let run_index = entries.len();
-                    entries.push(RunEntry {
-                        run_index,
+                if keywords.contains(&instruction.keyword.as_str()) {
+                    entries.push(InstructionEntry {
+                        index: entries.len(),
                         stage_index,
                         stage_name: stage.name.as_deref(),
                         instruction,
@@ -916,11 +1722,16 @@ impl<'a> Evaluator<'a> {
         entries
     }
 
-    fn select_stage_indices(&self, selector: &Index, path: &str) -> Result<Vec<usize>, Error> {
+    fn select_stage_indices(
+        &self,
+        selector: &Index,
+        rest: &[Segment],
+        path: &str,
+    ) -> Result<Vec<usize>, Error> {
         match selector {
             Index::Position(index) => {
                 if *index >= self.model.stages.len() {
-                    return Err(Error::not_found(path.to_string()));
+                    return Err(self.not_found_at(path, rest.len() + 1, Lookup::Index));
                 }
                 Ok(vec![*index])
             }
@@ -941,7 +1752,15 @@ impl<'a> Evaluator<'a> {
                     .collect();
 
                 if matches.is_empty() {
-                    return Err(Error::not_found(path.to_string()));
+                    return Err(self
+                        .not_found_at(path, rest.len() + 1, Lookup::Stage)
+                        .with_suggestion(suggest(
+                            name,
+                            self.model
+                                .stages
+                                .iter()
+                                .filter_map(|stage| stage.name.as_deref()),
+                        )));
                 }
                 if matches.len() > 1 {
                     return Err(Error::eval(format!(
@@ -955,6 +1774,96 @@ impl<'a> Evaluator<'a> {
     }
 }
 
+pub(crate) const ROOT_FIELDS: &[&str] = &[
+    "ARG",
+    "FROM",
+    "STAGE",
+    "RUN",
+    "COPY",
+    "PACKAGES",
+    "INSTRUCTION",
+    "COMMENTS",
+    "DIRECTIVES",
+    "CONTEXT",
+    "RESOLVE",
+    "IGNORED",
+    "EXISTS",
+    "HAS",
+];
+pub(crate) const ARG_FIELDS: &[&str] = &["DEFAULT", "SET"];
+pub(crate) const PARENT_FIELDS: &[&str] = &["RAW", "RESOLVED", "KIND", "REGISTRY", "STAGE"];
+pub(crate) const RUN_COLLECTION_FIELDS: &[&str] = &[
+    "COUNT",
+    "RAW",
+    "GREP",
+    "CONTAINS",
+    "COMMAND",
+    "KEYWORD",
+    "INDEX",
+    "STAGE",
+    "STAGE_NAME",
+    "SPAN",
+];
+pub(crate) const RUN_FIELDS: &[&str] = &[
+    "RAW",
+    "COMMAND",
+    "KEYWORD",
+    "INDEX",
+    "STAGE",
+    "STAGE_NAME",
+    "SPAN",
+];
+pub(crate) const COPY_COLLECTION_FIELDS: &[&str] = &[
+    "COUNT",
+    "RAW",
+    "KEYWORD",
+    "SOURCES",
+    "DEST",
+    "FROM",
+    "SOURCES_MATCHED",
+    "MISSING",
+    "INDEX",
+    "STAGE",
+    "STAGE_NAME",
+    "SPAN",
+];
+pub(crate) const COPY_FIELDS: &[&str] = &[
+    "RAW",
+    "KEYWORD",
+    "SOURCES",
+    "DEST",
+    "FROM",
+    "SOURCES_MATCHED",
+    "MISSING",
+    "INDEX",
+    "STAGE",
+    "STAGE_NAME",
+    "SPAN",
+];
+pub(crate) const PACKAGE_FIELDS: &[&str] = &["MANAGER", "NAME", "VERSION", "STAGE", "RUN_INDEX"];
+pub(crate) const PACKAGES_COLLECTION_FIELDS: &[&str] =
+    &["COUNT", "MANAGER", "NAME", "VERSION", "STAGE", "RUN_INDEX"];
+pub(crate) const CONTEXT_FIELDS: &[&str] = &["FILES"];
+pub(crate) const STAGES_FIELDS: &[&str] = &["COUNT"];
+pub(crate) const STAGE_FIELDS: &[&str] = &["NAME", "ARG", "PARENT", "INSTRUCTIONS", "RESOLVE"];
+pub(crate) const INSTRUCTIONS_FIELDS: &[&str] = &["COUNT"];
+pub(crate) const INSTRUCTION_FIELDS: &[&str] = &["RAW", "KEYWORD", "SPAN", "COMMENT"];
+pub(crate) const INSTRUCTION_COLLECTION_FIELDS: &[&str] =
+    &["COUNT", "RAW", "KEYWORD", "SPAN", "COMMENT"];
+pub(crate) const COMMENT_FIELDS: &[&str] = &["TEXT", "LINE", "INSTRUCTION"];
+pub(crate) const COMMENTS_COLLECTION_FIELDS: &[&str] = &["COUNT", "TEXT", "LINE", "INSTRUCTION"];
+pub(crate) const DIRECTIVE_FIELDS: &[&str] = &["SYNTAX", "ESCAPE", "CHECK"];
+
+const COLLECTION_ROOTS: &[&str] = &[
+    "FROM",
+    "RUN",
+    "COPY",
+    "STAGE",
+    "INSTRUCTION",
+    "COMMENTS",
+    "PACKAGES",
+];
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 enum ResolveScope {
     Global,
@@ -977,8 +1886,8 @@ impl ResolveKey {
 }
 
 #[derive(Clone, Copy)]
-struct RunEntry<'a> {
-    run_index: usize,
+struct InstructionEntry<'a> {
+    index: usize,
     stage_index: usize,
     stage_name: Option<&'a str>,
     instruction: &'a Instruction,
@@ -992,7 +1901,7 @@ fn is_var_continue(byte: u8) -> bool {
     byte.is_ascii_alphanumeric() || byte == b'_'
 }
 
-fn is_valid_var_name(name: &str) -> bool {
+pub(crate) fn is_valid_var_name(name: &str) -> bool {
     let mut bytes = name.bytes();
     let Some(first) = bytes.next() else {
         return false;
@@ -1011,6 +1920,20 @@ fn parent_raw(parent: &Parent) -> String {
     }
 }
 
+fn image_registry(image: &str) -> &str {
+    match image.split_once('/') {
+        Some((host, _)) if host.contains(['.', ':']) || host == "localhost" => host,
+        _ => "docker.io",
+    }
+}
+
+fn parent_fields(parent: &Parent) -> impl Iterator<Item = &'static str> + '_ {
+    PARENT_FIELDS
+        .iter()
+        .copied()
+        .filter(move |field| *field != "STAGE" || matches!(parent, Parent::StageRef(_)))
+}
+
 fn parent_kind(parent: &Parent) -> &'static str {
     match parent {
         Parent::Image(_) => "image",
@@ -1028,6 +1951,180 @@ fn function_single_string_arg<'a>(args: &'a [Arg], name: &str) -> Result<&'a str
     }
 }
 
+fn locate(error: Error, query: &Query) -> Error {
+    let Error::NotFound {
+        segment: Some(index),
+        span: None,
+        ..
+    } = error
+    else {
+        return error;
+    };
+    let span = query.spans.get(index).copied();
+    error.with_segment(Some(index), span)
+}
+
+fn trailing_len(rest: &[Segment], fields: &[&str]) -> usize {
+    match rest.first() {
+        None => 1,
+        Some(Segment::Ident(field)) if fields.contains(&field.as_str()) => rest.len() - 1,
+        Some(_) => rest.len(),
+    }
+}
+
+fn segment_suggestion<S: AsRef<str>>(
+    segment: &Segment,
+    candidates: impl IntoIterator<Item = S>,
+) -> Option<String> {
+    match segment {
+        Segment::Ident(ident)
+        | Segment::Indexed { ident, .. }
+        | Segment::Function { ident, .. } => suggest(ident, candidates),
+        _ => None,
+    }
+}
+
+fn suggest<S: AsRef<str>>(name: &str, candidates: impl IntoIterator<Item = S>) -> Option<String> {
+    let needle = name.to_ascii_uppercase();
+    let threshold = (needle.len() / 3).max(1);
+    let mut best: Option<(usize, String)> = None;
+
+    for candidate in candidates {
+        let candidate = candidate.as_ref();
+        if candidate == name {
+            continue;
+        }
+        let upper = candidate.to_ascii_uppercase();
+        let distance = edit_distance(&needle, &upper);
+        let score = if distance <= threshold {
+            distance
+        } else if needle.len() >= 2 && is_abbreviation(&needle, &upper) {
+            upper.len() - needle.len()
+        } else {
+            continue;
+        };
+        if best
+            .as_ref()
+            .is_none_or(|(best_score, _)| score < *best_score)
+        {
+            best = Some((score, candidate.to_string()));
+        }
+    }
+
+    best.map(|(_, candidate)| candidate)
+}
+
+fn edit_distance(left: &str, right: &str) -> usize {
+    let left: Vec<char> = left.chars().collect();
+    let right: Vec<char> = right.chars().collect();
+    let mut rows = vec![vec![0; right.len() + 1]; left.len() + 1];
+    rows[0] = (0..=right.len()).collect();
+    for (i, row) in rows.iter_mut().enumerate() {
+        row[0] = i;
+    }
+
+    for i in 1..=left.len() {
+        for j in 1..=right.len() {
+            let cost = usize::from(left[i - 1] != right[j - 1]);
+            let mut value = (rows[i - 1][j] + 1)
+                .min(rows[i][j - 1] + 1)
+                .min(rows[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && left[i - 1] == right[j - 2] && left[i - 2] == right[j - 1] {
+                value = value.min(rows[i - 2][j - 2] + 1);
+            }
+            rows[i][j] = value;
+        }
+    }
+    rows[left.len()][right.len()]
+}
+
+/// `CMD` abbreviates `COMMAND`: same first letter, remaining letters in order.
+fn is_abbreviation(needle: &str, name: &str) -> bool {
+    let mut name = name.chars();
+    let mut needle = needle.chars();
+    needle.next() == name.next() && needle.all(|ch| name.any(|candidate| candidate == ch))
+}
+
+fn is_composite(segment: &Segment) -> bool {
+    match segment {
+        Segment::Object(_) | Segment::Array(_) | Segment::Alternative(_) | Segment::Literal(_) => {
+            true
+        }
+        Segment::Function { ident, .. } => ident == "EXISTS" || ident == "HAS",
+        _ => false,
+    }
+}
+
+fn eval_composite(
+    rest: &[Segment],
+    path: &str,
+    mut eval_member: impl FnMut(&[Segment]) -> Result<Value, Error>,
+) -> Result<Option<Value>, Error> {
+    let Some(first) = rest.first().filter(|segment| is_composite(segment)) else {
+        return Ok(None);
+    };
+    if rest.len() != 1 {
+        return Err(Error::not_found(path.to_string()));
+    }
+
+    match first {
+        Segment::Literal(value) => Ok(Some(value.clone())),
+        Segment::Alternative(options) => {
+            let mut last = Err(Error::not_found(path.to_string()));
+            for member in options {
+                last = eval_member(member);
+                match &last {
+                    Ok(Value::Null) | Err(Error::NotFound { .. }) => continue,
+                    _ => break,
+                }
+            }
+            last.map(Some)
+        }
+        Segment::Function { .. } => match existence_member(first)? {
+            Some(member) => exists(eval_member(&member)).map(Some),
+            None => Ok(None),
+        },
+        Segment::Object(fields) => {
+            let mut out = BTreeMap::new();
+            for (key, member) in fields {
+                out.insert(key.clone(), eval_member(member)?);
+            }
+            Ok(Some(Value::Object(out)))
+        }
+        Segment::Array(items) => {
+            let mut out = Vec::with_capacity(items.len());
+            for member in items {
+                out.push(eval_member(member)?);
+            }
+            Ok(Some(Value::Array(out)))
+        }
+        _ => Ok(None),
+    }
+}
+
+fn existence_member(segment: &Segment) -> Result<Option<Vec<Segment>>, Error> {
+    match segment {
+        Segment::Function { ident, args } if ident == "EXISTS" => match args.as_slice() {
+            [Arg::Path(member)] => Ok(Some(member.clone())),
+            [Arg::Ident(ident)] => Ok(Some(vec![Segment::Ident(ident.clone())])),
+            _ => Err(Error::eval("EXISTS requires exactly one path argument")),
+        },
+        Segment::Function { ident, args } if ident == "HAS" => {
+            let field = function_single_string_arg(args, "HAS")?;
+            Ok(Some(vec![Segment::Ident(field.to_string())]))
+        }
+        _ => Ok(None),
+    }
+}
+
+fn exists(result: Result<Value, Error>) -> Result<Value, Error> {
+    match result {
+        Ok(_) => Ok(Value::Bool(true)),
+        Err(Error::NotFound { .. }) => Ok(Value::Bool(false)),
+        Err(other) => Err(other),
+    }
+}
+
 fn stage_path(stage_index: usize, rest: &[Segment]) -> String {
     let mut out = format!("STAGE[{stage_index}]");
     for segment in rest {
@@ -1037,8 +2134,21 @@ fn stage_path(stage_index: usize, rest: &[Segment]) -> String {
     out
 }
 
-fn run_path(run_index: usize, rest: &[Segment]) -> String {
-    let mut out = format!("RUN[{run_index}]");
+fn span_value(instruction: &Instruction) -> Value {
+    Value::Object(BTreeMap::from([
+        (
+            "start".to_string(),
+            Value::Number(instruction.start_line as i64),
+        ),
+        (
+            "end".to_string(),
+            Value::Number(instruction.end_line as i64),
+        ),
+    ]))
+}
+
+fn entry_path(namespace: &str, index: usize, rest: &[Segment]) -> String {
+    let mut out = format!("{namespace}[{index}]");
     for segment in rest {
         out.push('.');
         out.push_str(&segment_to_string(segment));
@@ -1075,17 +2185,55 @@ fn segment_to_string(segment: &Segment) -> String {
                     Arg::String(value) => format!("\"{value}\""),
                     Arg::Ident(value) => value.clone(),
                     Arg::Number(value) => value.to_string(),
+                    Arg::Path(member) => path_to_string(member),
                 })
                 .collect();
             format!("{ident}({})", rendered_args.join(", "))
         }
+        Segment::Object(fields) => {
+            let rendered_fields: Vec<String> = fields
+                .iter()
+                .map(|(key, member)| {
+                    if is_valid_var_name(key) {
+                        format!("{key}: {}", path_to_string(member))
+                    } else {
+                        format!("\"{key}\": {}", path_to_string(member))
+                    }
+                })
+                .collect();
+            format!("{{{}}}", rendered_fields.join(", "))
+        }
+        Segment::Array(items) => {
+            let rendered_items: Vec<String> =
+                items.iter().map(|item| path_to_string(item)).collect();
+            format!("[{}]", rendered_items.join(", "))
+        }
+        Segment::Alternative(options) => {
+            let rendered_options: Vec<String> = options
+                .iter()
+                .map(|option| path_to_string(option))
+                .collect();
+            format!("({})", rendered_options.join(" // "))
+        }
+        Segment::Literal(value) => value.to_json_string(),
     }
 }
 
+fn path_to_string(segments: &[Segment]) -> String {
+    segments
+        .iter()
+        .map(segment_to_string)
+        .collect::<Vec<_>>()
+        .join(".")
+}
+
 #[cfg(test)]
 mod tests {
     use std::collections::BTreeMap;
 
+    use crate::context::BuildContext;
+    use crate::dockerignore::DockerIgnore;
+    use crate::error::{Error, Lookup, Span};
     use crate::eval::Evaluator;
     use crate::parser::parse_dockerfile;
     use crate::query::parse_query;
@@ -1120,6 +2268,57 @@ mod tests {
         assert_eq!(result.meta.missing_paths.len(), 2);
     }
 
+    #[test]
+    fn copy_sources_are_matched_against_the_build_context() {
+        let dockerfile = "FROM golang AS build\n\
+                          ARG APP=api\n\
+                          COPY go.mod go.sum ./\n\
+                          COPY cmd/${APP} ./cmd/\n\
+                          ADD https://example.com/ca.pem /etc/ssl/\n\
+                          FROM scratch\n\
+                          COPY --from=build /out/app /app\n";
+        let model = parse_dockerfile(dockerfile).expect("dockerfile should parse");
+        let overrides = BTreeMap::new();
+        let context = BuildContext::new(
+            ["go.mod", "cmd/api/main.go", "cmd/cli/main.go", "notes.md"].map(str::to_string),
+            DockerIgnore::parse("*.md\n").expect("dockerignore should parse"),
+        );
+        let evaluator = Evaluator::new(&model, &overrides, false).with_context(Some(&context));
+        let evaluate = |source: &str| {
+            let query = parse_query(source).expect("query should parse");
+            evaluator
+                .evaluate(&query)
+                .expect("query should evaluate")
+                .value
+                .to_json_string()
+        };
+
+        assert_eq!(evaluate("COPY.COUNT"), "4");
+        assert_eq!(evaluate("COPY[1].SOURCES"), r#"["cmd/api"]"#);
+        assert_eq!(
+            evaluate("COPY[*].SOURCES_MATCHED"),
+            r#"[["go.mod"],["cmd/api/main.go"],[],[]]"#
+        );
+        assert_eq!(evaluate("COPY[*].MISSING"), r#"[["go.sum"],[],[],[]]"#);
+        assert_eq!(evaluate("COPY.MISSING"), r#"["go.sum"]"#);
+        assert_eq!(evaluate("COPY[3].FROM"), r#""build""#);
+        assert_eq!(
+            evaluate("CONTEXT.FILES"),
+            r#"["cmd/api/main.go","cmd/cli/main.go","go.mod"]"#
+        );
+        assert_eq!(evaluate(r#"IGNORED("docs.md")"#), "true");
+        assert_eq!(evaluate(r#"IGNORED("docs/a.md")"#), "false");
+
+        let query = parse_query("COPY[0].MISSING").expect("query should parse");
+        let error = Evaluator::new(&model, &overrides, false)
+            .evaluate(&query)
+            .expect_err("context is required");
+        assert_eq!(
+            error.to_string(),
+            "evaluation error: MISSING requires a build context"
+        );
+    }
+
     #[test]
     fn run_queries_work_for_wildcard_and_index() {
         let dockerfile = "FROM alpine AS base\nRUN echo one\nRUN echo two\n";
@@ -1144,6 +2343,68 @@ mod tests {
         );
     }
 
+    #[test]
+    fn comments_and_directives_are_queryable() {
+        let dockerfile = "# syntax=docker/dockerfile:1.7\n\n# base image\nFROM alpine\n# install sudo\n# as root\nRUN apk add sudo\n\n# add user\n\nRUN adduser -D app\n# trailing\n";
+        let model = parse_dockerfile(dockerfile).expect("dockerfile should parse");
+        let overrides = BTreeMap::new();
+        let evaluator = Evaluator::new(&model, &overrides, false);
+        let eval = |query: &str| {
+            evaluator
+                .evaluate(&parse_query(query).expect("query should parse"))
+                .expect("query should evaluate")
+                .value
+                .to_json_string()
+        };
+
+        assert_eq!(eval("DIRECTIVES"), "{\"syntax\":\"docker/dockerfile:1.7\"}");
+        assert_eq!(eval("DIRECTIVES.syntax"), "\"docker/dockerfile:1.7\"");
+        assert_eq!(eval("DIRECTIVES.ESCAPE"), "null");
+        assert_eq!(eval("COMMENTS.COUNT"), "5");
+        assert_eq!(
+            eval("COMMENTS[*].{line: LINE, before: INSTRUCTION}"),
+            "[{\"before\":\"FROM alpine\",\"line\":3},{\"before\":\"RUN apk add sudo\",\"line\":5},{\"before\":\"RUN apk add sudo\",\"line\":6},{\"before\":\"RUN adduser -D app\",\"line\":9},{\"before\":null,\"line\":12}]"
+        );
+        assert_eq!(
+            eval("INSTRUCTION[*].COMMENT"),
+            "[\"base image\",\"install sudo\\nas root\",null]"
+        );
+        assert_eq!(
+            eval("STAGE[0].INSTRUCTIONS[0].COMMENT"),
+            "\"install sudo\\nas root\""
+        );
+
+        let unknown = parse_query("DIRECTIVES.SYNTAXX").expect("query should parse");
+        let error = evaluator.evaluate(&unknown).expect_err("unknown directive");
+        assert!(error.to_string().contains("did you mean 'SYNTAX'?"));
+    }
+
+    #[test]
+    fn packages_list_installs_from_run_commands() {
+        let dockerfile = "FROM alpine AS build\nRUN apk add --no-cache curl=8.9.1-r1 git\nFROM python:3.12\nRUN echo hi\nRUN pip install pkg==1.2\n";
+        let model = parse_dockerfile(dockerfile).expect("dockerfile should parse");
+        let overrides = BTreeMap::new();
+        let evaluator = Evaluator::new(&model, &overrides, false);
+        let eval = |query: &str| {
+            evaluator
+                .evaluate(&parse_query(query).expect("query should parse"))
+                .expect("query should evaluate")
+                .value
+                .to_json_string()
+        };
+
+        assert_eq!(eval("PACKAGES.COUNT"), "3");
+        assert_eq!(
+            eval("PACKAGES[0]"),
+            "{\"manager\":\"apk\",\"name\":\"curl\",\"run_index\":0,\"stage\":0,\"version\":\"8.9.1-r1\"}"
+        );
+        assert_eq!(eval("PACKAGES[*].VERSION"), "[\"8.9.1-r1\",null,\"1.2\"]");
+        assert_eq!(
+            eval("PACKAGES[2].{name: NAME, run: RUN_INDEX, stage: STAGE}"),
+            "{\"name\":\"pkg\",\"run\":2,\"stage\":1}"
+        );
+    }
+
     #[test]
     fn run_directives_filter_and_count() {
         let dockerfile = "FROM alpine\nRUN apk add curl\nRUN echo done\n";
@@ -1182,4 +2443,196 @@ mod tests {
             Some("1".to_string())
         );
     }
+
+    #[test]
+    fn projections_build_objects_and_arrays() {
+        let dockerfile = "ARG VERSION=1\nFROM alpine:${VERSION} AS build\nRUN make\nFROM build\n";
+        let model = parse_dockerfile(dockerfile).expect("dockerfile should parse");
+        let overrides = BTreeMap::new();
+        let evaluator = Evaluator::new(&model, &overrides, false);
+
+        let stages =
+            parse_query("STAGE[*].{name: NAME, base: PARENT.RESOLVED, runs: INSTRUCTIONS.COUNT}")
+                .expect("query should parse");
+        let stages_result = evaluator.evaluate(&stages).expect("query should evaluate");
+        assert_eq!(
+            stages_result.value.to_json_string(),
+            "[{\"base\":\"alpine:1\",\"name\":\"build\",\"runs\":1},{\"base\":\"build\",\"name\":null,\"runs\":0}]"
+        );
+
+        let array = parse_query("[ARG.VERSION, FROM[0].RESOLVED]").expect("query should parse");
+        let array_result = evaluator.evaluate(&array).expect("query should evaluate");
+        assert_eq!(array_result.value.to_json_string(), "[\"1\",\"alpine:1\"]");
+        assert!(array_result.meta.used_vars.contains("VERSION"));
+    }
+
+    #[test]
+    fn not_found_errors_suggest_valid_fields() {
+        let dockerfile = "ARG VERSION=1\nFROM alpine AS builder\nRUN make\n";
+        let model = parse_dockerfile(dockerfile).expect("dockerfile should parse");
+        let overrides = BTreeMap::new();
+        let evaluator = Evaluator::new(&model, &overrides, false);
+
+        let cases = [
+            ("STAGE[0].PARNET.RAW", "PARENT"),
+            ("RUN[0].CMD", "COMMAND"),
+            ("ARG.VERSOIN", "VERSION"),
+            ("STAGE[\"bulider\"].NAME", "builder"),
+            ("STAGES", "STAGE"),
+            ("RUN.CONUT", "COUNT"),
+        ];
+        for (source, expected) in cases {
+            let query = parse_query(source).expect("query should parse");
+            match evaluator.evaluate(&query) {
+                Err(Error::NotFound { suggestion, .. }) => {
+                    assert_eq!(suggestion.as_deref(), Some(expected), "{source}");
+                }
+                other => panic!("{source} should be not found, got {other:?}"),
+            }
+        }
+
+        for source in [
+            "FROM[0].TAG",
+            "FROM[0].STAG",
+            "STAGE[0].PARENT.STAGES",
+            "RUN.X",
+        ] {
+            let query = parse_query(source).expect("query should parse");
+            match evaluator.evaluate(&query) {
+                Err(Error::NotFound { suggestion, .. }) => {
+                    assert_eq!(suggestion, None, "{source}");
+                }
+                other => panic!("{source} should be not found, got {other:?}"),
+            }
+        }
+    }
+
+    #[test]
+    fn not_found_errors_identify_the_failing_segment() {
+        let dockerfile = "ARG VERSION=1\nFROM alpine AS builder\nARG VERSION\nRUN make\n";
+        let model = parse_dockerfile(dockerfile).expect("dockerfile should parse");
+        let overrides = BTreeMap::new();
+        let evaluator = Evaluator::new(&model, &overrides, false);
+
+        let cases = [
+            (
+                "STAGE[\"builder\"].ARG.VERSON.DEFAULT",
+                Lookup::Arg,
+                2,
+                Span::new(21, 27),
+            ),
+            ("STAGE[\"nope\"].NAME", Lookup::Stage, 0, Span::new(0, 13)),
+            ("FROM[3].RAW", Lookup::Index, 0, Span::new(0, 7)),
+            ("STAGE[0].NAME.EXTRA", Lookup::Field, 2, Span::new(14, 19)),
+            ("RUN.CMD", Lookup::Field, 1, Span::new(4, 7)),
+            (
+                "STAGE[0].{name: NAMEX}",
+                Lookup::Field,
+                2,
+                Span::new(16, 21),
+            ),
+            (
+                "[ARG.VERSION, FROM[0].TAG]",
+                Lookup::Field,
+                4,
+                Span::new(22, 25),
+            ),
+            ("STAGE[0].{x: PARNET}", Lookup::Field, 2, Span::new(13, 19)),
+            (
+                "STAGE[0].{x: (NAMEX // PARNET)}",
+                Lookup::Field,
+                4,
+                Span::new(23, 29),
+            ),
+        ];
+        for (source, expected_lookup, expected_segment, expected_span) in cases {
+            let query = parse_query(source).expect("query should parse");
+            match evaluator.evaluate(&query) {
+                Err(Error::NotFound {
+                    lookup,
+                    segment,
+                    span,
+                    ..
+                }) => {
+                    assert_eq!(lookup, expected_lookup, "{source}");
+                    assert_eq!(segment, Some(expected_segment), "{source}");
+                    assert_eq!(span, Some(expected_span), "{source}");
+                }
+                other => panic!("{source} should be not found, got {other:?}"),
+            }
+        }
+    }
+
+    #[test]
+    fn alternatives_and_existence_checks_substitute_defaults() {
+        let dockerfile =
+            "ARG VERSION=1\nFROM alpine AS a\nARG FOO=bar\nRUN true\nFROM alpine AS b\n";
+        let model = parse_dockerfile(dockerfile).expect("dockerfile should parse");
+        let overrides = BTreeMap::new();
+        let evaluator = Evaluator::new(&model, &overrides, false);
+
+        let fallback = parse_query("STAGE[1].ARG.FOO // \"fallback\"").expect("query should parse");
+        let fallback_result = evaluator
+            .evaluate(&fallback)
+            .expect("query should evaluate");
+        assert_eq!(
+            fallback_result.value.render_scalar(),
+            Some("fallback".to_string())
+        );
+
+        let per_stage =
+            parse_query("STAGE[*].(ARG.FOO // ARG.VERSION)").expect("query should parse");
+        let per_stage_result = evaluator
+            .evaluate(&per_stage)
+            .expect("query should evaluate");
+        assert_eq!(per_stage_result.value.to_json_string(), "[\"bar\",\"1\"]");
+        assert!(per_stage_result.meta.missing_paths.is_empty());
+
+        let exists = parse_query("[EXISTS(STAGE[0].ARG.FOO), STAGE[1].ARG.HAS(\"FOO\")]")
+            .expect("query should parse");
+        let exists_result = evaluator.evaluate(&exists).expect("query should evaluate");
+        assert_eq!(exists_result.value.to_json_string(), "[true,false]");
+
+        let collections = parse_query(
+            "[RUN.HAS(\"COUNT\"), RUN.HAS(\"NOPE\"), STAGE.HAS(\"COUNT\"), FROM.EXISTS(COUNT), COPY.HAS(\"COUNT\"), STAGE.HAS(\"NAME\")]",
+        )
+        .expect("query should parse");
+        let collections_result = evaluator
+            .evaluate(&collections)
+            .expect("query should evaluate");
+        assert_eq!(
+            collections_result.value.to_json_string(),
+            "[true,false,true,true,true,false]"
+        );
+
+        for query in [
+            "STAGE[*].EXISTS(ARG.FOO)",
+            "STAGE[*].(EXISTS(ARG.FOO))",
+            "STAGE[*].(ARG.NOPE // EXISTS(ARG.FOO))",
+        ] {
+            let per_item = parse_query(query).expect("query should parse");
+            let per_item_result = evaluator
+                .evaluate(&per_item)
+                .expect("query should evaluate");
+            assert_eq!(
+                per_item_result.value.to_json_string(),
+                "[true,false]",
+                "{query}"
+            );
+        }
+        let projected = parse_query("STAGE[*].{f: EXISTS(ARG.FOO)}").expect("query should parse");
+        let projected_result = evaluator
+            .evaluate(&projected)
+            .expect("query should evaluate");
+        assert_eq!(
+            projected_result.value.to_json_string(),
+            "[{\"f\":true},{\"f\":false}]"
+        );
+
+        let missing = parse_query("ARG.NOPE // STAGE[5].NAME").expect("query should parse");
+        assert!(matches!(
+            evaluator.evaluate(&missing),
+            Err(crate::error::Error::NotFound { .. })
+        ));
+    }
 }
@@ -4,11 +4,13 @@ use crate::error::Error;
 #[repr(i32)]
 pub enum ExitCode {
     Success = 0,
+    Lint = 1,
     QueryParse = 2,
     DockerfileParse = 3,
     NotFound = 4,
     Eval = 5,
     Io = 6,
+    Policy = 7,
     Usage = 64,
 }
 
@@ -26,6 +28,7 @@ impl From<&Error> for ExitCode {
             Error::NotFound { .. } => Self::NotFound,
             Error::Eval { .. } => Self::Eval,
             Error::Io { .. } => Self::Io,
+            Error::Config { .. } | Error::Template { .. } => Self::Usage,
         }
     }
 }
@@ -50,6 +53,14 @@ mod tests {
             ExitCode::NotFound
         );
         assert_eq!(ExitCode::from(&Error::eval("cycle")), ExitCode::Eval);
+        assert_eq!(
+            ExitCode::from(&Error::template("unterminated action")),
+            ExitCode::Usage
+        );
+        assert_eq!(
+            ExitCode::from(&Error::config("unknown bake target")),
+            ExitCode::Usage
+        );
         assert_eq!(
             ExitCode::from(&Error::io(std::io::Error::from(std::io::ErrorKind::Other))),
             ExitCode::Io
@@ -0,0 +1,133 @@
+pub fn is_glob(pattern: &str) -> bool {
+    pattern.contains(['*', '?', '['])
+}
+
+pub fn glob_match(pattern: &str, path: &str) -> bool {
+    let pattern: Vec<&str> = pattern
+        .split('/')
+        .filter(|part| !part.is_empty() && *part != ".")
+        .collect();
+    let path: Vec<&str> = path
+        .split('/')
+        .filter(|part| !part.is_empty() && *part != ".")
+        .collect();
+    match_components(&pattern, &path)
+}
+
+/// Like [`glob_match`], but also true when the pattern matches one of the
+/// path's parent directories, the way `.dockerignore` and COPY sources treat
+/// a matched directory as covering everything below it.
+pub fn glob_match_or_parent(pattern: &str, path: &str) -> bool {
+    glob_match(pattern, path)
+        || path
+            .match_indices('/')
+            .any(|(index, _)| glob_match(pattern, &path[..index]))
+}
+
+fn match_components(pattern: &[&str], path: &[&str]) -> bool {
+    match pattern.split_first() {
+        None => path.is_empty(),
+        Some((&"**", [])) => !path.is_empty(),
+        Some((&"**", rest)) => (0..=path.len()).any(|skip| match_components(rest, &path[skip..])),
+        Some((first, rest)) => path.split_first().is_some_and(|(name, remaining)| {
+            let pattern: Vec<char> = first.chars().collect();
+            let name: Vec<char> = name.chars().collect();
+            match_component(&pattern, &name) && match_components(rest, remaining)
+        }),
+    }
+}
+
+fn match_component(pattern: &[char], name: &[char]) -> bool {
+    match pattern.split_first() {
+        None => name.is_empty(),
+        Some(('*', rest)) => (0..=name.len()).any(|skip| match_component(rest, &name[skip..])),
+        Some(('?', rest)) => !name.is_empty() && match_component(rest, &name[1..]),
+        Some(('[', rest)) => {
+            let Some((&ch, remaining)) = name.split_first() else {
+                return false;
+            };
+            match match_class(rest, ch) {
+                Some((true, after)) => match_component(after, remaining),
+                Some((false, _)) => false,
+                None => ch == '[' && match_component(rest, remaining),
+            }
+        }
+        Some(('\\', rest)) if !rest.is_empty() => {
+            name.first() == Some(&rest[0]) && match_component(&rest[1..], &name[1..])
+        }
+        Some((literal, rest)) => name.first() == Some(literal) && match_component(rest, &name[1..]),
+    }
+}
+
+fn match_class(class: &[char], ch: char) -> Option<(bool, &[char])> {
+    let (negated, mut rest) = match class.split_first() {
+        Some(('!' | '^', rest)) => (true, rest),
+        _ => (false, class),
+    };
+    let mut matched = false;
+    let mut first = true;
+    loop {
+        match rest {
+            [']', after @ ..] if !first => return Some((matched != negated, after)),
+            ['\\', escaped, after @ ..] => {
+                matched |= *escaped == ch;
+                rest = after;
+            }
+            [low, '-', high, after @ ..] if *high != ']' => {
+                matched |= (*low..=*high).contains(&ch);
+                rest = after;
+            }
+            [single, after @ ..] => {
+                matched |= *single == ch;
+                rest = after;
+            }
+            [] => return None,
+        }
+        first = false;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::glob::{glob_match, glob_match_or_parent, is_glob};
+
+    #[test]
+    fn matches_wildcards_within_components() {
+        assert!(glob_match("*.Dockerfile", "api.Dockerfile"));
+        assert!(!glob_match("*.Dockerfile", "svc/api.Dockerfile"));
+        assert!(glob_match(
+            "services/*/Dockerfile",
+            "services/api/Dockerfile"
+        ));
+        assert!(glob_match("Dockerfile.?", "Dockerfile.a"));
+        assert!(glob_match("Dockerfile.[a-c]", "Dockerfile.b"));
+        assert!(!glob_match("Dockerfile.[!a-c]", "Dockerfile.b"));
+        assert!(glob_match("literal\\*", "literal*"));
+        assert!(!is_glob("services/api/Dockerfile"));
+    }
+
+    #[test]
+    fn double_star_spans_directories() {
+        assert!(glob_match("**/Dockerfile", "Dockerfile"));
+        assert!(glob_match("./**/Dockerfile", "./a/Dockerfile"));
+        assert!(glob_match("**/Dockerfile", "a/b/c/Dockerfile"));
+        assert!(glob_match(
+            "apps/**/*.Dockerfile",
+            "apps/web/prod.Dockerfile"
+        ));
+        assert!(!glob_match(
+            "apps/**/*.Dockerfile",
+            "libs/web/prod.Dockerfile"
+        ));
+        assert!(glob_match("build/**", "build/out/app"));
+        assert!(!glob_match("build/**", "build"));
+    }
+
+    #[test]
+    fn matches_parent_directories() {
+        assert!(glob_match_or_parent("docs", "docs/a/b.md"));
+        assert!(glob_match_or_parent("/src/*", "src/lib/mod.rs"));
+        assert!(!glob_match_or_parent("src/*.rs", "src/lib/mod.rs"));
+        assert!(glob_match("[\\]]x", "]x"));
+    }
+}
@@ -1,13 +1,35 @@
+pub mod bake;
+pub mod complete;
+pub mod compose;
+pub mod context;
+pub mod dockerignore;
 pub mod error;
 pub mod eval;
 pub mod exit_code;
+pub mod glob;
+pub mod lint;
 pub mod model;
+mod packages;
 pub mod parser;
+pub mod policy;
 pub mod query;
+pub mod report;
+pub mod template;
+mod toml;
 pub mod value;
+mod yaml;
 
-pub use crate::error::{Error, Span};
+pub use crate::bake::{BakeTarget, load_bake};
+pub use crate::complete::{Completion, complete_query};
+pub use crate::compose::{ComposeBuild, load_compose};
+pub use crate::context::BuildContext;
+pub use crate::dockerignore::DockerIgnore;
+pub use crate::error::{Error, Lookup, Span};
 pub use crate::eval::{EvalMeta, EvalResult, Evaluator, Scope};
 pub use crate::exit_code::ExitCode;
-pub use crate::model::{DockerfileModel, Instruction, Parent, Stage};
+pub use crate::lint::{Finding, RuleInfo, Severity, lint};
+pub use crate::model::{Comment, DockerfileModel, Instruction, Parent, Stage};
+pub use crate::policy::{Policy, PolicyRule, RuleOutcome};
+pub use crate::report::{Report, ReportResult, ReportRule};
+pub use crate::template::Template;
 pub use crate::value::Value;
@@ -0,0 +1,770 @@
+use std::collections::BTreeMap;
+
+use crate::context::{is_remote, parse_copy};
+use crate::error::Error;
+use crate::eval::{Evaluator, is_valid_var_name};
+use crate::model::{DockerfileModel, Instruction, Parent};
+use crate::query::parse_query;
+use crate::toml::parse_toml;
+use crate::value::Value;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Info,
+    Warning,
+    Error,
+}
+
+impl Severity {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Info => "info",
+            Self::Warning => "warning",
+            Self::Error => "error",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RuleInfo {
+    pub id: &'static str,
+    pub name: &'static str,
+    pub severity: Severity,
+    pub description: &'static str,
+}
+
+const UNPINNED_BASE_IMAGE: RuleInfo = RuleInfo {
+    id: "DFQ001",
+    name: "unpinned-base-image",
+    severity: Severity::Warning,
+    description: "FROM uses an image without a tag or with the latest tag",
+};
+
+const APT_INSTALL_RECOMMENDS: RuleInfo = RuleInfo {
+    id: "DFQ002",
+    name: "apt-install-recommends",
+    severity: Severity::Warning,
+    description: "apt-get install runs without --no-install-recommends",
+};
+
+const APT_LISTS_NOT_CLEANED: RuleInfo = RuleInfo {
+    id: "DFQ003",
+    name: "apt-lists-not-cleaned",
+    severity: Severity::Warning,
+    description: "apt-get install runs without removing /var/lib/apt/lists in the same RUN",
+};
+
+const ADD_REMOTE_URL: RuleInfo = RuleInfo {
+    id: "DFQ004",
+    name: "add-remote-url",
+    severity: Severity::Warning,
+    description: "ADD downloads a URL without --checksum",
+};
+
+const FINAL_USER_ROOT: RuleInfo = RuleInfo {
+    id: "DFQ005",
+    name: "final-user-root",
+    severity: Severity::Warning,
+    description: "the final stage runs as root",
+};
+
+const MISSING_HEALTHCHECK: RuleInfo = RuleInfo {
+    id: "DFQ006",
+    name: "missing-healthcheck",
+    severity: Severity::Info,
+    description: "the final stage has no HEALTHCHECK",
+};
+
+const MULTIPLE_CMD: RuleInfo = RuleInfo {
+    id: "DFQ007",
+    name: "multiple-cmd",
+    severity: Severity::Warning,
+    description: "a stage has more than one CMD; only the last takes effect",
+};
+
+const CD_IN_RUN: RuleInfo = RuleInfo {
+    id: "DFQ008",
+    name: "cd-in-run",
+    severity: Severity::Info,
+    description: "RUN changes directory with cd instead of WORKDIR",
+};
+
+const UNUSED_ARG: RuleInfo = RuleInfo {
+    id: "DFQ009",
+    name: "unused-arg",
+    severity: Severity::Info,
+    description: "an ARG is declared but never referenced",
+};
+
+const DUPLICATE_STAGE_NAME: RuleInfo = RuleInfo {
+    id: "DFQ010",
+    name: "duplicate-stage-name",
+    severity: Severity::Error,
+    description: "two stages share a name, which makes references ambiguous",
+};
+
+pub const RULES: &[RuleInfo] = &[
+    UNPINNED_BASE_IMAGE,
+    APT_INSTALL_RECOMMENDS,
+    APT_LISTS_NOT_CLEANED,
+    ADD_REMOTE_URL,
+    FINAL_USER_ROOT,
+    MISSING_HEALTHCHECK,
+    MULTIPLE_CMD,
+    CD_IN_RUN,
+    UNUSED_ARG,
+    DUPLICATE_STAGE_NAME,
+];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Finding {
+    pub rule: &'static RuleInfo,
+    pub start_line: usize,
+    pub end_line: usize,
+    pub message: String,
+    pub instruction: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Baseline {
+    entries: Vec<BaselineEntry>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct BaselineEntry {
+    path: String,
+    rule: String,
+    line: usize,
+    instruction: String,
+}
+
+impl Finding {
+    fn new(rule: &'static RuleInfo, instruction: &Instruction, message: impl Into<String>) -> Self {
+        Self {
+            rule,
+            start_line: instruction.start_line,
+            end_line: instruction.end_line,
+            message: message.into(),
+            instruction: instruction
+                .raw
+                .split_whitespace()
+                .collect::<Vec<_>>()
+                .join(" "),
+        }
+    }
+
+    pub fn to_value(&self) -> Value {
+        Value::Object(BTreeMap::from([
+            ("rule".to_string(), Value::String(self.rule.id.to_string())),
+            (
+                "name".to_string(),
+                Value::String(self.rule.name.to_string()),
+            ),
+            (
+                "severity".to_string(),
+                Value::String(self.rule.severity.as_str().to_string()),
+            ),
+            ("message".to_string(), Value::String(self.message.clone())),
+            (
+                "span".to_string(),
+                Value::Object(BTreeMap::from([
+                    ("start".to_string(), Value::Number(self.start_line as i64)),
+                    ("end".to_string(), Value::Number(self.end_line as i64)),
+                ])),
+            ),
+        ]))
+    }
+}
+
+impl Baseline {
+    pub fn parse(input: &str) -> Result<Self, Error> {
+        let mut root = parse_toml(input)?;
+        let findings = match root.remove("finding") {
+            Some(Value::Array(findings)) => findings,
+            None => Vec::new(),
+            Some(_) => return Err(Error::config("'finding' must be an array of tables")),
+        };
+        let entries = findings
+            .into_iter()
+            .enumerate()
+            .map(|(index, finding)| {
+                let field = |name: &str| match &finding {
+                    Value::Object(map) => match map.get(name) {
+                        Some(Value::String(value)) => Ok(value.clone()),
+                        _ => Err(Error::config(format!(
+                            "finding {}: missing string '{name}'",
+                            index + 1
+                        ))),
+                    },
+                    _ => Err(Error::config(format!(
+                        "finding {}: expected a table",
+                        index + 1
+                    ))),
+                };
+                let line = match &finding {
+                    Value::Object(map) => match map.get("line") {
+                        Some(Value::Number(line)) => *line as usize,
+                        _ => 0,
+                    },
+                    _ => 0,
+                };
+                Ok(BaselineEntry {
+                    path: field("path")?,
+                    rule: field("rule")?,
+                    line,
+                    instruction: field("instruction")?,
+                })
+            })
+            .collect::<Result<_, Error>>()?;
+        Ok(Self { entries })
+    }
+
+    pub fn from_findings<'f>(findings: impl IntoIterator<Item = (&'f str, &'f Finding)>) -> Self {
+        Self {
+            entries: findings
+                .into_iter()
+                .map(|(path, finding)| BaselineEntry {
+                    path: path.to_string(),
+                    rule: finding.rule.id.to_string(),
+                    line: finding.start_line,
+                    instruction: finding.instruction.clone(),
+                })
+                .collect(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn to_toml_string(&self) -> String {
+        let findings = self
+            .entries
+            .iter()
+            .map(|entry| {
+                Value::Object(BTreeMap::from([
+                    ("path".to_string(), Value::String(entry.path.clone())),
+                    ("rule".to_string(), Value::String(entry.rule.clone())),
+                    ("line".to_string(), Value::Number(entry.line as i64)),
+                    (
+                        "instruction".to_string(),
+                        Value::String(entry.instruction.clone()),
+                    ),
+                ]))
+            })
+            .collect();
+        let root = Value::Object(BTreeMap::from([(
+            "finding".to_string(),
+            Value::Array(findings),
+        )]));
+        format!(
+            "# dfq lint baseline: findings listed here are not reported\n{}",
+            root.to_toml_string().unwrap_or_default()
+        )
+    }
+
+    pub fn retain_new<'f>(&self, findings: Vec<(&'f str, Finding)>) -> Vec<(&'f str, Finding)> {
+        let mut used = vec![false; self.entries.len()];
+        findings
+            .into_iter()
+            .filter(|(path, finding)| {
+                let known = self.entries.iter().enumerate().position(|(index, entry)| {
+                    !used[index]
+                        && entry.path == *path
+                        && entry.rule == finding.rule.id
+                        && entry.instruction == finding.instruction
+                });
+                if let Some(index) = known {
+                    used[index] = true;
+                }
+                known.is_none()
+            })
+            .collect()
+    }
+}
+
+pub fn rule(id: &str) -> Option<&'static RuleInfo> {
+    RULES
+        .iter()
+        .find(|rule| rule.id.eq_ignore_ascii_case(id) || rule.name == id)
+}
+
+pub fn lint(model: &DockerfileModel, overrides: &BTreeMap<String, String>) -> Vec<Finding> {
+    let froms: Vec<&Instruction> = model
+        .raw_instructions
+        .iter()
+        .filter(|instruction| instruction.keyword == "FROM")
+        .collect();
+
+    let mut findings = Vec::new();
+    unpinned_base_images(model, overrides, &froms, &mut findings);
+    duplicate_stage_names(model, &froms, &mut findings);
+    unused_args(model, &mut findings);
+    for stage in &model.stages {
+        let cmds: Vec<&Instruction> = stage
+            .instructions
+            .iter()
+            .filter(|instruction| instruction.keyword == "CMD")
+            .collect();
+        if let Some((last, overridden)) = cmds.split_last() {
+            for cmd in overridden {
+                findings.push(Finding::new(
+                    &MULTIPLE_CMD,
+                    cmd,
+                    format!("CMD is overridden by the CMD on line {}", last.start_line),
+                ));
+            }
+        }
+        for instruction in &stage.instructions {
+            match instruction.keyword.as_str() {
+                "RUN" => run_rules(instruction, &mut findings),
+                "ADD" => {
+                    if instruction.raw.contains("--checksum=") {
+                        continue;
+                    }
+                    for source in parse_copy(&instruction.raw).sources {
+                        if is_remote(&source) && !source.starts_with("git") {
+                            findings.push(Finding::new(
+                                &ADD_REMOTE_URL,
+                                instruction,
+                                format!(
+                                    "ADD downloads {source} without --checksum; pin it with --checksum or fetch it in RUN"
+                                ),
+                            ));
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+    if let (Some(stage), Some(from)) = (model.stages.last(), froms.last()) {
+        let user = stage
+            .instructions
+            .iter()
+            .rfind(|instruction| instruction.keyword == "USER");
+        if let Some(user) = user {
+            let name = arguments(&user.raw).split(':').next().unwrap_or_default();
+            if name == "root" || name == "0" {
+                findings.push(Finding::new(
+                    &FINAL_USER_ROOT,
+                    user,
+                    "the final stage runs as root; switch to an unprivileged USER",
+                ));
+            }
+        }
+        if !stage
+            .instructions
+            .iter()
+            .any(|instruction| instruction.keyword == "HEALTHCHECK")
+        {
+            findings.push(Finding::new(
+                &MISSING_HEALTHCHECK,
+                from,
+                "the final stage has no HEALTHCHECK",
+            ));
+        }
+    }
+
+    suppress(model, &mut findings);
+    findings.sort_by(|left, right| {
+        (left.start_line, left.rule.id).cmp(&(right.start_line, right.rule.id))
+    });
+    findings
+}
+
+fn suppress(model: &DockerfileModel, findings: &mut Vec<Finding>) {
+    for comment in &model.comments {
+        let Some(directive) = comment.text.strip_prefix("dfq:") else {
+            continue;
+        };
+        let (kind, list) = directive
+            .split_once(char::is_whitespace)
+            .unwrap_or((directive, ""));
+        let listed: Vec<&str> = list
+            .split(|ch: char| ch == ',' || ch.is_whitespace())
+            .filter(|id| !id.is_empty())
+            .collect();
+        let ids: Vec<&str> = listed
+            .iter()
+            .filter_map(|id| rule(id))
+            .map(|rule| rule.id)
+            .collect();
+        let applies = |finding: &Finding| listed.is_empty() || ids.contains(&finding.rule.id);
+        match kind {
+            "ignore" | "ignore-next-line" => {
+                let Some(next) = model
+                    .raw_instructions
+                    .iter()
+                    .find(|instruction| instruction.start_line > comment.line)
+                else {
+                    continue;
+                };
+                findings.retain(|finding| {
+                    !(applies(finding)
+                        && (next.start_line..=next.end_line).contains(&finding.start_line))
+                });
+            }
+            "disable" => {
+                findings.retain(|finding| !(applies(finding) && finding.start_line > comment.line));
+            }
+            _ => {}
+        }
+    }
+}
+
+fn unpinned_base_images(
+    model: &DockerfileModel,
+    overrides: &BTreeMap<String, String>,
+    froms: &[&Instruction],
+    findings: &mut Vec<Finding>,
+) {
+    let evaluator = Evaluator::new(model, overrides, false);
+    for (index, (stage, from)) in model.stages.iter().zip(froms).enumerate() {
+        if !matches!(stage.parent, Parent::Image(_)) {
+            continue;
+        }
+        let Ok(query) = parse_query(&format!("FROM[{index}].RESOLVED")) else {
+            continue;
+        };
+        let Ok(result) = evaluator.evaluate(&query) else {
+            continue;
+        };
+        let Value::String(image) = result.value else {
+            continue;
+        };
+        if !result.meta.missing_vars.is_empty() || image == "scratch" || image.contains('@') {
+            continue;
+        }
+        let name = image.rsplit('/').next().unwrap_or_default();
+        match name.split_once(':') {
+            None => findings.push(Finding::new(
+                &UNPINNED_BASE_IMAGE,
+                from,
+                format!("base image {image} has no tag; pin a version or digest"),
+            )),
+            Some((_, "latest")) => findings.push(Finding::new(
+                &UNPINNED_BASE_IMAGE,
+                from,
+                format!("base image {image} uses the latest tag; pin a version or digest"),
+            )),
+            Some(_) => {}
+        }
+    }
+}
+
+fn duplicate_stage_names(
+    model: &DockerfileModel,
+    froms: &[&Instruction],
+    findings: &mut Vec<Finding>,
+) {
+    let mut seen: BTreeMap<String, usize> = BTreeMap::new();
+    for (stage, from) in model.stages.iter().zip(froms) {
+        let Some(name) = &stage.name else {
+            continue;
+        };
+        match seen.get(&name.to_ascii_lowercase()) {
+            Some(line) => findings.push(Finding::new(
+                &DUPLICATE_STAGE_NAME,
+                from,
+                format!("stage name {name} is already used on line {line}"),
+            )),
+            None => {
+                seen.insert(name.to_ascii_lowercase(), from.start_line);
+            }
+        }
+    }
+}
+
+fn unused_args(model: &DockerfileModel, findings: &mut Vec<Finding>) {
+    let global: Vec<&Instruction> = model
+        .raw_instructions
+        .iter()
+        .take_while(|instruction| instruction.keyword != "FROM")
+        .filter(|instruction| instruction.keyword == "ARG")
+        .collect();
+    for instruction in global {
+        for name in arg_names(&instruction.raw) {
+            let used = model.raw_instructions.iter().any(|other| {
+                (other.keyword == "FROM" && references(&other.raw, &name))
+                    || (other.keyword == "ARG"
+                        && !std::ptr::eq(other, instruction)
+                        && (arg_names(&other.raw).contains(&name) || references(&other.raw, &name)))
+            });
+            if !used {
+                findings.push(Finding::new(
+                    &UNUSED_ARG,
+                    instruction,
+                    format!("ARG {name} is never used by a FROM or redeclared in a stage"),
+                ));
+            }
+        }
+    }
+
+    for stage in &model.stages {
+        for (index, instruction) in stage.instructions.iter().enumerate() {
+            if instruction.keyword != "ARG" {
+                continue;
+            }
+            for name in arg_names(&instruction.raw) {
+                let used = stage.instructions[index + 1..]
+                    .iter()
+                    .any(|other| other.keyword == "RUN" || references(&other.raw, &name));
+                if !used {
+                    findings.push(Finding::new(
+                        &UNUSED_ARG,
+                        instruction,
+                        format!("ARG {name} is never referenced in its stage"),
+                    ));
+                }
+            }
+        }
+    }
+}
+
+fn run_rules(instruction: &Instruction, findings: &mut Vec<Finding>) {
+    let commands = shell_commands(arguments(&instruction.raw));
+    let installs_apt = commands.iter().any(|command| {
+        matches!(command.first().copied(), Some("apt-get" | "apt")) && command.contains(&"install")
+    });
+    if installs_apt {
+        let no_recommends = commands.iter().any(|command| {
+            command.iter().any(|token| {
+                *token == "--no-install-recommends"
+                    || token.contains("APT::Install-Recommends=false")
+            })
+        });
+        if !no_recommends {
+            findings.push(Finding::new(
+                &APT_INSTALL_RECOMMENDS,
+                instruction,
+                "apt-get install without --no-install-recommends",
+            ));
+        }
+        let cleaned = commands.iter().any(|command| {
+            command.first() == Some(&"rm")
+                && command
+                    .iter()
+                    .any(|token| token.starts_with("/var/lib/apt/lists"))
+        });
+        let cached = instruction.raw.contains("--mount=type=cache")
+            && instruction.raw.contains("/var/lib/apt");
+        if !cleaned && !cached {
+            findings.push(Finding::new(
+                &APT_LISTS_NOT_CLEANED,
+                instruction,
+                "apt-get install without rm -rf /var/lib/apt/lists/* in the same RUN",
+            ));
+        }
+    }
+    if commands
+        .iter()
+        .any(|command| command.first() == Some(&"cd"))
+    {
+        findings.push(Finding::new(
+            &CD_IN_RUN,
+            instruction,
+            "use WORKDIR instead of cd in RUN",
+        ));
+    }
+}
+
+pub(crate) fn shell_commands(script: &str) -> Vec<Vec<&str>> {
+    let mut commands = Vec::new();
+    let mut current = Vec::new();
+    for token in script.split_whitespace() {
+        if matches!(token, "&&" | "||" | ";" | "|" | "\\") {
+            if !current.is_empty() {
+                commands.push(std::mem::take(&mut current));
+            }
+            continue;
+        }
+        let (token, ends) = match token.strip_suffix(';') {
+            Some(token) => (token, true),
+            None => (token, false),
+        };
+        let wrapper = current.is_empty()
+            && (matches!(token, "sudo" | "(" | "{" | "then" | "do" | "else")
+                || (token.contains('=') && !token.starts_with('-')));
+        if !token.is_empty() && !wrapper {
+            current.push(token);
+        }
+        if ends && !current.is_empty() {
+            commands.push(std::mem::take(&mut current));
+        }
+    }
+    if !current.is_empty() {
+        commands.push(current);
+    }
+    commands
+}
+
+pub(crate) fn arguments(raw: &str) -> &str {
+    let raw = raw.trim_start();
+    let rest = raw
+        .find(char::is_whitespace)
+        .map_or("", |index| raw[index..].trim_start());
+    let mut rest = rest;
+    while rest.starts_with("--") {
+        rest = rest
+            .find(char::is_whitespace)
+            .map_or("", |index| rest[index..].trim_start());
+    }
+    rest.trim_end()
+}
+
+fn arg_names(raw: &str) -> Vec<String> {
+    arguments(raw)
+        .split_whitespace()
+        .filter_map(|token| {
+            let name = token.split('=').next().unwrap_or_default();
+            is_valid_var_name(name).then(|| name.to_string())
+        })
+        .collect()
+}
+
+fn references(text: &str, name: &str) -> bool {
+    text.match_indices('$').any(|(index, _)| {
+        let after = &text[index + 1..];
+        match after.strip_prefix('{') {
+            Some(inner) => inner
+                .strip_prefix(name)
+                .is_some_and(|rest| rest.starts_with(['}', ':'])),
+            None => after.strip_prefix(name).is_some_and(|rest| {
+                !rest.starts_with(|ch: char| ch.is_ascii_alphanumeric() || ch == '_')
+            }),
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use crate::lint::{Baseline, Finding, lint, shell_commands};
+    use crate::parser::parse_dockerfile;
+
+    fn findings(dockerfile: &str) -> Vec<(usize, &'static str)> {
+        let model = parse_dockerfile(dockerfile).expect("dockerfile should parse");
+        lint(&model, &BTreeMap::new())
+            .into_iter()
+            .map(|finding| (finding.start_line, finding.rule.id))
+            .collect()
+    }
+
+    #[test]
+    fn flags_each_built_in_rule() {
+        let dockerfile = "ARG UNUSED=1\n\
+                          FROM ubuntu AS build\n\
+                          RUN apt-get update && apt-get install -y curl\n\
+                          RUN cd /src && make\n\
+                          FROM alpine:latest AS build\n\
+                          ARG VERSION\n\
+                          ADD https://example.com/tool.tgz /tmp/\n\
+                          CMD [\"a\"]\n\
+                          CMD [\"b\"]\n\
+                          USER root\n";
+        assert_eq!(
+            findings(dockerfile),
+            vec![
+                (1, "DFQ009"),
+                (2, "DFQ001"),
+                (3, "DFQ002"),
+                (3, "DFQ003"),
+                (4, "DFQ008"),
+                (5, "DFQ001"),
+                (5, "DFQ006"),
+                (5, "DFQ010"),
+                (6, "DFQ009"),
+                (7, "DFQ004"),
+                (8, "DFQ007"),
+                (10, "DFQ005"),
+            ]
+        );
+    }
+
+    #[test]
+    fn accepts_a_clean_dockerfile() {
+        let dockerfile = "ARG BASE=debian:12\n\
+                          FROM ${BASE}\n\
+                          ARG VERSION=1\n\
+                          RUN DEBIAN_FRONTEND=noninteractive apt-get install -y --no-install-recommends \\\n  \
+                          curl=${VERSION} \\\n  \
+                          && rm -rf /var/lib/apt/lists/*\n\
+                          WORKDIR /app\n\
+                          USER app\n\
+                          HEALTHCHECK CMD curl -f http://localhost/\n\
+                          CMD [\"app\"]\n";
+        assert_eq!(findings(dockerfile), vec![]);
+    }
+
+    #[test]
+    fn honours_suppression_comments() {
+        let dockerfile = "# dfq:disable DFQ009, unused-arg\n\
+                          ARG UNUSED=1\n\
+                          # dfq:ignore DFQ001\n\
+                          FROM ubuntu\n\
+                          # dfq:ignore-next-line\n\
+                          \n\
+                          RUN apt-get install -y curl \\\n  \
+                          && make\n\
+                          # dfq:ignore DFQ999\n\
+                          RUN cd /src\n\
+                          HEALTHCHECK NONE\n";
+        assert_eq!(findings(dockerfile), vec![(10, "DFQ008")]);
+    }
+
+    #[test]
+    fn baseline_hides_known_findings_by_instruction() {
+        let model =
+            parse_dockerfile("FROM ubuntu\nRUN cd /src\n").expect("dockerfile should parse");
+        let known: Vec<(&str, Finding)> = lint(&model, &BTreeMap::new())
+            .into_iter()
+            .map(|finding| ("Dockerfile", finding))
+            .collect();
+        let baseline =
+            Baseline::from_findings(known.iter().map(|(path, finding)| (*path, finding)));
+        let written = baseline.to_toml_string();
+        assert!(written.starts_with(
+            "# dfq lint baseline: findings listed here are not reported\n[[finding]]\ninstruction = \"FROM ubuntu\"\nline = 1\npath = \"Dockerfile\"\nrule = \"DFQ001\"\n"
+        ));
+        let baseline = Baseline::parse(&written).expect("baseline should parse");
+        assert_eq!(baseline.len(), 3);
+
+        let model = parse_dockerfile("# moved\nFROM ubuntu\nRUN cd /src\nRUN cd /tmp\n")
+            .expect("dockerfile should parse");
+        let current = lint(&model, &BTreeMap::new())
+            .into_iter()
+            .map(|finding| ("Dockerfile", finding))
+            .collect();
+        let new: Vec<(usize, &str)> = baseline
+            .retain_new(current)
+            .into_iter()
+            .map(|(_, finding)| (finding.start_line, finding.rule.id))
+            .collect();
+        assert_eq!(new, vec![(4, "DFQ008")]);
+        assert!(
+            Baseline::parse("finding = []\n")
+                .expect("empty baseline")
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn splits_shell_commands() {
+        assert_eq!(
+            shell_commands("sudo apt-get install -y a; cd /x && make || true | tee log"),
+            vec![
+                vec!["apt-get", "install", "-y", "a"],
+                vec!["cd", "/x"],
+                vec!["make"],
+                vec!["true"],
+                vec!["tee", "log"],
+            ]
+        );
+    }
+}
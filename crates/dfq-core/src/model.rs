@@ -7,6 +7,8 @@ pub struct DockerfileModel {
     pub global_args: ArgDefaults,
     pub stages: Vec<Stage>,
     pub raw_instructions: Vec<Instruction>,
+    pub comments: Vec<Comment>,
+    pub directives: BTreeMap<String, String>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -32,3 +34,9 @@ pub struct Instruction {
     pub start_line: usize,
     pub end_line: usize,
 }
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Comment {
+    pub text: String,
+    pub line: usize,
+}
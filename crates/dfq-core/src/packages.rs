@@ -0,0 +1,385 @@
+use crate::lint::{arguments, shell_commands};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Package {
+    pub manager: &'static str,
+    pub name: String,
+    pub version: Option<String>,
+}
+
+const APK_VALUE_FLAGS: &[&str] = &[
+    "-t",
+    "--virtual",
+    "-X",
+    "--repository",
+    "-p",
+    "--root",
+    "--arch",
+    "--cache-dir",
+    "--keys-dir",
+    "--repositories-file",
+];
+const APT_VALUE_FLAGS: &[&str] = &["-o", "--option", "-t", "--target-release", "-c"];
+const RPM_VALUE_FLAGS: &[&str] = &[
+    "-x",
+    "--exclude",
+    "--enablerepo",
+    "--disablerepo",
+    "--repo",
+    "--setopt",
+    "--installroot",
+    "--releasever",
+    "-c",
+    "--config",
+];
+const PIP_VALUE_FLAGS: &[&str] = &[
+    "-r",
+    "--requirement",
+    "-c",
+    "--constraint",
+    "-e",
+    "--editable",
+    "-i",
+    "--index-url",
+    "--extra-index-url",
+    "-f",
+    "--find-links",
+    "-t",
+    "--target",
+    "--prefix",
+    "--root",
+    "--trusted-host",
+    "--platform",
+    "--python-version",
+    "--cache-dir",
+];
+const NPM_VALUE_FLAGS: &[&str] = &["--registry", "--prefix", "--tag", "--cache"];
+const GEM_VALUE_FLAGS: &[&str] = &[
+    "-v",
+    "--version",
+    "-s",
+    "--source",
+    "-i",
+    "--install-dir",
+    "-n",
+    "--bindir",
+    "--platform",
+];
+const GO_VALUE_FLAGS: &[&str] = &[
+    "-ldflags",
+    "-gcflags",
+    "-asmflags",
+    "-tags",
+    "-mod",
+    "-modfile",
+    "-o",
+    "-p",
+    "-pkgdir",
+];
+const CARGO_VALUE_FLAGS: &[&str] = &[
+    "--version",
+    "--vers",
+    "--git",
+    "--branch",
+    "--tag",
+    "--rev",
+    "--path",
+    "--root",
+    "-F",
+    "--features",
+    "--target",
+    "--target-dir",
+    "--profile",
+    "-j",
+    "--jobs",
+    "--registry",
+    "--index",
+    "--bin",
+    "--example",
+    "--config",
+    "-Z",
+];
+
+pub(crate) fn parse_packages(raw: &str) -> Vec<Package> {
+    let script = arguments(raw).replace("\\\r\n", " ").replace("\\\n", " ");
+    script
+        .lines()
+        .flat_map(shell_commands)
+        .flat_map(|command| command_packages(&command))
+        .collect()
+}
+
+fn command_packages(command: &[&str]) -> Vec<Package> {
+    let (program, args) = match command {
+        [python, "-m", module, args @ ..] if python.starts_with("python") => (*module, args),
+        [program, args @ ..] => (*program, args),
+        [] => return Vec::new(),
+    };
+    let program = program.rsplit('/').next().unwrap_or(program);
+    match program {
+        "apk" => install(args, APK_VALUE_FLAGS, &["add"])
+            .map(|names| pinned(names, "apk"))
+            .unwrap_or_default(),
+        "apt-get" | "apt" => install(args, APT_VALUE_FLAGS, &["install"])
+            .map(|names| pinned(names, "apt"))
+            .unwrap_or_default(),
+        "yum" | "dnf" | "microdnf" => install(args, RPM_VALUE_FLAGS, &["install"])
+            .map(|names| {
+                let manager = if program == "yum" { "yum" } else { "dnf" };
+                names.into_iter().map(|name| rpm(manager, name)).collect()
+            })
+            .unwrap_or_default(),
+        _ if program.starts_with("pip") => install(args, PIP_VALUE_FLAGS, &["install"])
+            .map(|names| names.into_iter().map(pip).collect())
+            .unwrap_or_default(),
+        "npm" if args.iter().any(|arg| matches!(*arg, "-g" | "--global")) => {
+            install(args, NPM_VALUE_FLAGS, &["install", "i", "add"])
+                .map(|names| {
+                    names
+                        .into_iter()
+                        .map(|name| at_version("npm", name))
+                        .collect()
+                })
+                .unwrap_or_default()
+        }
+        "gem" => install(args, GEM_VALUE_FLAGS, &["install"])
+            .map(|names| gem(args, names))
+            .unwrap_or_default(),
+        "go" => install(args, GO_VALUE_FLAGS, &["install"])
+            .map(|names| {
+                names
+                    .into_iter()
+                    .map(|name| at_version("go", name))
+                    .collect()
+            })
+            .unwrap_or_default(),
+        "cargo" => install(args, CARGO_VALUE_FLAGS, &["install"])
+            .map(|names| {
+                let version = flag_value(args, &["--version", "--vers"]);
+                names
+                    .into_iter()
+                    .map(|name| match &version {
+                        Some(version) => package("cargo", name, Some(version.clone())),
+                        None => at_version("cargo", name),
+                    })
+                    .collect()
+            })
+            .unwrap_or_default(),
+        _ => Vec::new(),
+    }
+}
+
+fn install<'c>(
+    args: &[&'c str],
+    value_flags: &[&str],
+    subcommands: &[&str],
+) -> Option<Vec<&'c str>> {
+    let mut operands = Vec::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        if arg.starts_with('-') {
+            if value_flags.contains(arg) {
+                args.next();
+            }
+            continue;
+        }
+        operands.push(unquote(arg));
+    }
+    let (subcommand, names) = operands.split_first()?;
+    subcommands.contains(subcommand).then(|| {
+        names
+            .iter()
+            .copied()
+            .filter(|name| !name.is_empty())
+            .collect()
+    })
+}
+
+fn flag_value(args: &[&str], flags: &[&str]) -> Option<String> {
+    args.iter().enumerate().find_map(|(index, arg)| {
+        if flags.contains(arg) {
+            return args.get(index + 1).map(|value| unquote(value).to_string());
+        }
+        flags.iter().find_map(|flag| {
+            arg.strip_prefix(flag)?
+                .strip_prefix('=')
+                .map(|value| unquote(value).to_string())
+        })
+    })
+}
+
+fn unquote(token: &str) -> &str {
+    token.trim_matches(|ch| ch == '"' || ch == '\'')
+}
+
+fn package(manager: &'static str, name: &str, version: Option<String>) -> Package {
+    Package {
+        manager,
+        name: name.to_string(),
+        version,
+    }
+}
+
+fn pinned(names: Vec<&str>, manager: &'static str) -> Vec<Package> {
+    names
+        .into_iter()
+        .map(|name| match name.find(['=', '~', '<', '>']) {
+            Some(index) if index > 0 => {
+                let spec = &name[index..];
+                let version = spec.strip_prefix('=').unwrap_or(spec);
+                package(manager, &name[..index], Some(version.to_string()))
+            }
+            _ => package(manager, name, None),
+        })
+        .collect()
+}
+
+fn rpm(manager: &'static str, name: &str) -> Package {
+    if !name.contains('/') {
+        let split = name
+            .match_indices('-')
+            .map(|(index, _)| index)
+            .find(|index| name[index + 1..].starts_with(|ch: char| ch.is_ascii_digit()));
+        if let Some(index) = split {
+            let version = &name[index + 1..];
+            if version
+                .split('-')
+                .all(|part| part.starts_with(|ch: char| ch.is_ascii_digit()))
+            {
+                return package(manager, &name[..index], Some(version.to_string()));
+            }
+        }
+    }
+    package(manager, name, None)
+}
+
+fn pip(requirement: &str) -> Package {
+    let Some(index) = requirement.find(['=', '<', '>', '~', '!']) else {
+        return package("pip", strip_extras(requirement), None);
+    };
+    let (name, spec) = requirement.split_at(index);
+    let version = match spec.strip_prefix("==") {
+        Some(exact) if !exact.starts_with('=') && !exact.contains(',') => exact,
+        _ => spec,
+    };
+    package("pip", strip_extras(name), Some(version.to_string()))
+}
+
+fn strip_extras(name: &str) -> &str {
+    name.split_once('[').map_or(name, |(name, _)| name).trim()
+}
+
+fn at_version(manager: &'static str, name: &str) -> Package {
+    match name.rfind('@') {
+        Some(index) if index > 0 => {
+            package(manager, &name[..index], Some(name[index + 1..].to_string()))
+        }
+        _ => package(manager, name, None),
+    }
+}
+
+fn gem(args: &[&str], names: Vec<&str>) -> Vec<Package> {
+    let version = flag_value(args, &["-v", "--version"]);
+    names
+        .into_iter()
+        .map(|name| match (name.split_once(':'), &version) {
+            (Some((name, version)), _) => package("gem", name, Some(version.to_string())),
+            (None, Some(version)) => package("gem", name, Some(version.clone())),
+            (None, None) => package("gem", name, None),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::packages::parse_packages;
+
+    fn packages(raw: &str) -> Vec<(&'static str, String, Option<String>)> {
+        parse_packages(raw)
+            .into_iter()
+            .map(|package| (package.manager, package.name, package.version))
+            .collect()
+    }
+
+    fn entry(
+        manager: &'static str,
+        name: &str,
+        version: Option<&str>,
+    ) -> (&'static str, String, Option<String>) {
+        (manager, name.to_string(), version.map(str::to_string))
+    }
+
+    #[test]
+    fn extracts_system_packages_with_pins() {
+        assert_eq!(
+            packages(
+                "RUN --mount=type=cache,target=/var/cache/apk apk add --no-cache --virtual .build-deps curl=7.88.1-r1 \\\n    bash~5.2 git"
+            ),
+            vec![
+                entry("apk", "curl", Some("7.88.1-r1")),
+                entry("apk", "bash", Some("~5.2")),
+                entry("apk", "git", None),
+            ]
+        );
+        assert_eq!(
+            packages(
+                "RUN apt-get update && apt-get -o Dpkg::Options::=--force-confold install -y --no-install-recommends ca-certificates curl=7.88.1-10 && rm -rf /var/lib/apt/lists/*"
+            ),
+            vec![
+                entry("apt", "ca-certificates", None),
+                entry("apt", "curl", Some("7.88.1-10")),
+            ]
+        );
+        assert_eq!(
+            packages(
+                "RUN yum install -y httpd-2.4.57-5.el9 java-1.8.0-openjdk && microdnf install gcc-c++"
+            ),
+            vec![
+                entry("yum", "httpd", Some("2.4.57-5.el9")),
+                entry("yum", "java-1.8.0-openjdk", None),
+                entry("dnf", "gcc-c++", None),
+            ]
+        );
+        assert_eq!(packages("RUN apt-get update && apk info"), vec![]);
+    }
+
+    #[test]
+    fn extracts_language_packages_with_pins() {
+        assert_eq!(
+            packages(
+                "RUN pip install --no-cache-dir -r requirements.txt pkg==1.2 'uvicorn[standard]>=0.30' && python3 -m pip install black"
+            ),
+            vec![
+                entry("pip", "pkg", Some("1.2")),
+                entry("pip", "uvicorn", Some(">=0.30")),
+                entry("pip", "black", None),
+            ]
+        );
+        assert_eq!(
+            packages("RUN npm install -g @angular/cli@17.3.0 pnpm && npm install left-pad"),
+            vec![
+                entry("npm", "@angular/cli", Some("17.3.0")),
+                entry("npm", "pnpm", None),
+            ]
+        );
+        assert_eq!(
+            packages(
+                "RUN gem install bundler -v 2.5.6 --no-document && gem install rails:7.1.0 \\\n    && go install golang.org/x/tools/gopls@v0.15.0"
+            ),
+            vec![
+                entry("gem", "bundler", Some("2.5.6")),
+                entry("gem", "rails", Some("7.1.0")),
+                entry("go", "golang.org/x/tools/gopls", Some("v0.15.0")),
+            ]
+        );
+        assert_eq!(
+            packages(
+                "RUN cargo install --locked ripgrep --version 14.1.0 && cargo install cargo-edit@0.12.2"
+            ),
+            vec![
+                entry("cargo", "ripgrep", Some("14.1.0")),
+                entry("cargo", "cargo-edit", Some("0.12.2")),
+            ]
+        );
+    }
+}
@@ -1,7 +1,11 @@
+use std::collections::BTreeMap;
+
 use dockerfile_parser::{Dockerfile, Instruction as ParsedInstruction, StageParent};
 
 use crate::error::Error;
-use crate::model::{DockerfileModel, Instruction, Parent, Stage};
+use crate::model::{Comment, DockerfileModel, Instruction, Parent, Stage};
+
+pub(crate) const DIRECTIVES: &[&str] = &["syntax", "escape", "check"];
 
 pub fn parse_dockerfile(input: &str) -> Result<DockerfileModel, Error> {
     let dockerfile =
@@ -22,6 +26,11 @@ pub fn parse_dockerfile(input: &str) -> Result<DockerfileModel, Error> {
         .iter()
         .map(|instruction| map_instruction(instruction, &dockerfile.content, &line_index))
         .collect::<Result<Vec<_>, _>>()?;
+    model.directives = parse_directives(&dockerfile.content);
+    model.comments = collect_comments(&dockerfile.content, &model.raw_instructions)
+        .into_iter()
+        .skip(model.directives.len())
+        .collect();
 
     for parsed_stage in dockerfile.stages().iter() {
         let from = parsed_stage
@@ -105,6 +114,43 @@ fn map_instruction(
     })
 }
 
+fn parse_directives(content: &str) -> BTreeMap<String, String> {
+    let mut directives = BTreeMap::new();
+    for line in content.lines() {
+        let Some((name, value)) = line
+            .trim()
+            .strip_prefix('#')
+            .and_then(|text| text.split_once('='))
+        else {
+            break;
+        };
+        let name = name.trim().to_ascii_lowercase();
+        if !DIRECTIVES.contains(&name.as_str()) || directives.contains_key(&name) {
+            break;
+        }
+        directives.insert(name, value.trim().to_string());
+    }
+    directives
+}
+
+fn collect_comments(content: &str, instructions: &[Instruction]) -> Vec<Comment> {
+    content
+        .lines()
+        .enumerate()
+        .filter_map(|(index, line)| {
+            let line_number = index + 1;
+            let text = line.trim_start().strip_prefix('#')?;
+            let inside = instructions.iter().any(|instruction| {
+                (instruction.start_line..=instruction.end_line).contains(&line_number)
+            });
+            (!inside).then(|| Comment {
+                text: text.trim().to_string(),
+                line: line_number,
+            })
+        })
+        .collect()
+}
+
 fn span_slice(content: &str, start: usize, end: usize) -> Result<String, Error> {
     let bytes = content.as_bytes();
     if start > end || end > bytes.len() {
@@ -143,7 +189,7 @@ impl LineIndex {
 
 #[cfg(test)]
 mod tests {
-    use crate::model::Parent;
+    use crate::model::{Comment, Parent};
     use crate::parser::parse_dockerfile;
 
     #[test]
@@ -166,6 +212,52 @@ mod tests {
         assert_eq!(parsed.stages[0].instructions.len(), 1);
     }
 
+    #[test]
+    fn keeps_comments_outside_instructions() {
+        let file = "# syntax=docker/dockerfile:1\n\n  # base image\nFROM alpine\nRUN echo a \\\n  # not a top-level comment\n  && echo b\nRUN echo '#'\n#trailing\n";
+        let parsed = parse_dockerfile(file).expect("dockerfile should parse");
+        assert_eq!(
+            parsed.comments,
+            [
+                Comment {
+                    text: "base image".to_string(),
+                    line: 3,
+                },
+                Comment {
+                    text: "trailing".to_string(),
+                    line: 9,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn reads_parser_directives_from_the_header() {
+        let file = "# syntax = docker/dockerfile:1.7\n#ESCAPE=`\n# check=skip=JSONArgsRecommended\n# syntax=ignored\n# build notes\nFROM alpine\n";
+        let parsed = parse_dockerfile(file).expect("dockerfile should parse");
+        assert_eq!(
+            parsed.directives.into_iter().collect::<Vec<_>>(),
+            [
+                ("check".to_string(), "skip=JSONArgsRecommended".to_string()),
+                ("escape".to_string(), "`".to_string()),
+                ("syntax".to_string(), "docker/dockerfile:1.7".to_string()),
+            ]
+        );
+        assert_eq!(
+            parsed
+                .comments
+                .iter()
+                .map(|comment| comment.line)
+                .collect::<Vec<_>>(),
+            [4, 5]
+        );
+
+        let parsed = parse_dockerfile("# a comment\n# syntax=docker/dockerfile:1\nFROM alpine\n")
+            .expect("dockerfile should parse");
+        assert!(parsed.directives.is_empty());
+        assert_eq!(parsed.comments.len(), 2);
+    }
+
     #[test]
     fn parses_stage_reference() {
         let file = "FROM alpine AS base\nFROM base\n";
@@ -0,0 +1,529 @@
+use std::collections::BTreeMap;
+
+use regex::Regex;
+
+use crate::error::Error;
+use crate::eval::Evaluator;
+use crate::lint::Severity;
+use crate::query::{Query, parse_query};
+use crate::toml::parse_toml;
+use crate::value::Value;
+
+const RULE_KEYS: &[&str] = &["id", "description", "query", "severity"];
+const EXPECTATION_KEYS: &[&str] = &[
+    "equals",
+    "matches",
+    "not_contains",
+    "count",
+    "number",
+    "all",
+    "any",
+];
+
+#[derive(Debug, Clone)]
+pub struct Policy {
+    pub rules: Vec<PolicyRule>,
+}
+
+#[derive(Debug, Clone)]
+pub struct PolicyRule {
+    pub id: String,
+    pub description: Option<String>,
+    pub query: String,
+    pub severity: Severity,
+    pub expectations: Vec<Expectation>,
+    parsed: Query,
+}
+
+#[derive(Debug, Clone)]
+pub enum Expectation {
+    Equals(Value),
+    Matches(Regex),
+    NotContains(String),
+    Count(Comparison, i64),
+    Number(Comparison, i64),
+    All(Vec<Expectation>),
+    Any(Vec<Expectation>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Comparison {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+#[derive(Debug, Clone)]
+pub struct RuleOutcome<'p> {
+    pub rule: &'p PolicyRule,
+    pub value: Option<Value>,
+    pub failure: Option<String>,
+    pub lines: Option<(usize, usize)>,
+}
+
+impl Policy {
+    pub fn parse(input: &str) -> Result<Self, Error> {
+        let mut root = parse_toml(input)?;
+        let rules = match root.remove("rule") {
+            Some(Value::Array(rules)) => rules,
+            Some(_) => {
+                return Err(Error::config(
+                    "'rule' must be an array of tables ([[rule]])",
+                ));
+            }
+            None => Vec::new(),
+        };
+        if let Some(key) = root.keys().next() {
+            return Err(Error::config(format!("unknown top-level key '{key}'")));
+        }
+        if rules.is_empty() {
+            return Err(Error::config("policy defines no [[rule]] entries"));
+        }
+
+        let mut parsed = Vec::with_capacity(rules.len());
+        for (index, rule) in rules.into_iter().enumerate() {
+            let rule = PolicyRule::from_value(rule)
+                .map_err(|msg| Error::config(format!("rule {}: {msg}", index + 1)))?;
+            if parsed.iter().any(|other: &PolicyRule| other.id == rule.id) {
+                return Err(Error::config(format!("duplicate rule id '{}'", rule.id)));
+            }
+            parsed.push(rule);
+        }
+        Ok(Self { rules: parsed })
+    }
+
+    pub fn check<'p>(&'p self, evaluator: &Evaluator) -> Vec<RuleOutcome<'p>> {
+        self.rules
+            .iter()
+            .map(|rule| match evaluator.evaluate(&rule.parsed) {
+                Ok(result) => {
+                    let failure = check_all(&rule.expectations, &result.value).err();
+                    let lines = failure.as_ref().and_then(|_| {
+                        let item = failing_item(&rule.expectations, &result.value);
+                        evaluator.instruction_lines(&rule.parsed, item)
+                    });
+                    RuleOutcome {
+                        rule,
+                        value: Some(result.value),
+                        failure,
+                        lines,
+                    }
+                }
+                Err(error) => RuleOutcome {
+                    rule,
+                    value: None,
+                    failure: Some(error.to_string()),
+                    lines: evaluator.instruction_lines(&rule.parsed, None),
+                },
+            })
+            .collect()
+    }
+}
+
+impl PolicyRule {
+    fn from_value(value: Value) -> Result<Self, String> {
+        let Value::Object(mut table) = value else {
+            return Err("expected a table".to_string());
+        };
+        let id = match table.remove("id") {
+            Some(Value::String(id)) if !id.is_empty() => id,
+            Some(_) => return Err("'id' must be a non-empty string".to_string()),
+            None => return Err("missing 'id'".to_string()),
+        };
+        let context = |msg: String| format!("'{id}': {msg}");
+        let description = match table.remove("description") {
+            Some(Value::String(description)) => Some(description),
+            Some(_) => return Err(context("'description' must be a string".to_string())),
+            None => None,
+        };
+        let query = match table.remove("query") {
+            Some(Value::String(query)) => query,
+            Some(_) => return Err(context("'query' must be a string".to_string())),
+            None => return Err(context("missing 'query'".to_string())),
+        };
+        let parsed = parse_query(&query).map_err(|error| context(error.to_string()))?;
+        let severity = match table.remove("severity") {
+            Some(Value::String(name)) => match name.as_str() {
+                "info" => Severity::Info,
+                "warning" => Severity::Warning,
+                "error" => Severity::Error,
+                _ => {
+                    return Err(context(format!(
+                        "unknown severity '{name}' (expected info, warning, or error)"
+                    )));
+                }
+            },
+            Some(_) => return Err(context("'severity' must be a string".to_string())),
+            None => Severity::Error,
+        };
+        let expectations = expectations(table).map_err(context)?;
+        Ok(Self {
+            id,
+            description,
+            query,
+            severity,
+            expectations,
+            parsed,
+        })
+    }
+}
+
+impl RuleOutcome<'_> {
+    pub fn passed(&self) -> bool {
+        self.failure.is_none()
+    }
+
+    pub fn to_value(&self) -> Value {
+        let rule = self.rule;
+        Value::Object(BTreeMap::from([
+            ("rule".to_string(), Value::String(rule.id.clone())),
+            (
+                "description".to_string(),
+                rule.description.clone().map_or(Value::Null, Value::String),
+            ),
+            ("query".to_string(), Value::String(rule.query.clone())),
+            (
+                "severity".to_string(),
+                Value::String(rule.severity.as_str().to_string()),
+            ),
+            ("passed".to_string(), Value::Bool(self.passed())),
+            (
+                "value".to_string(),
+                self.value.clone().unwrap_or(Value::Null),
+            ),
+            (
+                "message".to_string(),
+                self.failure.clone().map_or(Value::Null, Value::String),
+            ),
+        ]))
+    }
+}
+
+impl Expectation {
+    fn check(&self, value: &Value) -> Result<(), String> {
+        let failed = || Err(format!("expected {self}, got {}", value.to_json_string()));
+        match self {
+            Self::Equals(expected) => {
+                let equal = match (value.render_scalar(), expected.render_scalar()) {
+                    (Some(actual), Some(expected)) if value.is_scalar() => actual == expected,
+                    _ => value == expected,
+                };
+                if equal { Ok(()) } else { failed() }
+            }
+            Self::Matches(pattern) => match value {
+                Value::Null | Value::Array(_) | Value::Object(_) => failed(),
+                _ if pattern.is_match(&value.render_scalar().unwrap_or_default()) => Ok(()),
+                _ => failed(),
+            },
+            Self::NotContains(text) => {
+                let contains = match value {
+                    Value::Array(items) => items
+                        .iter()
+                        .any(|item| item.render_scalar().as_deref() == Some(text.as_str())),
+                    Value::Object(map) => map.contains_key(text),
+                    _ => value
+                        .render_scalar()
+                        .is_some_and(|actual| actual.contains(text.as_str())),
+                };
+                if contains { failed() } else { Ok(()) }
+            }
+            Self::Count(comparison, expected) => {
+                let count = match value {
+                    Value::Array(items) => items.len(),
+                    Value::Object(map) => map.len(),
+                    Value::Null => 0,
+                    _ => 1,
+                } as i64;
+                if comparison.holds(count, *expected) {
+                    Ok(())
+                } else {
+                    Err(format!("expected {self}, got count {count}"))
+                }
+            }
+            Self::Number(comparison, expected) => match value {
+                Value::Number(number) if comparison.holds(*number, *expected) => Ok(()),
+                _ => failed(),
+            },
+            Self::All(inner) => {
+                for (index, item) in items(value).enumerate() {
+                    if let Some(item) = item {
+                        check_all(inner, item).map_err(|msg| format!("item {index}: {msg}"))?;
+                    }
+                }
+                Ok(())
+            }
+            Self::Any(inner) => {
+                if items(value)
+                    .flatten()
+                    .any(|item| check_all(inner, item).is_ok())
+                {
+                    Ok(())
+                } else {
+                    failed()
+                }
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for Expectation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Equals(value) => write!(f, "equals {}", value.to_json_string()),
+            Self::Matches(pattern) => write!(f, "matches /{}/", pattern.as_str()),
+            Self::NotContains(text) => write!(f, "not_contains {text:?}"),
+            Self::Count(comparison, count) => write!(f, "count {} {count}", comparison.as_str()),
+            Self::Number(comparison, number) => {
+                write!(f, "number {} {number}", comparison.as_str())
+            }
+            Self::All(inner) | Self::Any(inner) => {
+                let name = if matches!(self, Self::All(_)) {
+                    "all"
+                } else {
+                    "any"
+                };
+                let inner: Vec<String> = inner.iter().map(ToString::to_string).collect();
+                write!(f, "{name} items {}", inner.join(" and "))
+            }
+        }
+    }
+}
+
+impl Comparison {
+    fn parse(input: &str) -> Option<(Self, &str)> {
+        let ops = [
+            ("<=", Self::Le),
+            (">=", Self::Ge),
+            ("==", Self::Eq),
+            ("!=", Self::Ne),
+            ("<", Self::Lt),
+            (">", Self::Gt),
+            ("=", Self::Eq),
+        ];
+        ops.into_iter()
+            .find_map(|(op, comparison)| input.strip_prefix(op).map(|rest| (comparison, rest)))
+    }
+
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Eq => "==",
+            Self::Ne => "!=",
+            Self::Lt => "<",
+            Self::Le => "<=",
+            Self::Gt => ">",
+            Self::Ge => ">=",
+        }
+    }
+
+    fn holds(self, left: i64, right: i64) -> bool {
+        match self {
+            Self::Eq => left == right,
+            Self::Ne => left != right,
+            Self::Lt => left < right,
+            Self::Le => left <= right,
+            Self::Gt => left > right,
+            Self::Ge => left >= right,
+        }
+    }
+}
+
+fn expectations(table: BTreeMap<String, Value>) -> Result<Vec<Expectation>, String> {
+    if table.is_empty() {
+        return Err(format!(
+            "no expectation (expected one of: {})",
+            EXPECTATION_KEYS.join(", ")
+        ));
+    }
+    table
+        .into_iter()
+        .map(|(key, value)| expectation(&key, value))
+        .collect()
+}
+
+fn expectation(key: &str, value: Value) -> Result<Expectation, String> {
+    match (key, value) {
+        ("equals", value) => Ok(Expectation::Equals(value)),
+        ("matches", Value::String(pattern)) => Regex::new(&pattern)
+            .map(Expectation::Matches)
+            .map_err(|error| format!("invalid 'matches' pattern: {error}")),
+        ("not_contains", Value::String(text)) => Ok(Expectation::NotContains(text)),
+        ("count", Value::Number(count)) => Ok(Expectation::Count(Comparison::Eq, count)),
+        ("count", Value::String(text)) => {
+            comparison(key, &text).map(|(comparison, count)| Expectation::Count(comparison, count))
+        }
+        ("number", Value::Number(number)) => Ok(Expectation::Number(Comparison::Eq, number)),
+        ("number", Value::String(text)) => comparison(key, &text)
+            .map(|(comparison, number)| Expectation::Number(comparison, number)),
+        ("all" | "any", Value::Object(inner)) => {
+            let inner = expectations(inner).map_err(|msg| format!("'{key}': {msg}"))?;
+            Ok(if key == "all" {
+                Expectation::All(inner)
+            } else {
+                Expectation::Any(inner)
+            })
+        }
+        ("matches" | "not_contains", _) => Err(format!("'{key}' must be a string")),
+        ("count" | "number", _) => Err(format!(
+            "'{key}' must be an integer or a string like \"<= 3\""
+        )),
+        ("all" | "any", _) => Err(format!("'{key}' must be a table of expectations")),
+        (key, _) => Err(format!(
+            "unknown key '{key}' (expected {} or one of: {})",
+            RULE_KEYS.join(", "),
+            EXPECTATION_KEYS.join(", ")
+        )),
+    }
+}
+
+fn comparison(key: &str, text: &str) -> Result<(Comparison, i64), String> {
+    Comparison::parse(text.trim())
+        .and_then(|(comparison, number)| Some((comparison, number.trim().parse().ok()?)))
+        .ok_or_else(|| format!("invalid '{key}' expectation '{text}' (expected e.g. \"<= 3\")"))
+}
+
+fn check_all(expectations: &[Expectation], value: &Value) -> Result<(), String> {
+    expectations
+        .iter()
+        .try_for_each(|expectation| expectation.check(value))
+}
+
+fn failing_item(expectations: &[Expectation], value: &Value) -> Option<usize> {
+    match expectations
+        .iter()
+        .find(|expectation| expectation.check(value).is_err())?
+    {
+        Expectation::All(inner) => {
+            items(value).position(|item| item.is_some_and(|item| check_all(inner, item).is_err()))
+        }
+        _ => None,
+    }
+}
+
+fn items(value: &Value) -> impl Iterator<Item = Option<&Value>> {
+    let items = match value {
+        Value::Array(items) => items.iter().collect(),
+        Value::Null => Vec::new(),
+        value => vec![value],
+    };
+    items
+        .into_iter()
+        .map(|item| (!matches!(item, Value::Null)).then_some(item))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use crate::eval::Evaluator;
+    use crate::parser::parse_dockerfile;
+    use crate::policy::Policy;
+
+    const DOCKERFILE: &str = "ARG VERSION=3.19\nFROM registry.internal/base/alpine:${VERSION} AS build\nRUN apk add curl\nFROM build AS test\nFROM alpine:latest\nRUN echo done\n";
+
+    fn check(policy: &str) -> Vec<(String, Option<String>)> {
+        let model = parse_dockerfile(DOCKERFILE).expect("dockerfile should parse");
+        let overrides = BTreeMap::new();
+        let evaluator = Evaluator::new(&model, &overrides, false);
+        let policy = Policy::parse(policy).expect("policy should parse");
+        policy
+            .check(&evaluator)
+            .into_iter()
+            .map(|outcome| (outcome.rule.id.clone(), outcome.failure))
+            .collect()
+    }
+
+    #[test]
+    fn evaluates_expectations_against_query_results() {
+        let outcomes = check(
+            "[[rule]]\nid = \"registry\"\nquery = \"FROM[*].REGISTRY\"\nall = { equals = \"registry.internal\" }\n\n[[rule]]\nid = \"pinned\"\nquery = \"FROM[*].RESOLVED\"\nall.not_contains = \":latest\"\n\n[[rule]]\nid = \"version\"\nquery = \"ARG.VERSION\"\nmatches = '^\\d+\\.\\d+$'\n\n[[rule]]\nid = \"runs\"\nquery = \"RUN\"\ncount = \"<= 1\"\n\n[[rule]]\nid = \"run-count\"\nquery = \"RUN.COUNT\"\nnumber = \"<= 1\"\n\n[[rule]]\nid = \"run-count-items\"\nquery = \"RUN.COUNT\"\ncount = 1\n\n[[rule]]\nid = \"apk\"\nquery = \"RUN[*].COMMAND\"\nany.matches = \"^apk add\"\n\n[[rule]]\nid = \"missing\"\nquery = \"ARG.NOPE\"\nequals = \"x\"\n",
+        );
+        assert_eq!(
+            outcomes,
+            [
+                (
+                    "registry".to_string(),
+                    Some(
+                        "item 2: expected equals \"registry.internal\", got \"docker.io\""
+                            .to_string()
+                    )
+                ),
+                (
+                    "pinned".to_string(),
+                    Some(
+                        "item 2: expected not_contains \":latest\", got \"alpine:latest\""
+                            .to_string()
+                    )
+                ),
+                ("version".to_string(), None),
+                (
+                    "runs".to_string(),
+                    Some("expected count <= 1, got count 2".to_string())
+                ),
+                (
+                    "run-count".to_string(),
+                    Some("expected number <= 1, got 2".to_string())
+                ),
+                ("run-count-items".to_string(), None),
+                ("apk".to_string(), None),
+                (
+                    "missing".to_string(),
+                    Some("not found: ARG.NOPE: unset arg at segment 1".to_string())
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn records_lines_of_the_failing_instruction() {
+        let model = parse_dockerfile(DOCKERFILE).expect("dockerfile should parse");
+        let overrides = BTreeMap::new();
+        let evaluator = Evaluator::new(&model, &overrides, false);
+        let policy = Policy::parse(
+            "[[rule]]\nid = \"registry\"\nquery = \"FROM[*].REGISTRY\"\nall.equals = \"registry.internal\"\n\n[[rule]]\nid = \"version\"\nquery = \"ARG.VERSION\"\nmatches = '^\\d+$'\n\n[[rule]]\nid = \"echo\"\nquery = \"RUN[1].COMMAND\"\nequals = \"true\"\n\n[[rule]]\nid = \"runs\"\nquery = \"RUN\"\ncount = 1\n\n[[rule]]\nid = \"apk\"\nquery = \"RUN[*].COMMAND\"\nany.matches = \"^apk add\"\n",
+        )
+        .expect("policy should parse");
+        let lines: Vec<(String, Option<(usize, usize)>)> = policy
+            .check(&evaluator)
+            .into_iter()
+            .map(|outcome| (outcome.rule.id.clone(), outcome.lines))
+            .collect();
+        assert_eq!(
+            lines,
+            [
+                ("registry".to_string(), Some((5, 5))),
+                ("version".to_string(), Some((1, 1))),
+                ("echo".to_string(), Some((6, 6))),
+                ("runs".to_string(), None),
+                ("apk".to_string(), None),
+            ]
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_rules() {
+        let error = |input: &str| {
+            Policy::parse(input)
+                .expect_err("policy should fail")
+                .to_string()
+        };
+        assert_eq!(
+            error("[[rule]]\nid = \"a\"\nquery = \"ARG\"\nequal = 1\n"),
+            "config error: rule 1: 'a': unknown key 'equal' (expected id, description, query, severity or one of: equals, matches, not_contains, count, number, all, any)"
+        );
+        assert_eq!(
+            error("[[rule]]\nid = \"a\"\nquery = \"ARG\"\n"),
+            "config error: rule 1: 'a': no expectation (expected one of: equals, matches, not_contains, count, number, all, any)"
+        );
+        assert_eq!(
+            error("[[rule]]\nid = \"a\"\nquery = \"RUN\"\ncount = \"about 3\"\n"),
+            "config error: rule 1: 'a': invalid 'count' expectation 'about 3' (expected e.g. \"<= 3\")"
+        );
+        assert_eq!(
+            error("title = \"x\"\n"),
+            "config error: unknown top-level key 'title'"
+        );
+    }
+}
@@ -1,9 +1,13 @@
 use crate::error::{Error, Span};
+use crate::value::Value;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Query {
     pub source: String,
     pub segments: Vec<Segment>,
+    /// Source spans of every segment in pre-order: the top-level segments
+    /// first, then the members of a trailing projection or group.
+    pub spans: Vec<Span>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -11,6 +15,21 @@ pub enum Segment {
     Ident(String),
     Indexed { ident: String, index: Index },
     Function { ident: String, args: Vec<Arg> },
+    Object(Vec<(String, Vec<Segment>)>),
+    Array(Vec<Vec<Segment>>),
+    Alternative(Vec<Vec<Segment>>),
+    Literal(Value),
+}
+
+impl Segment {
+    fn terminal_kind(&self) -> Option<&'static str> {
+        match self {
+            Self::Object(_) | Self::Array(_) => Some("projection"),
+            Self::Alternative(_) => Some("group"),
+            Self::Literal(_) => Some("literal"),
+            _ => None,
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -25,15 +44,19 @@ pub enum Arg {
     String(String),
     Ident(String),
     Number(i64),
+    Path(Vec<Segment>),
 }
 
 impl Query {
     pub fn parse(input: &str) -> Result<Self, Error> {
         let mut parser = Parser::new(input);
-        let segments = parser.parse_query()?;
+        let segments = parser
+            .parse_query()
+            .map_err(|error| error.with_query(input))?;
         Ok(Self {
             source: input.to_string(),
             segments,
+            spans: parser.spans,
         })
     }
 }
@@ -45,11 +68,16 @@ pub fn parse_query(input: &str) -> Result<Query, Error> {
 struct Parser<'a> {
     input: &'a str,
     pos: usize,
+    spans: Vec<Span>,
 }
 
 impl<'a> Parser<'a> {
     fn new(input: &'a str) -> Self {
-        Self { input, pos: 0 }
+        Self {
+            input,
+            pos: 0,
+            spans: Vec::new(),
+        }
     }
 
     fn parse_query(&mut self) -> Result<Vec<Segment>, Error> {
@@ -58,22 +86,92 @@ impl<'a> Parser<'a> {
             return Err(self.error("query is empty"));
         }
 
-        let mut segments = vec![self.parse_segment()?];
+        let segments = self.parse_expr()?;
+        self.skip_ws();
+        if !self.is_eof() {
+            self.expect_byte(b'.')?;
+        }
+
+        Ok(segments)
+    }
+
+    fn parse_expr(&mut self) -> Result<Vec<Segment>, Error> {
+        let start = self.pos;
+        let slot = self.spans.len();
+        let mut options = vec![self.parse_path()?];
+        options.extend(self.parse_alternatives()?);
+        if options.len() == 1 {
+            return Ok(options.remove(0));
+        }
+        self.spans.insert(slot, self.span_from(start));
+        Ok(vec![Segment::Alternative(options)])
+    }
+
+    fn parse_alternatives(&mut self) -> Result<Vec<Vec<Segment>>, Error> {
+        let mut options = Vec::new();
         loop {
             self.skip_ws();
-            if self.is_eof() {
+            if !self.input[self.pos..].starts_with("//") {
                 break;
             }
 
-            self.expect_byte(b'.')?;
+            self.pos += 2;
             self.skip_ws();
-            segments.push(self.parse_segment()?);
+            options.push(self.parse_path()?);
+        }
+        Ok(options)
+    }
+
+    fn parse_path(&mut self) -> Result<Vec<Segment>, Error> {
+        let mut segments = vec![self.parse_spanned_segment()?];
+        loop {
+            self.skip_ws();
+            if self.peek_byte() != Some(b'.') {
+                break;
+            }
+            if let Some(kind) = segments.last().and_then(Segment::terminal_kind) {
+                return Err(self.error(format!("{kind} must be the last segment")));
+            }
+
+            self.bump_byte();
+            self.skip_ws();
+            segments.push(self.parse_spanned_segment()?);
         }
 
         Ok(segments)
     }
 
+    fn parse_spanned_segment(&mut self) -> Result<Segment, Error> {
+        let start = self.pos;
+        let slot = self.spans.len();
+        self.spans.push(Span::new(start, start));
+        let segment = self.parse_segment()?;
+        self.spans[slot] = self.span_from(start);
+        Ok(segment)
+    }
+
+    fn span_from(&self, start: usize) -> Span {
+        Span::new(start, start + self.input[start..self.pos].trim_end().len())
+    }
+
     fn parse_segment(&mut self) -> Result<Segment, Error> {
+        match self.peek_byte() {
+            Some(b'{') => return self.parse_object(),
+            Some(b'[') => return self.parse_array(),
+            Some(b'(') => return self.parse_group(),
+            Some(b'"') | Some(b'\'') => {
+                return self
+                    .parse_string()
+                    .map(|value| Segment::Literal(Value::String(value)));
+            }
+            Some(byte) if byte.is_ascii_digit() || byte == b'-' => {
+                return self
+                    .parse_i64()
+                    .map(|value| Segment::Literal(Value::Number(value)));
+            }
+            _ => {}
+        }
+
         let ident = self.parse_ident()?;
         self.skip_ws();
 
@@ -124,6 +222,85 @@ impl<'a> Parser<'a> {
         }
     }
 
+    fn parse_object(&mut self) -> Result<Segment, Error> {
+        self.expect_byte(b'{')?;
+        let mut fields: Vec<(String, Vec<Segment>)> = Vec::new();
+
+        self.skip_ws();
+        if self.peek_byte() != Some(b'}') {
+            loop {
+                let key = match self.peek_byte() {
+                    Some(b'"') | Some(b'\'') => self.parse_string()?,
+                    _ => self.parse_ident()?,
+                };
+                if fields.iter().any(|(existing, _)| *existing == key) {
+                    return Err(self.error(format!("duplicate object key '{key}'")));
+                }
+
+                self.skip_ws();
+                self.expect_byte(b':')?;
+                self.skip_ws();
+                fields.push((key, self.parse_expr()?));
+                self.skip_ws();
+
+                match self.peek_byte() {
+                    Some(b',') => {
+                        self.bump_byte();
+                        self.skip_ws();
+                    }
+                    Some(b'}') => break,
+                    _ => return Err(self.error("expected ',' or '}'")),
+                }
+            }
+        }
+
+        self.expect_byte(b'}')?;
+        Ok(Segment::Object(fields))
+    }
+
+    fn parse_array(&mut self) -> Result<Segment, Error> {
+        self.expect_byte(b'[')?;
+        let mut items = Vec::new();
+
+        self.skip_ws();
+        if self.peek_byte() != Some(b']') {
+            loop {
+                items.push(self.parse_expr()?);
+                self.skip_ws();
+
+                match self.peek_byte() {
+                    Some(b',') => {
+                        self.bump_byte();
+                        self.skip_ws();
+                    }
+                    Some(b']') => break,
+                    _ => return Err(self.error("expected ',' or ']'")),
+                }
+            }
+        }
+
+        self.expect_byte(b']')?;
+        Ok(Segment::Array(items))
+    }
+
+    fn parse_group(&mut self) -> Result<Segment, Error> {
+        self.expect_byte(b'(')?;
+        self.skip_ws();
+        let slot = self.spans.len();
+        let expr = self.parse_expr()?;
+        self.skip_ws();
+        self.expect_byte(b')')?;
+
+        match <[Segment; 1]>::try_from(expr) {
+            Ok([Segment::Alternative(options)]) => {
+                self.spans.remove(slot);
+                Ok(Segment::Alternative(options))
+            }
+            Ok([segment]) => Ok(Segment::Alternative(vec![vec![segment]])),
+            Err(path) => Ok(Segment::Alternative(vec![path])),
+        }
+    }
+
     fn parse_arg(&mut self) -> Result<Arg, Error> {
         self.skip_ws();
         match self.peek_byte() {
@@ -131,7 +308,17 @@ impl<'a> Parser<'a> {
             Some(byte) if byte.is_ascii_digit() || byte == b'-' => {
                 self.parse_i64().map(Arg::Number)
             }
-            Some(_) => self.parse_ident().map(Arg::Ident),
+            Some(_) => {
+                let slot = self.spans.len();
+                let mut path = self.parse_path()?;
+                self.spans.truncate(slot);
+                if let [Segment::Ident(_)] = path.as_slice()
+                    && let Some(Segment::Ident(ident)) = path.pop()
+                {
+                    return Ok(Arg::Ident(ident));
+                }
+                Ok(Arg::Path(path))
+            }
             None => Err(self.error("expected function argument")),
         }
     }
@@ -279,7 +466,9 @@ impl<'a> Parser<'a> {
 
 #[cfg(test)]
 mod tests {
+    use crate::error::Span;
     use crate::query::{Arg, Index, Segment, parse_query};
+    use crate::value::Value;
 
     #[test]
     fn parses_stage_name_index() {
@@ -297,6 +486,41 @@ mod tests {
         );
     }
 
+    #[test]
+    fn records_segment_spans_in_pre_order() {
+        let query = parse_query(r#"STAGE["builder"] .ARG.VERSION"#).expect("query should parse");
+        assert_eq!(
+            query.spans,
+            vec![Span::new(0, 16), Span::new(18, 21), Span::new(22, 29)]
+        );
+
+        let query = parse_query(r#"ARG.A // "x""#).expect("query should parse");
+        assert_eq!(
+            query.spans,
+            vec![
+                Span::new(0, 12),
+                Span::new(0, 3),
+                Span::new(4, 5),
+                Span::new(9, 12)
+            ]
+        );
+
+        let query = parse_query("STAGE[0].{x: (A // B), y: [C.D]}").expect("query should parse");
+        assert_eq!(
+            query.spans,
+            vec![
+                Span::new(0, 8),
+                Span::new(9, 32),
+                Span::new(13, 21),
+                Span::new(14, 15),
+                Span::new(19, 20),
+                Span::new(26, 31),
+                Span::new(27, 28),
+                Span::new(29, 30)
+            ]
+        );
+    }
+
     #[test]
     fn parses_wildcard() {
         let query = parse_query("FROM[*].RESOLVED").expect("query should parse");
@@ -328,6 +552,108 @@ mod tests {
         );
     }
 
+    #[test]
+    fn parses_object_projection() {
+        let query = parse_query("STAGE[*].{name: NAME, \"base image\": PARENT.RESOLVED}")
+            .expect("query should parse");
+        assert_eq!(
+            query.segments,
+            vec![
+                Segment::Indexed {
+                    ident: "STAGE".to_string(),
+                    index: Index::Wildcard,
+                },
+                Segment::Object(vec![
+                    ("name".to_string(), vec![Segment::Ident("NAME".to_string())]),
+                    (
+                        "base image".to_string(),
+                        vec![
+                            Segment::Ident("PARENT".to_string()),
+                            Segment::Ident("RESOLVED".to_string()),
+                        ]
+                    ),
+                ]),
+            ]
+        );
+    }
+
+    #[test]
+    fn parses_array_construction() {
+        let query = parse_query("[ARG.VERSION, FROM[0].RESOLVED]").expect("query should parse");
+        assert_eq!(
+            query.segments,
+            vec![Segment::Array(vec![
+                vec![
+                    Segment::Ident("ARG".to_string()),
+                    Segment::Ident("VERSION".to_string()),
+                ],
+                vec![
+                    Segment::Indexed {
+                        ident: "FROM".to_string(),
+                        index: Index::Position(0),
+                    },
+                    Segment::Ident("RESOLVED".to_string()),
+                ],
+            ])]
+        );
+    }
+
+    #[test]
+    fn rejects_segments_after_projection() {
+        let error = parse_query("STAGE[0].{name: NAME}.name").expect_err("query should fail");
+        assert!(
+            error
+                .to_string()
+                .contains("projection must be the last segment")
+        );
+    }
+
+    #[test]
+    fn parses_alternative_with_literal() {
+        let query = parse_query(r#"STAGE[0].ARG.FOO // "fallback""#).expect("query should parse");
+        assert_eq!(
+            query.segments,
+            vec![Segment::Alternative(vec![
+                vec![
+                    Segment::Indexed {
+                        ident: "STAGE".to_string(),
+                        index: Index::Position(0),
+                    },
+                    Segment::Ident("ARG".to_string()),
+                    Segment::Ident("FOO".to_string()),
+                ],
+                vec![Segment::Literal(Value::String("fallback".to_string()))],
+            ])]
+        );
+    }
+
+    #[test]
+    fn parses_grouped_alternative_and_path_argument() {
+        let query = parse_query("STAGE[*].(ARG.FOO // 0)").expect("grouped query should parse");
+        assert_eq!(
+            query.segments[1],
+            Segment::Alternative(vec![
+                vec![
+                    Segment::Ident("ARG".to_string()),
+                    Segment::Ident("FOO".to_string()),
+                ],
+                vec![Segment::Literal(Value::Number(0))],
+            ])
+        );
+
+        let exists = parse_query("EXISTS(ARG.FOO)").expect("exists query should parse");
+        assert_eq!(
+            exists.segments,
+            vec![Segment::Function {
+                ident: "EXISTS".to_string(),
+                args: vec![Arg::Path(vec![
+                    Segment::Ident("ARG".to_string()),
+                    Segment::Ident("FOO".to_string()),
+                ])],
+            }]
+        );
+    }
+
     #[test]
     fn rejects_invalid_query() {
         let error = parse_query("ARG.").expect_err("query should fail");
@@ -0,0 +1,340 @@
+use std::collections::BTreeMap;
+
+use crate::lint::{Finding, RuleInfo, Severity};
+use crate::policy::{PolicyRule, RuleOutcome};
+use crate::value::Value;
+
+const SARIF_SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReportRule {
+    pub id: String,
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub severity: Severity,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReportResult {
+    pub path: String,
+    pub rule_id: String,
+    pub severity: Severity,
+    pub message: String,
+    pub lines: Option<(usize, usize)>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Report {
+    pub rules: Vec<ReportRule>,
+    pub paths: Vec<String>,
+    pub results: Vec<ReportResult>,
+}
+
+impl From<&RuleInfo> for ReportRule {
+    fn from(rule: &RuleInfo) -> Self {
+        Self {
+            id: rule.id.to_string(),
+            name: Some(rule.name.to_string()),
+            description: Some(rule.description.to_string()),
+            severity: rule.severity,
+        }
+    }
+}
+
+impl From<&PolicyRule> for ReportRule {
+    fn from(rule: &PolicyRule) -> Self {
+        Self {
+            id: rule.id.clone(),
+            name: None,
+            description: rule.description.clone(),
+            severity: rule.severity,
+        }
+    }
+}
+
+impl ReportResult {
+    pub fn from_finding(path: &str, finding: &Finding) -> Self {
+        Self {
+            path: path.to_string(),
+            rule_id: finding.rule.id.to_string(),
+            severity: finding.rule.severity,
+            message: finding.message.clone(),
+            lines: Some((finding.start_line, finding.end_line)),
+        }
+    }
+
+    pub fn from_outcome(path: &str, outcome: &RuleOutcome) -> Option<Self> {
+        Some(Self {
+            path: path.to_string(),
+            rule_id: outcome.rule.id.clone(),
+            severity: outcome.rule.severity,
+            message: outcome.failure.clone()?,
+            lines: outcome.lines,
+        })
+    }
+}
+
+impl Report {
+    pub fn to_sarif(&self) -> Value {
+        let rules = self
+            .rules
+            .iter()
+            .map(|rule| {
+                let mut out = object([
+                    ("id", string(&rule.id)),
+                    (
+                        "defaultConfiguration",
+                        object([("level", string(sarif_level(rule.severity)))]),
+                    ),
+                ]);
+                if let Value::Object(map) = &mut out {
+                    if let Some(name) = &rule.name {
+                        map.insert("name".to_string(), string(name));
+                    }
+                    if let Some(description) = &rule.description {
+                        map.insert(
+                            "shortDescription".to_string(),
+                            object([("text", string(description))]),
+                        );
+                    }
+                }
+                out
+            })
+            .collect();
+
+        let results = self
+            .results
+            .iter()
+            .map(|result| {
+                let mut location = object([(
+                    "artifactLocation",
+                    object([("uri", string(&artifact_uri(&result.path)))]),
+                )]);
+                if let (Value::Object(map), Some((start, end))) = (&mut location, result.lines) {
+                    map.insert(
+                        "region".to_string(),
+                        object([
+                            ("startLine", Value::Number(start as i64)),
+                            ("endLine", Value::Number(end as i64)),
+                        ]),
+                    );
+                }
+                let mut out = object([
+                    ("ruleId", string(&result.rule_id)),
+                    ("level", string(sarif_level(result.severity))),
+                    ("message", object([("text", string(&result.message))])),
+                    (
+                        "locations",
+                        Value::Array(vec![object([("physicalLocation", location)])]),
+                    ),
+                ]);
+                if let (Value::Object(map), Some(index)) = (
+                    &mut out,
+                    self.rules.iter().position(|rule| rule.id == result.rule_id),
+                ) {
+                    map.insert("ruleIndex".to_string(), Value::Number(index as i64));
+                }
+                out
+            })
+            .collect();
+
+        object([
+            ("$schema", string(SARIF_SCHEMA)),
+            ("version", string("2.1.0")),
+            (
+                "runs",
+                Value::Array(vec![object([
+                    (
+                        "tool",
+                        object([(
+                            "driver",
+                            object([
+                                ("name", string("dfq")),
+                                ("version", string(env!("CARGO_PKG_VERSION"))),
+                                ("rules", Value::Array(rules)),
+                            ]),
+                        )]),
+                    ),
+                    ("results", Value::Array(results)),
+                ])]),
+            ),
+        ])
+    }
+
+    pub fn to_junit(&self, name: &str) -> String {
+        let mut suites = String::new();
+        let mut total_failures = 0;
+        for path in &self.paths {
+            let mut cases = String::new();
+            let mut failures = 0;
+            for rule in &self.rules {
+                let results: Vec<&ReportResult> = self
+                    .results
+                    .iter()
+                    .filter(|result| result.path == *path && result.rule_id == rule.id)
+                    .collect();
+                let case_name = match &rule.name {
+                    Some(name) => format!("{} {name}", rule.id),
+                    None => rule.id.clone(),
+                };
+                cases.push_str(&format!(
+                    "    <testcase name=\"{}\" classname=\"{}\"",
+                    escape_xml(&case_name),
+                    escape_xml(path)
+                ));
+                let Some(first) = results.first() else {
+                    cases.push_str("/>\n");
+                    continue;
+                };
+                failures += 1;
+                let details: Vec<String> = results
+                    .iter()
+                    .map(|result| match result.lines {
+                        Some((start, end)) if start == end => {
+                            format!("{path}:{start}: {}", result.message)
+                        }
+                        Some((start, end)) => format!("{path}:{start}-{end}: {}", result.message),
+                        None => format!("{path}: {}", result.message),
+                    })
+                    .collect();
+                cases.push_str(&format!(
+                    ">\n      <failure message=\"{}\" type=\"{}\">{}</failure>\n    </testcase>\n",
+                    escape_xml(&first.message),
+                    first.severity.as_str(),
+                    escape_xml(&details.join("\n"))
+                ));
+            }
+            total_failures += failures;
+            suites.push_str(&format!(
+                "  <testsuite name=\"{}\" tests=\"{}\" failures=\"{failures}\" errors=\"0\">\n{cases}  </testsuite>\n",
+                escape_xml(path),
+                self.rules.len()
+            ));
+        }
+        format!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<testsuites name=\"{}\" tests=\"{}\" failures=\"{total_failures}\" errors=\"0\">\n{suites}</testsuites>\n",
+            escape_xml(name),
+            self.rules.len() * self.paths.len()
+        )
+    }
+}
+
+fn sarif_level(severity: Severity) -> &'static str {
+    match severity {
+        Severity::Info => "note",
+        Severity::Warning => "warning",
+        Severity::Error => "error",
+    }
+}
+
+fn artifact_uri(path: &str) -> String {
+    let path = path.replace('\\', "/");
+    let path = path.strip_prefix("./").unwrap_or(&path);
+    path.replace('%', "%25").replace(' ', "%20")
+}
+
+fn escape_xml(input: &str) -> String {
+    let mut out = String::with_capacity(input.len());
+    for ch in input.chars() {
+        match ch {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&apos;"),
+            '\n' | '\t' | '\r' => out.push(ch),
+            ch if ch.is_control() => {}
+            ch => out.push(ch),
+        }
+    }
+    out
+}
+
+fn object<const N: usize>(entries: [(&str, Value); N]) -> Value {
+    Value::Object(
+        entries
+            .into_iter()
+            .map(|(key, value)| (key.to_string(), value))
+            .collect::<BTreeMap<_, _>>(),
+    )
+}
+
+fn string(value: &str) -> Value {
+    Value::String(value.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::lint::Severity;
+    use crate::report::{Report, ReportResult, ReportRule};
+
+    fn report() -> Report {
+        Report {
+            rules: vec![
+                ReportRule {
+                    id: "DFQ001".to_string(),
+                    name: Some("unpinned-base-image".to_string()),
+                    description: Some("FROM uses an unpinned image".to_string()),
+                    severity: Severity::Warning,
+                },
+                ReportRule {
+                    id: "registry".to_string(),
+                    name: None,
+                    description: None,
+                    severity: Severity::Error,
+                },
+            ],
+            paths: vec!["./app/Dockerfile".to_string(), "Dockerfile".to_string()],
+            results: vec![
+                ReportResult {
+                    path: "./app/Dockerfile".to_string(),
+                    rule_id: "DFQ001".to_string(),
+                    severity: Severity::Warning,
+                    message: "base image <ubuntu> has no tag".to_string(),
+                    lines: Some((2, 3)),
+                },
+                ReportResult {
+                    path: "Dockerfile".to_string(),
+                    rule_id: "registry".to_string(),
+                    severity: Severity::Error,
+                    message: "expected \"registry.internal\"".to_string(),
+                    lines: None,
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn renders_sarif_results_with_rule_metadata() {
+        let sarif = report().to_sarif().to_json_string();
+        assert!(sarif.starts_with(
+            "{\"$schema\":\"https://json.schemastore.org/sarif-2.1.0.json\",\"runs\":[{\"results\":[{\"level\":\"warning\",\"locations\":[{\"physicalLocation\":{\"artifactLocation\":{\"uri\":\"app/Dockerfile\"},\"region\":{\"endLine\":3,\"startLine\":2}}}],\"message\":{\"text\":\"base image <ubuntu> has no tag\"},\"ruleId\":\"DFQ001\",\"ruleIndex\":0},{\"level\":\"error\",\"locations\":[{\"physicalLocation\":{\"artifactLocation\":{\"uri\":\"Dockerfile\"}}}]"
+        ));
+        assert!(sarif.contains(
+            "\"rules\":[{\"defaultConfiguration\":{\"level\":\"warning\"},\"id\":\"DFQ001\",\"name\":\"unpinned-base-image\",\"shortDescription\":{\"text\":\"FROM uses an unpinned image\"}},{\"defaultConfiguration\":{\"level\":\"error\"},\"id\":\"registry\"}]"
+        ));
+        assert!(sarif.ends_with("\"version\":\"2.1.0\"}"));
+    }
+
+    #[test]
+    fn renders_junit_suites_per_file() {
+        assert_eq!(
+            report().to_junit("dfq lint"),
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+             <testsuites name=\"dfq lint\" tests=\"4\" failures=\"2\" errors=\"0\">\n  \
+             <testsuite name=\"./app/Dockerfile\" tests=\"2\" failures=\"1\" errors=\"0\">\n    \
+             <testcase name=\"DFQ001 unpinned-base-image\" classname=\"./app/Dockerfile\">\n      \
+             <failure message=\"base image &lt;ubuntu&gt; has no tag\" type=\"warning\">./app/Dockerfile:2-3: base image &lt;ubuntu&gt; has no tag</failure>\n    \
+             </testcase>\n    \
+             <testcase name=\"registry\" classname=\"./app/Dockerfile\"/>\n  \
+             </testsuite>\n  \
+             <testsuite name=\"Dockerfile\" tests=\"2\" failures=\"1\" errors=\"0\">\n    \
+             <testcase name=\"DFQ001 unpinned-base-image\" classname=\"Dockerfile\"/>\n    \
+             <testcase name=\"registry\" classname=\"Dockerfile\">\n      \
+             <failure message=\"expected &quot;registry.internal&quot;\" type=\"error\">Dockerfile: expected &quot;registry.internal&quot;</failure>\n    \
+             </testcase>\n  \
+             </testsuite>\n\
+             </testsuites>\n"
+        );
+    }
+}
@@ -0,0 +1,416 @@
+use crate::error::Error;
+use crate::value::Value;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Template {
+    nodes: Vec<Node>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Node {
+    Text(String),
+    Output(Expr),
+    If {
+        cond: Cond,
+        then: Vec<Node>,
+        otherwise: Vec<Node>,
+    },
+    Range {
+        target: Expr,
+        body: Vec<Node>,
+        otherwise: Vec<Node>,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Cond {
+    negated: bool,
+    expr: Expr,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Expr {
+    base: Base,
+    fields: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Base {
+    Dot,
+    Root,
+    Var(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+    Text(String),
+    Action(String),
+}
+
+impl Template {
+    pub fn parse(input: &str) -> Result<Self, Error> {
+        let tokens = tokenize(input)?;
+        let mut parser = Parser { tokens, pos: 0 };
+        let (nodes, terminator) = parser.parse_block()?;
+        if let Some(terminator) = terminator {
+            return Err(Error::template(format!(
+                "unexpected {{{{ {terminator} }}}}"
+            )));
+        }
+        Ok(Self { nodes })
+    }
+
+    pub fn render(&self, value: &Value) -> String {
+        let scope = Scope {
+            dot: value,
+            root: value,
+            vars: Vec::new(),
+        };
+        let mut out = String::new();
+        render_nodes(&self.nodes, &scope, &mut out);
+        out
+    }
+}
+
+struct Scope<'a> {
+    dot: &'a Value,
+    root: &'a Value,
+    vars: Vec<(&'static str, Value)>,
+}
+
+fn render_nodes(nodes: &[Node], scope: &Scope<'_>, out: &mut String) {
+    for node in nodes {
+        match node {
+            Node::Text(text) => out.push_str(text),
+            Node::Output(expr) => {
+                let value = lookup(expr, scope);
+                out.push_str(
+                    &value
+                        .render_scalar()
+                        .unwrap_or_else(|| value.to_json_string()),
+                );
+            }
+            Node::If {
+                cond,
+                then,
+                otherwise,
+            } => {
+                if is_truthy(&lookup(&cond.expr, scope)) != cond.negated {
+                    render_nodes(then, scope, out);
+                } else {
+                    render_nodes(otherwise, scope, out);
+                }
+            }
+            Node::Range {
+                target,
+                body,
+                otherwise,
+            } => {
+                let target = lookup(target, scope);
+                let items: Vec<(Value, &Value)> = match &target {
+                    Value::Array(values) => values
+                        .iter()
+                        .enumerate()
+                        .map(|(index, value)| (Value::Number(index as i64), value))
+                        .collect(),
+                    Value::Object(map) => map
+                        .iter()
+                        .map(|(key, value)| (Value::String(key.clone()), value))
+                        .collect(),
+                    _ => Vec::new(),
+                };
+                if items.is_empty() {
+                    render_nodes(otherwise, scope, out);
+                    continue;
+                }
+
+                for (key, value) in items {
+                    let name = if matches!(target, Value::Array(_)) {
+                        "index"
+                    } else {
+                        "key"
+                    };
+                    let mut vars = scope.vars.clone();
+                    vars.push((name, key));
+                    let inner = Scope {
+                        dot: value,
+                        root: scope.root,
+                        vars,
+                    };
+                    render_nodes(body, &inner, out);
+                }
+            }
+        }
+    }
+}
+
+fn lookup(expr: &Expr, scope: &Scope<'_>) -> Value {
+    let mut current = match &expr.base {
+        Base::Dot => scope.dot.clone(),
+        Base::Root => scope.root.clone(),
+        Base::Var(name) => scope
+            .vars
+            .iter()
+            .rev()
+            .find(|(var, _)| var == name)
+            .map_or(Value::Null, |(_, value)| value.clone()),
+    };
+
+    for field in &expr.fields {
+        current = match current {
+            Value::Object(mut map) => map.remove(field).unwrap_or(Value::Null),
+            Value::Array(mut values) => match field.parse::<usize>() {
+                Ok(index) if index < values.len() => values.swap_remove(index),
+                _ => Value::Null,
+            },
+            _ => Value::Null,
+        };
+    }
+    current
+}
+
+fn is_truthy(value: &Value) -> bool {
+    match value {
+        Value::Null => false,
+        Value::Bool(value) => *value,
+        Value::Number(value) => *value != 0,
+        Value::String(value) => !value.is_empty(),
+        Value::Array(values) => !values.is_empty(),
+        Value::Object(map) => !map.is_empty(),
+    }
+}
+
+fn tokenize(input: &str) -> Result<Vec<Token>, Error> {
+    let mut tokens = Vec::new();
+    let mut rest = input;
+    let mut trim_next = false;
+
+    while !rest.is_empty() {
+        let Some(open) = rest.find("{{") else {
+            push_text(&mut tokens, rest, trim_next, false);
+            break;
+        };
+
+        let after_open = &rest[open + 2..];
+        let trim_before = after_open.starts_with("- ") || after_open.starts_with("-\n");
+        push_text(&mut tokens, &rest[..open], trim_next, trim_before);
+
+        let Some(close) = after_open.find("}}") else {
+            return Err(Error::template("unterminated {{ action"));
+        };
+        let mut action = &after_open[..close];
+        if trim_before {
+            action = &action[1..];
+        }
+        trim_next = action.ends_with(" -") || action.ends_with("\n-");
+        if trim_next {
+            action = &action[..action.len() - 1];
+        }
+
+        let action = action.trim();
+        if !(action.starts_with("/*") && action.ends_with("*/")) {
+            tokens.push(Token::Action(action.to_string()));
+        }
+        rest = &after_open[close + 2..];
+    }
+
+    Ok(tokens)
+}
+
+fn push_text(tokens: &mut Vec<Token>, text: &str, trim_start: bool, trim_end: bool) {
+    let mut text = text;
+    if trim_start {
+        text = text.trim_start();
+    }
+    if trim_end {
+        text = text.trim_end();
+    }
+    if !text.is_empty() {
+        tokens.push(Token::Text(text.to_string()));
+    }
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn parse_block(&mut self) -> Result<(Vec<Node>, Option<String>), Error> {
+        let mut nodes = Vec::new();
+
+        while let Some(token) = self.tokens.get(self.pos).cloned() {
+            self.pos += 1;
+            let action = match token {
+                Token::Text(text) => {
+                    nodes.push(Node::Text(text));
+                    continue;
+                }
+                Token::Action(action) => action,
+            };
+
+            if action == "end" || action == "else" || action.starts_with("else ") {
+                return Ok((nodes, Some(action)));
+            }
+
+            if let Some(cond) = action.strip_prefix("if ") {
+                nodes.push(self.parse_if(cond)?);
+            } else if let Some(target) = action.strip_prefix("range ") {
+                let target = parse_expr(target)?;
+                let (body, terminator) = self.parse_block()?;
+                let otherwise = match terminator.as_deref() {
+                    Some("end") => Vec::new(),
+                    Some("else") => self.parse_until_end()?,
+                    _ => return Err(Error::template("range is missing {{ end }}")),
+                };
+                nodes.push(Node::Range {
+                    target,
+                    body,
+                    otherwise,
+                });
+            } else {
+                nodes.push(Node::Output(parse_expr(&action)?));
+            }
+        }
+
+        Ok((nodes, None))
+    }
+
+    fn parse_if(&mut self, cond: &str) -> Result<Node, Error> {
+        let cond = parse_cond(cond)?;
+        let (then, terminator) = self.parse_block()?;
+        let otherwise = match terminator.as_deref() {
+            Some("end") => Vec::new(),
+            Some("else") => self.parse_until_end()?,
+            Some(other) => match other.strip_prefix("else if ") {
+                Some(next) => vec![self.parse_if(next)?],
+                None => return Err(Error::template(format!("unexpected {{{{ {other} }}}}"))),
+            },
+            None => return Err(Error::template("if is missing {{ end }}")),
+        };
+        Ok(Node::If {
+            cond,
+            then,
+            otherwise,
+        })
+    }
+
+    fn parse_until_end(&mut self) -> Result<Vec<Node>, Error> {
+        let (nodes, terminator) = self.parse_block()?;
+        match terminator.as_deref() {
+            Some("end") => Ok(nodes),
+            Some(other) => Err(Error::template(format!("unexpected {{{{ {other} }}}}"))),
+            None => Err(Error::template("block is missing {{ end }}")),
+        }
+    }
+}
+
+fn parse_cond(input: &str) -> Result<Cond, Error> {
+    let input = input.trim();
+    match input.strip_prefix("not ") {
+        Some(rest) => Ok(Cond {
+            negated: true,
+            expr: parse_expr(rest)?,
+        }),
+        None => Ok(Cond {
+            negated: false,
+            expr: parse_expr(input)?,
+        }),
+    }
+}
+
+fn parse_expr(input: &str) -> Result<Expr, Error> {
+    let input = input.trim();
+    let (base, path) = if let Some(rest) = input.strip_prefix('$') {
+        let name_end = rest.find('.').unwrap_or(rest.len());
+        let name = &rest[..name_end];
+        let base = match name {
+            "" => Base::Root,
+            "index" | "key" => Base::Var(name.to_string()),
+            _ => return Err(Error::template(format!("unknown variable '${name}'"))),
+        };
+        (base, &rest[name_end..])
+    } else if input.starts_with('.') {
+        (Base::Dot, input)
+    } else {
+        return Err(Error::template(format!(
+            "expected a field path like .name, found '{input}'"
+        )));
+    };
+
+    let mut fields = Vec::new();
+    if path != "." && !path.is_empty() {
+        for field in path[1..].split('.') {
+            let valid = !field.is_empty()
+                && field
+                    .chars()
+                    .all(|ch| ch.is_ascii_alphanumeric() || ch == '_' || ch == '-');
+            if !valid {
+                return Err(Error::template(format!("invalid field path '{input}'")));
+            }
+            fields.push(field.to_string());
+        }
+    }
+
+    Ok(Expr { base, fields })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use crate::template::Template;
+    use crate::value::Value;
+
+    fn stage() -> Value {
+        let mut parent = BTreeMap::new();
+        parent.insert(
+            "resolved".to_string(),
+            Value::String("alpine:3.22".to_string()),
+        );
+        let mut stage = BTreeMap::new();
+        stage.insert("name".to_string(), Value::String("builder".to_string()));
+        stage.insert("parent".to_string(), Value::Object(parent));
+        stage.insert(
+            "tags".to_string(),
+            Value::Array(vec![
+                Value::String("a".to_string()),
+                Value::String("b".to_string()),
+            ]),
+        );
+        Value::Object(stage)
+    }
+
+    #[test]
+    fn renders_fields_conditionals_and_ranges() {
+        let template = Template::parse(
+            "{{ .name }} -> {{ .parent.resolved }}\n\
+             {{- range .tags }} [{{ $index }}={{ . }}]{{ end }}\n\
+             {{- if not .missing }} ok{{ else }} bad{{ end }}",
+        )
+        .expect("template should parse");
+        assert_eq!(
+            template.render(&stage()),
+            "builder -> alpine:3.22 [0=a] [1=b] ok"
+        );
+    }
+
+    #[test]
+    fn renders_else_if_and_empty_range_fallback() {
+        let template = Template::parse(
+            "{{ if .nope }}a{{ else if .name }}b{{ else }}c{{ end }}\
+             {{ range .nope }}x{{ else }} none{{ end }}",
+        )
+        .expect("template should parse");
+        assert_eq!(template.render(&stage()), "b none");
+    }
+
+    #[test]
+    fn rejects_unbalanced_blocks() {
+        let error = Template::parse("{{ if .name }}x").expect_err("template should fail");
+        assert!(error.to_string().contains("if is missing {{ end }}"));
+
+        let error = Template::parse("{{ end }}").expect_err("template should fail");
+        assert!(error.to_string().contains("unexpected {{ end }}"));
+    }
+}
@@ -0,0 +1,67 @@
+use std::collections::BTreeMap;
+
+use crate::error::Error;
+use crate::value::Value;
+
+/// Parses a TOML document into its top-level table. Floats and datetimes have
+/// no `Value` counterpart and are kept as their TOML text.
+pub(crate) fn parse_toml(input: &str) -> Result<BTreeMap<String, Value>, Error> {
+    let table = input.parse::<toml::Table>().map_err(|error| {
+        let offset = error.span().map_or(0, |span| span.start);
+        let line = input[..offset.min(input.len())].matches('\n').count() + 1;
+        Error::config(format!("line {line}: {}", error.message()))
+    })?;
+    Ok(table
+        .into_iter()
+        .map(|(key, value)| (key, convert(value)))
+        .collect())
+}
+
+fn convert(value: toml::Value) -> Value {
+    match value {
+        toml::Value::String(text) => Value::String(text),
+        toml::Value::Integer(number) => Value::Number(number),
+        toml::Value::Boolean(flag) => Value::Bool(flag),
+        toml::Value::Float(number) => Value::String(number.to_string()),
+        toml::Value::Datetime(datetime) => Value::String(datetime.to_string()),
+        toml::Value::Array(items) => Value::Array(items.into_iter().map(convert).collect()),
+        toml::Value::Table(table) => Value::Object(
+            table
+                .into_iter()
+                .map(|(key, value)| (key, convert(value)))
+                .collect(),
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::toml::parse_toml;
+    use crate::value::Value;
+
+    #[test]
+    fn parses_tables_arrays_of_tables_and_values() {
+        let input = "# policy\ntitle = \"base images\"\n\n[[rule]]\nid = 'registry'\nall.equals = \"registry.internal\" # trailing\ncount = \"<= 3\"\n\n[[rule]]\nid = \"tags\"\nany = { matches = '''^\\d+$''' }\nlist = [\n  1,\n  -2,\n]\nstrict = true\n[meta.owner]\nname = \"platform\"\n";
+        let value = Value::Object(parse_toml(input).expect("toml should parse"));
+        assert_eq!(
+            value.to_json_string(),
+            "{\"meta\":{\"owner\":{\"name\":\"platform\"}},\"rule\":[{\"all\":{\"equals\":\"registry.internal\"},\"count\":\"<= 3\",\"id\":\"registry\"},{\"any\":{\"matches\":\"^\\\\d+$\"},\"id\":\"tags\",\"list\":[1,-2],\"strict\":true}],\"title\":\"base images\"}"
+        );
+    }
+
+    #[test]
+    fn reports_errors_with_line_numbers() {
+        let error = parse_toml("a = 1\na = 2\n").expect_err("duplicate key");
+        assert_eq!(error.to_string(), "config error: line 2: duplicate key");
+        let error = parse_toml("[rule]\nid = \"x\n").expect_err("unterminated");
+        assert_eq!(
+            error.to_string(),
+            "config error: line 2: invalid basic string, expected `\"`"
+        );
+        let error = parse_toml("id = \"x\" y\n").expect_err("trailing text");
+        assert_eq!(
+            error.to_string(),
+            "config error: line 1: unexpected key or value, expected newline, `#`"
+        );
+    }
+}
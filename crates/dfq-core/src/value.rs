@@ -1,5 +1,7 @@
 use std::collections::BTreeMap;
 
+use yaml_rust2::{Yaml, YamlEmitter};
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Value {
     Null,
@@ -48,44 +50,380 @@ impl Value {
     }
 
     pub fn to_json_string(&self) -> String {
-        match self {
-            Self::Null => "null".to_string(),
-            Self::Bool(value) => value.to_string(),
-            Self::Number(value) => value.to_string(),
-            Self::String(value) => {
-                let mut out = String::with_capacity(value.len() + 2);
-                out.push('"');
-                out.push_str(&escape_json(value));
-                out.push('"');
-                out
+        let mut out = String::new();
+        self.write_json(&mut out, None, 0);
+        out
+    }
+
+    pub fn to_json_pretty_string(&self) -> String {
+        let mut out = String::new();
+        self.write_json(&mut out, Some(2), 0);
+        out
+    }
+
+    pub fn to_yaml_string(&self) -> String {
+        let mut out = String::new();
+        let mut emitter = YamlEmitter::new(&mut out);
+        emitter.multiline_strings(true);
+        emitter
+            .dump(&self.to_yaml())
+            .expect("writing to a String cannot fail");
+        out.push('\n');
+        out
+    }
+
+    /// Renders an object as a TOML document. TOML has no null, so `null`
+    /// fields are dropped; callers reject arrays holding nulls beforehand.
+    pub fn to_toml_string(&self) -> Option<String> {
+        let Self::Object(map) = self else {
+            return None;
+        };
+
+        Some(toml_table(map).to_string())
+    }
+
+    pub fn to_table_string(&self, columns: &[String], header: bool) -> Option<String> {
+        let (names, rows) = self.tabulate(columns)?;
+        let escape = |cell: &str| cell.replace('\n', "\\n").replace('\t', "\\t");
+        let mut lines: Vec<Vec<String>> = Vec::with_capacity(rows.len() + 1);
+        if header {
+            lines.push(names);
+        }
+        lines.extend(
+            rows.into_iter()
+                .map(|row| row.iter().map(|cell| escape(cell)).collect()),
+        );
+
+        let mut widths = Vec::new();
+        for line in &lines {
+            widths.resize(widths.len().max(line.len()), 0);
+            for (index, cell) in line.iter().enumerate() {
+                widths[index] = widths[index].max(cell.chars().count());
+            }
+        }
+
+        let mut out = String::new();
+        for line in lines {
+            let mut rendered = String::new();
+            for (index, cell) in line.iter().enumerate() {
+                if index > 0 {
+                    rendered.push_str("  ");
+                }
+                rendered.push_str(cell);
+                let padding = widths[index] - cell.chars().count();
+                rendered.push_str(&" ".repeat(padding));
+            }
+            out.push_str(rendered.trim_end());
+            out.push('\n');
+        }
+        Some(out)
+    }
+
+    pub fn to_delimited_string(
+        &self,
+        delimiter: char,
+        columns: &[String],
+        header: bool,
+    ) -> Option<String> {
+        let (names, rows) = self.tabulate(columns)?;
+        let escape = |cell: &str| {
+            if delimiter == '\t' {
+                cell.replace('\\', "\\\\")
+                    .replace('\t', "\\t")
+                    .replace('\n', "\\n")
+                    .replace('\r', "\\r")
+            } else if cell.contains([delimiter, '"', '\n', '\r']) {
+                format!("\"{}\"", cell.replace('"', "\"\""))
+            } else {
+                cell.to_string()
             }
+        };
+
+        let mut out = String::new();
+        let header_row = header.then_some(names);
+        for row in header_row.into_iter().chain(rows) {
+            let cells: Vec<String> = row.iter().map(|cell| escape(cell)).collect();
+            out.push_str(&cells.join(&delimiter.to_string()));
+            out.push('\n');
+        }
+        Some(out)
+    }
+
+    pub fn stream_items(&self) -> &[Value] {
+        match self {
+            Self::Array(values) => values,
+            other => std::slice::from_ref(other),
+        }
+    }
+
+    fn write_json(&self, out: &mut String, indent: Option<usize>, depth: usize) {
+        match self {
+            Self::Null => out.push_str("null"),
+            Self::Bool(value) => out.push_str(&value.to_string()),
+            Self::Number(value) => out.push_str(&value.to_string()),
+            Self::String(value) => push_json_string(out, value),
             Self::Array(values) => {
-                let mut out = String::from("[");
+                out.push('[');
                 for (idx, value) in values.iter().enumerate() {
                     if idx > 0 {
                         out.push(',');
                     }
-                    out.push_str(&value.to_json_string());
+                    push_json_newline(out, indent, depth + 1);
+                    value.write_json(out, indent, depth + 1);
+                }
+                if !values.is_empty() {
+                    push_json_newline(out, indent, depth);
                 }
                 out.push(']');
-                out
             }
             Self::Object(map) => {
-                let mut out = String::from("{");
+                out.push('{');
                 for (idx, (key, value)) in map.iter().enumerate() {
                     if idx > 0 {
                         out.push(',');
                     }
-                    out.push('"');
-                    out.push_str(&escape_json(key));
-                    out.push_str("\":");
-                    out.push_str(&value.to_json_string());
+                    push_json_newline(out, indent, depth + 1);
+                    push_json_string(out, key);
+                    out.push(':');
+                    if indent.is_some() {
+                        out.push(' ');
+                    }
+                    value.write_json(out, indent, depth + 1);
+                }
+                if !map.is_empty() {
+                    push_json_newline(out, indent, depth);
                 }
                 out.push('}');
-                out
             }
         }
     }
+
+    fn tabulate(&self, columns: &[String]) -> Option<(Vec<String>, Vec<Vec<String>>)> {
+        let records: Vec<Vec<(String, String)>> = match self {
+            Self::Object(_) => vec![self.flatten_cells()],
+            Self::Array(values) => values.iter().map(Self::flatten_cells).collect(),
+            _ => return None,
+        };
+
+        let mut available: Vec<&String> = Vec::new();
+        for (name, _) in records.iter().flatten() {
+            if !available.contains(&name) {
+                available.push(name);
+            }
+        }
+
+        let names: Vec<String> = if columns.is_empty() {
+            available.into_iter().cloned().collect()
+        } else {
+            let mut names = Vec::new();
+            for column in columns {
+                let matched: Vec<String> = available
+                    .iter()
+                    .filter(|name| {
+                        name.as_str() == column.as_str()
+                            || name
+                                .strip_prefix(column.as_str())
+                                .is_some_and(|rest| rest.starts_with('.'))
+                    })
+                    .map(|name| name.to_string())
+                    .collect();
+                if matched.is_empty() {
+                    names.push(column.clone());
+                } else {
+                    names.extend(matched);
+                }
+            }
+            names
+        };
+
+        let rows = records
+            .into_iter()
+            .map(|record| {
+                names
+                    .iter()
+                    .map(|name| {
+                        record
+                            .iter()
+                            .find(|(key, _)| key == name)
+                            .map(|(_, cell)| cell.clone())
+                            .unwrap_or_default()
+                    })
+                    .collect()
+            })
+            .collect();
+        Some((names, rows))
+    }
+
+    fn flatten_cells(&self) -> Vec<(String, String)> {
+        let mut out = Vec::new();
+        match self {
+            Self::Object(_) => self.flatten_into("", &mut out),
+            other => out.push(("value".to_string(), other.render_text())),
+        }
+        out
+    }
+
+    fn flatten_into(&self, prefix: &str, out: &mut Vec<(String, String)>) {
+        match self {
+            Self::Object(map) => {
+                for (key, value) in map {
+                    let name = if prefix.is_empty() {
+                        key.clone()
+                    } else {
+                        format!("{prefix}.{key}")
+                    };
+                    value.flatten_into(&name, out);
+                }
+            }
+            other => out.push((prefix.to_string(), other.render_text())),
+        }
+    }
+
+    fn to_yaml(&self) -> Yaml {
+        match self {
+            Self::Null => Yaml::Null,
+            Self::Bool(value) => Yaml::Boolean(*value),
+            Self::Number(value) => Yaml::Integer(*value),
+            Self::String(value) => Yaml::String(value.clone()),
+            Self::Array(values) => Yaml::Array(values.iter().map(Self::to_yaml).collect()),
+            Self::Object(map) => Yaml::Hash(
+                map.iter()
+                    .map(|(key, value)| (Yaml::String(key.clone()), value.to_yaml()))
+                    .collect(),
+            ),
+        }
+    }
+
+    fn to_toml(&self) -> Option<toml::Value> {
+        match self {
+            Self::Null => None,
+            Self::Bool(value) => Some(toml::Value::Boolean(*value)),
+            Self::Number(value) => Some(toml::Value::Integer(*value)),
+            Self::String(value) => Some(toml::Value::String(value.clone())),
+            Self::Array(values) => Some(toml::Value::Array(
+                values.iter().filter_map(Self::to_toml).collect(),
+            )),
+            Self::Object(map) => Some(toml::Value::Table(toml_table(map))),
+        }
+    }
+
+    pub fn has_null_array_items(&self) -> bool {
+        match self {
+            Self::Array(items) => items
+                .iter()
+                .any(|item| matches!(item, Self::Null) || item.has_null_array_items()),
+            Self::Object(map) => map.values().any(Self::has_null_array_items),
+            _ => false,
+        }
+    }
+
+    pub fn to_env_string(&self, export: bool) -> Option<String> {
+        let Self::Object(map) = self else {
+            return None;
+        };
+
+        let mut out = String::new();
+        for (key, value) in map {
+            if export {
+                out.push_str("export ");
+            }
+            out.push_str(&env_key(key));
+            out.push('=');
+            out.push_str(&shell_quote(&value.render_text()));
+            out.push('\n');
+        }
+        Some(out)
+    }
+
+    pub fn to_github_output_string(&self) -> Option<String> {
+        let Self::Object(map) = self else {
+            return None;
+        };
+
+        let mut out = String::new();
+        for (key, value) in map {
+            let key = github_key(key);
+            let text = value.render_text();
+            if text.contains('\n') || text.contains('\r') {
+                let mut delimiter = "DFQ_EOF".to_string();
+                let mut attempt = 0;
+                while text.contains(&delimiter) {
+                    attempt += 1;
+                    delimiter = format!("DFQ_EOF_{attempt}");
+                }
+                out.push_str(&format!("{key}<<{delimiter}\n{text}\n{delimiter}\n"));
+            } else {
+                out.push_str(&format!("{key}={text}\n"));
+            }
+        }
+        Some(out)
+    }
+
+    fn render_text(&self) -> String {
+        self.render_scalar()
+            .unwrap_or_else(|| self.to_json_string())
+    }
+}
+
+fn push_json_string(out: &mut String, value: &str) {
+    out.push('"');
+    out.push_str(&escape_json(value));
+    out.push('"');
+}
+
+fn push_json_newline(out: &mut String, indent: Option<usize>, depth: usize) {
+    if let Some(width) = indent {
+        out.push('\n');
+        out.push_str(&" ".repeat(width * depth));
+    }
+}
+
+fn toml_table(map: &BTreeMap<String, Value>) -> toml::Table {
+    map.iter()
+        .filter_map(|(key, value)| Some((key.clone(), value.to_toml()?)))
+        .collect()
+}
+
+fn env_key(key: &str) -> String {
+    let mut out: String = key
+        .chars()
+        .map(|ch| {
+            if ch.is_ascii_alphanumeric() || ch == '_' {
+                ch
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    if !out.starts_with(|ch: char| ch.is_ascii_alphabetic() || ch == '_') {
+        out.insert(0, '_');
+    }
+    out
+}
+
+fn github_key(key: &str) -> String {
+    key.chars()
+        .map(|ch| {
+            if ch.is_ascii_alphanumeric() || ch == '_' || ch == '-' {
+                ch
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+fn shell_quote(input: &str) -> String {
+    let is_safe = |ch: char| ch.is_ascii_alphanumeric() || "@%+=:,./_-".contains(ch);
+    if !input.is_empty() && input.chars().all(is_safe) {
+        return input.to_string();
+    }
+
+    let mut out = String::with_capacity(input.len() + 2);
+    out.push('\'');
+    out.push_str(&input.replace('\'', "'\\''"));
+    out.push('\'');
+    out
 }
 
 fn escape_json(input: &str) -> String {
@@ -108,3 +446,131 @@ fn escape_json(input: &str) -> String {
     }
     out
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use crate::value::Value;
+
+    fn sample() -> Value {
+        let mut map = BTreeMap::new();
+        map.insert("VERSION".to_string(), Value::String("1.2.3".to_string()));
+        map.insert(
+            "greeting".to_string(),
+            Value::String("it's a test".to_string()),
+        );
+        map.insert("base-image".to_string(), Value::Null);
+        map.insert("script".to_string(), Value::String("a\nb".to_string()));
+        Value::Object(map)
+    }
+
+    fn stages() -> Value {
+        let mut parent = BTreeMap::new();
+        parent.insert(
+            "resolved".to_string(),
+            Value::String("alpine:3.22".to_string()),
+        );
+        let mut stage = BTreeMap::new();
+        stage.insert("index".to_string(), Value::Number(0));
+        stage.insert("name".to_string(), Value::Null);
+        stage.insert("parent".to_string(), Value::Object(parent));
+        stage.insert(
+            "tags".to_string(),
+            Value::Array(vec![
+                Value::String("1.0".to_string()),
+                Value::String("latest".to_string()),
+            ]),
+        );
+        Value::Array(vec![Value::Object(stage)])
+    }
+
+    #[test]
+    fn renders_pretty_json() {
+        assert_eq!(
+            stages().to_json_pretty_string(),
+            "[\n  {\n    \"index\": 0,\n    \"name\": null,\n    \"parent\": {\n      \"resolved\": \"alpine:3.22\"\n    },\n    \"tags\": [\n      \"1.0\",\n      \"latest\"\n    ]\n  }\n]"
+        );
+        assert_eq!(Value::Array(Vec::new()).to_json_pretty_string(), "[]");
+    }
+
+    #[test]
+    fn renders_yaml_blocks_and_quotes_ambiguous_scalars() {
+        assert_eq!(
+            stages().to_yaml_string(),
+            "---\n- index: 0\n  name: ~\n  parent:\n    resolved: \"alpine:3.22\"\n  tags:\n    - \"1.0\"\n    - latest\n"
+        );
+        assert_eq!(
+            Value::String("yes".to_string()).to_yaml_string(),
+            "---\n\"yes\"\n"
+        );
+    }
+
+    #[test]
+    fn renders_toml_tables_and_skips_nulls() {
+        let mut root = BTreeMap::new();
+        root.insert("query".to_string(), Value::String("STAGE".to_string()));
+        root.insert("stage".to_string(), stages());
+        assert_eq!(
+            Value::Object(root).to_toml_string(),
+            Some(
+                "query = \"STAGE\"\n\n[[stage]]\nindex = 0\ntags = [\"1.0\", \"latest\"]\n\n[stage.parent]\nresolved = \"alpine:3.22\"\n"
+                    .to_string()
+            )
+        );
+        assert_eq!(stages().to_toml_string(), None);
+        assert!(!stages().has_null_array_items());
+        assert!(
+            Value::Object(BTreeMap::from([(
+                "from".to_string(),
+                Value::Array(vec![Value::String("alpine".to_string()), Value::Null]),
+            )]))
+            .has_null_array_items()
+        );
+    }
+
+    #[test]
+    fn renders_tables_and_delimited_rows() {
+        assert_eq!(
+            stages().to_table_string(&[], true),
+            Some(
+                "index  name  parent.resolved  tags\n0            alpine:3.22      [\"1.0\",\"latest\"]\n"
+                    .to_string()
+            )
+        );
+
+        let columns = vec!["parent".to_string(), "index".to_string()];
+        assert_eq!(
+            stages().to_delimited_string(',', &columns, true),
+            Some("parent.resolved,index\nalpine:3.22,0\n".to_string())
+        );
+        assert_eq!(
+            stages().to_delimited_string(',', &["tags".to_string()], false),
+            Some("\"[\"\"1.0\"\",\"\"latest\"\"]\"\n".to_string())
+        );
+        assert_eq!(Value::Number(1).to_table_string(&[], true), None);
+    }
+
+    #[test]
+    fn renders_env_lines_with_shell_quoting() {
+        assert_eq!(
+            sample().to_env_string(true),
+            Some(
+                "export VERSION=1.2.3\nexport base_image=''\nexport greeting='it'\\''s a test'\nexport script='a\nb'\n"
+                    .to_string()
+            )
+        );
+        assert_eq!(Value::String("x".to_string()).to_env_string(false), None);
+    }
+
+    #[test]
+    fn renders_github_output_with_multiline_delimiters() {
+        assert_eq!(
+            sample().to_github_output_string(),
+            Some(
+                "VERSION=1.2.3\nbase-image=\ngreeting=it's a test\nscript<<DFQ_EOF\na\nb\nDFQ_EOF\n"
+                    .to_string()
+            )
+        );
+    }
+}
@@ -0,0 +1,157 @@
+use yaml_rust2::YamlLoader;
+use yaml_rust2::yaml::Yaml as Node;
+
+use crate::error::Error;
+
+/// A YAML document reduced to what compose files need: every scalar keeps
+/// its text, aliases are expanded, and `<<` merge keys are applied.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum Yaml {
+    Null,
+    Scalar(String),
+    Seq(Vec<Yaml>),
+    Map(Vec<(String, Yaml)>),
+}
+
+impl Yaml {
+    pub(crate) fn get(&self, key: &str) -> Option<&Yaml> {
+        match self {
+            Self::Map(entries) => entries
+                .iter()
+                .rev()
+                .find(|(name, _)| name == key)
+                .map(|(_, value)| value),
+            _ => None,
+        }
+    }
+}
+
+pub(crate) fn parse_yaml(input: &str) -> Result<Yaml, Error> {
+    let documents = YamlLoader::load_from_str(input).map_err(|error| {
+        Error::config(format!("line {}: {}", error.marker().line(), error.info()))
+    })?;
+    Ok(documents.into_iter().next().map_or(Yaml::Null, convert))
+}
+
+fn convert(node: Node) -> Yaml {
+    match node {
+        Node::Array(items) => Yaml::Seq(items.into_iter().map(convert).collect()),
+        Node::Hash(hash) => {
+            let mut merged = Vec::new();
+            let mut entries = Vec::new();
+            for (key, value) in hash {
+                match scalar(key) {
+                    Some(key) if key == "<<" => merged.extend(merge_sources(value)),
+                    Some(key) => entries.push((key, convert(value))),
+                    None => {}
+                }
+            }
+            merged.extend(entries);
+            Yaml::Map(merged)
+        }
+        node => scalar(node).map_or(Yaml::Null, Yaml::Scalar),
+    }
+}
+
+/// Entries contributed by a `<<` key, ordered so that explicit keys and the
+/// earlier maps of a merge sequence win lookups.
+fn merge_sources(value: Node) -> Vec<(String, Yaml)> {
+    let sources = match value {
+        Node::Array(items) => items.into_iter().rev().collect(),
+        other => vec![other],
+    };
+    sources
+        .into_iter()
+        .flat_map(|source| match convert(source) {
+            Yaml::Map(entries) => entries,
+            _ => Vec::new(),
+        })
+        .collect()
+}
+
+fn scalar(node: Node) -> Option<String> {
+    match node {
+        Node::String(text) | Node::Real(text) => Some(text),
+        Node::Integer(value) => Some(value.to_string()),
+        Node::Boolean(value) => Some(value.to_string()),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::yaml::{Yaml, parse_yaml};
+
+    fn scalar(text: &str) -> Yaml {
+        Yaml::Scalar(text.to_string())
+    }
+
+    #[test]
+    fn parses_block_and_flow_collections() {
+        let yaml = parse_yaml(
+            "services:\n  app:  # main service\n    build:\n      context: ./app\n      \
+             args:\n        - VERSION=1.0\n        - \"QUOTED=a # b\"\n      \
+             platforms: [linux/amd64, 'linux/arm64']\n    labels: {tier: web, empty: }\n",
+        )
+        .expect("yaml should parse");
+
+        let app = yaml
+            .get("services")
+            .and_then(|services| services.get("app"))
+            .expect("service should exist");
+        let build = app.get("build").expect("build should exist");
+        assert_eq!(build.get("context"), Some(&scalar("./app")));
+        assert_eq!(
+            build.get("args"),
+            Some(&Yaml::Seq(vec![
+                scalar("VERSION=1.0"),
+                scalar("QUOTED=a # b")
+            ]))
+        );
+        assert_eq!(
+            build.get("platforms"),
+            Some(&Yaml::Seq(vec![
+                scalar("linux/amd64"),
+                scalar("linux/arm64")
+            ]))
+        );
+        assert_eq!(
+            app.get("labels"),
+            Some(&Yaml::Map(vec![
+                ("tier".to_string(), scalar("web")),
+                ("empty".to_string(), Yaml::Null),
+            ]))
+        );
+    }
+
+    #[test]
+    fn resolves_anchors_merge_keys_and_block_scalars() {
+        let yaml = parse_yaml(
+            "x-build: &build\n  context: .\n  target: base\nservices:\n  - name: app\n    \
+             build:\n      <<: *build\n      target: runtime\n    command: |\n      echo one\n      \
+             echo two\n  - name: worker\n",
+        )
+        .expect("yaml should parse");
+
+        let Some(Yaml::Seq(services)) = yaml.get("services") else {
+            panic!("services should be a sequence");
+        };
+        let build = services[0].get("build").expect("build should exist");
+        assert_eq!(build.get("context"), Some(&scalar(".")));
+        assert_eq!(build.get("target"), Some(&scalar("runtime")));
+        assert_eq!(
+            services[0].get("command"),
+            Some(&scalar("echo one\necho two\n"))
+        );
+        assert_eq!(services[1].get("name"), Some(&scalar("worker")));
+    }
+
+    #[test]
+    fn rejects_unknown_aliases() {
+        let error = parse_yaml("a: *missing\n").expect_err("alias is unknown");
+        assert_eq!(
+            error.to_string(),
+            "config error: line 1: while parsing node, found unknown anchor"
+        );
+    }
+}
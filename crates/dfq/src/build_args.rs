@@ -0,0 +1,165 @@
+use std::collections::BTreeMap;
+
+use dfq_core::Error;
+
+use crate::{AppError, Cli};
+
+const ENV_PREFIX: &str = "DFQ_BUILD_ARG_";
+
+pub(crate) fn overrides(cli: &Cli) -> Result<BTreeMap<String, String>, AppError> {
+    let from_env = !cli.no_build_arg_env;
+    let mut overrides = cli.build_file_args.clone();
+
+    if from_env {
+        for (name, value) in std::env::vars_os() {
+            let (Some(name), Some(value)) = (name.to_str(), value.to_str()) else {
+                continue;
+            };
+            if let Some(key) = name.strip_prefix(ENV_PREFIX)
+                && !key.is_empty()
+            {
+                overrides.insert(key.to_string(), value.to_string());
+            }
+        }
+    }
+
+    for path in &cli.build_arg_files {
+        let contents = std::fs::read_to_string(path).map_err(Error::from)?;
+        overrides.extend(parse_env_file(path, &contents)?);
+    }
+
+    for arg in &cli.build_args {
+        if let Some((key, value)) = parse_build_arg(arg, from_env)? {
+            overrides.insert(key, value);
+        }
+    }
+    Ok(overrides)
+}
+
+pub(crate) fn parse_build_arg(
+    input: &str,
+    from_env: bool,
+) -> Result<Option<(String, String)>, AppError> {
+    if let Some((key, value)) = input.split_once('=') {
+        let key = key.trim();
+        if key.is_empty() {
+            return Err(AppError::usage("build-arg key cannot be empty"));
+        }
+        return Ok(Some((key.to_string(), value.to_string())));
+    }
+
+    let key = input.trim();
+    if key.is_empty() {
+        return Err(AppError::usage("build-arg key cannot be empty"));
+    }
+    if !from_env {
+        return Ok(Some((key.to_string(), String::new())));
+    }
+    Ok(std::env::var(key)
+        .ok()
+        .map(|value| (key.to_string(), value)))
+}
+
+pub(crate) fn parse_env_file(
+    path: &str,
+    contents: &str,
+) -> Result<Vec<(String, String)>, AppError> {
+    let mut entries = Vec::new();
+    let mut lines = contents.lines().enumerate();
+
+    while let Some((index, line)) = lines.next() {
+        let error = |message: &str| AppError::usage(format!("{path}:{}: {message}", index + 1));
+        let line = line.trim_start();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let line = line
+            .strip_prefix("export ")
+            .map(str::trim_start)
+            .unwrap_or(line);
+
+        let Some((key, rest)) = line.split_once('=') else {
+            return Err(error("expected KEY=VALUE"));
+        };
+        let key = key.trim_end();
+        if key.is_empty() || key.contains(char::is_whitespace) {
+            return Err(error(&format!("invalid build-arg key '{key}'")));
+        }
+
+        let rest = rest.trim_start();
+        let value = match rest.chars().next() {
+            Some(quote @ ('"' | '\'')) => {
+                let mut body = rest[1..].to_string();
+                let end = loop {
+                    if let Some(end) = closing_quote(&body, quote) {
+                        break end;
+                    }
+                    let Some((_, next)) = lines.next() else {
+                        return Err(error("unterminated quoted value"));
+                    };
+                    body.push('\n');
+                    body.push_str(next);
+                };
+                let trailing = body[end + 1..].trim_start();
+                if !trailing.is_empty() && !trailing.starts_with('#') {
+                    return Err(error("unexpected characters after quoted value"));
+                }
+                if quote == '"' {
+                    unescape(&body[..end])
+                } else {
+                    body[..end].to_string()
+                }
+            }
+            _ => strip_comment(rest).trim_end().to_string(),
+        };
+        entries.push((key.to_string(), value));
+    }
+    Ok(entries)
+}
+
+fn closing_quote(body: &str, quote: char) -> Option<usize> {
+    let mut escaped = false;
+    for (index, ch) in body.char_indices() {
+        match ch {
+            _ if escaped => escaped = false,
+            '\\' if quote == '"' => escaped = true,
+            _ if ch == quote => return Some(index),
+            _ => {}
+        }
+    }
+    None
+}
+
+fn unescape(body: &str) -> String {
+    let mut out = String::with_capacity(body.len());
+    let mut chars = body.chars();
+    while let Some(ch) = chars.next() {
+        if ch != '\\' {
+            out.push(ch);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => out.push('\n'),
+            Some('t') => out.push('\t'),
+            Some('r') => out.push('\r'),
+            Some(next @ ('"' | '\\' | '$')) => out.push(next),
+            Some(next) => {
+                out.push('\\');
+                out.push(next);
+            }
+            None => out.push('\\'),
+        }
+    }
+    out
+}
+
+fn strip_comment(value: &str) -> &str {
+    let mut previous = None;
+    for (index, ch) in value.char_indices() {
+        if ch == '#' && previous.is_some_and(char::is_whitespace) {
+            return &value[..index];
+        }
+        previous = Some(ch);
+    }
+    value
+}
@@ -0,0 +1,112 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use dfq_core::Error;
+use dfq_core::bake::load_bake;
+use dfq_core::compose::load_compose;
+
+use crate::build_args::parse_env_file;
+use crate::{AppError, Cli};
+
+pub(crate) fn validate(cli: &Cli) -> Result<(), AppError> {
+    if cli.bake.is_some() && cli.compose.is_some() {
+        return Err(AppError::usage(
+            "--bake is mutually exclusive with --compose",
+        ));
+    }
+    for (flag, set) in [
+        ("--bake", cli.bake.is_some()),
+        ("--compose", cli.compose.is_some()),
+    ] {
+        if set && (!cli.files.is_empty() || !cli.recursive.is_empty() || cli.stdin) {
+            return Err(AppError::usage(format!(
+                "{flag} is mutually exclusive with --file, --recursive, and --stdin"
+            )));
+        }
+    }
+    if cli.bake.is_none() && cli.bake_target.is_some() {
+        return Err(AppError::usage("--bake-target requires --bake"));
+    }
+    if cli.compose.is_none() && cli.service.is_some() {
+        return Err(AppError::usage("--service requires --compose"));
+    }
+    Ok(())
+}
+
+pub(crate) fn apply(cli: &mut Cli) -> Result<(), AppError> {
+    if let Some(path) = cli.bake.clone() {
+        let source = std::fs::read_to_string(&path).map_err(Error::from)?;
+        let name = cli.bake_target.as_deref().unwrap_or("default");
+        let bake =
+            load_bake(&source, name, &process_env()).map_err(|error| with_path(error, &path))?;
+        configure(
+            cli,
+            bake.dockerfile_path().to_string_lossy().into_owned(),
+            &bake.platforms,
+            bake.args,
+        );
+    }
+
+    if let Some(path) = cli.compose.clone() {
+        let source = std::fs::read_to_string(&path).map_err(Error::from)?;
+        let project_dir = Path::new(&path).parent().unwrap_or(Path::new(""));
+        let mut env = BTreeMap::new();
+        let dotenv = project_dir.join(".env");
+        if let Ok(contents) = std::fs::read_to_string(&dotenv) {
+            env.extend(parse_env_file(&dotenv.to_string_lossy(), &contents)?);
+        }
+        env.extend(process_env());
+
+        let build = load_compose(&source, cli.service.as_deref(), &env)
+            .map_err(|error| with_path(error, &path))?;
+        configure(
+            cli,
+            build
+                .dockerfile_path(project_dir)
+                .to_string_lossy()
+                .into_owned(),
+            &build.platforms,
+            build.args,
+        );
+    }
+    Ok(())
+}
+
+fn configure(
+    cli: &mut Cli,
+    dockerfile: String,
+    platforms: &[String],
+    args: BTreeMap<String, String>,
+) {
+    cli.files = vec![dockerfile];
+    if let Some(platform) = platforms.first() {
+        cli.build_file_args.extend(platform_args(platform));
+    }
+    cli.build_file_args.extend(args);
+}
+
+fn process_env() -> BTreeMap<String, String> {
+    std::env::vars_os()
+        .filter_map(|(name, value)| Some((name.into_string().ok()?, value.into_string().ok()?)))
+        .collect()
+}
+
+fn with_path(error: Error, path: &str) -> Error {
+    match error {
+        Error::Config { msg } => Error::config(format!("{path}: {msg}")),
+        other => other,
+    }
+}
+
+fn platform_args(platform: &str) -> [(String, String); 4] {
+    let mut parts = platform.splitn(3, '/');
+    let os = parts.next().unwrap_or_default();
+    let arch = parts.next().unwrap_or_default();
+    let variant = parts.next().unwrap_or_default();
+    [
+        ("TARGETPLATFORM".to_string(), platform.to_string()),
+        ("TARGETOS".to_string(), os.to_string()),
+        ("TARGETARCH".to_string(), arch.to_string()),
+        ("TARGETVARIANT".to_string(), variant.to_string()),
+    ]
+}
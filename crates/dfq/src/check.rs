@@ -0,0 +1,129 @@
+use dfq_core::Error;
+use dfq_core::eval::Evaluator;
+use dfq_core::exit_code::ExitCode;
+use dfq_core::lint::Severity;
+use dfq_core::parser::parse_dockerfile;
+use dfq_core::policy::{Policy, RuleOutcome};
+use dfq_core::report::{Report, ReportResult, ReportRule};
+use dfq_core::value::Value;
+
+use crate::lint::{inputs, read_input, report_format, write_payload, write_report};
+use crate::{
+    AppError, Cli, OutputFormat, build_args, context, dockerfile_path, scan, write_scalar_items,
+};
+
+pub(crate) fn run(mut cli: Cli, paths: Vec<String>, policy: &str) -> Result<(), AppError> {
+    let format = report_format(&cli, "check")?;
+    let policy = load_policy(policy)?;
+    let inputs = inputs(&mut cli, paths, "check")?;
+    if let Some(dir) = &cli.context {
+        let dockerfile = (!cli.stdin && !scan::is_multi(&cli)).then(|| dockerfile_path(&cli));
+        cli.build_context = Some(context::load(dir, dockerfile)?);
+    }
+    let overrides = build_args::overrides(&cli)?;
+
+    let mut checked = Vec::new();
+    let mut outcomes = Vec::new();
+    let mut failure = None;
+    for path in &inputs {
+        match read_input(&cli, path).and_then(|contents| Ok(parse_dockerfile(&contents)?)) {
+            Ok(model) => {
+                checked.push(path.clone());
+                let evaluator = Evaluator::new(&model, &overrides, cli.strict)
+                    .with_context(cli.build_context.as_ref());
+                outcomes.extend(
+                    policy
+                        .check(&evaluator)
+                        .into_iter()
+                        .map(|outcome| (path.as_str(), outcome)),
+                );
+            }
+            Err(error) => {
+                eprintln!("{path}: {}", error.message);
+                failure.get_or_insert(AppError {
+                    message: String::new(),
+                    ..error
+                });
+            }
+        }
+    }
+
+    if matches!(format, OutputFormat::Sarif | OutputFormat::Junit) {
+        let report = Report {
+            rules: policy.rules.iter().map(ReportRule::from).collect(),
+            paths: checked,
+            results: outcomes
+                .iter()
+                .filter_map(|(path, outcome)| ReportResult::from_outcome(path, outcome))
+                .collect(),
+        };
+        write_report(format, &report, "dfq check")?;
+    } else {
+        write_outcomes(format, &outcomes)?;
+    }
+    if let Some(failure) = failure {
+        return Err(failure);
+    }
+    if outcomes
+        .iter()
+        .any(|(_, outcome)| !outcome.passed() && outcome.rule.severity == Severity::Error)
+    {
+        return Err(AppError {
+            code: ExitCode::Policy,
+            message: String::new(),
+            kind: "policy",
+            span: None,
+            path: None,
+            query: None,
+        });
+    }
+    Ok(())
+}
+
+fn load_policy(path: &str) -> Result<Policy, AppError> {
+    let contents = std::fs::read_to_string(path).map_err(Error::from)?;
+    Policy::parse(&contents).map_err(|error| match error {
+        Error::Config { msg } => Error::config(format!("{path}: {msg}")).into(),
+        other => other.into(),
+    })
+}
+
+fn write_outcomes(format: OutputFormat, outcomes: &[(&str, RuleOutcome)]) -> Result<(), AppError> {
+    if format == OutputFormat::Text {
+        let mut lines: Vec<String> = outcomes
+            .iter()
+            .filter_map(|(path, outcome)| {
+                let message = outcome.failure.as_ref()?;
+                let rule = outcome.rule;
+                Some(match &rule.description {
+                    Some(description) => format!(
+                        "{path}: {}[{}]: {description}: {message}",
+                        rule.severity.as_str(),
+                        rule.id
+                    ),
+                    None => format!("{path}: {}[{}]: {message}", rule.severity.as_str(), rule.id),
+                })
+            })
+            .collect();
+        let failed = lines.len();
+        lines.push(format!(
+            "{} passed, {failed} failed",
+            outcomes.len() - failed
+        ));
+        return write_scalar_items(&lines, false, false);
+    }
+
+    let payload = Value::Array(
+        outcomes
+            .iter()
+            .map(|(path, outcome)| {
+                let mut value = outcome.to_value();
+                if let Value::Object(map) = &mut value {
+                    map.insert("path".to_string(), Value::String(path.to_string()));
+                }
+                value
+            })
+            .collect(),
+    );
+    write_payload(format, &payload)
+}
@@ -0,0 +1,191 @@
+use std::io::Write;
+
+use dfq_core::Error;
+use dfq_core::complete::complete_query;
+use dfq_core::parser::parse_dockerfile;
+
+use crate::{AppError, CompletionShell};
+
+const VALUE_OPTIONS: &[&str] = &[
+    "-f",
+    "--file",
+    "-r",
+    "--recursive",
+    "--context",
+    "--bake",
+    "--bake-target",
+    "--compose",
+    "--service",
+    "--build-arg",
+    "--build-arg-file",
+    "--error-stream",
+    "--format",
+    "--columns",
+    "--template",
+    "--template-file",
+    "--query-file",
+    "--watch-interval",
+    "--on-change",
+];
+
+const BASH_QUERIES: &str = r#"
+_dfq_queries() {
+    local cur="${COMP_WORDS[COMP_CWORD]}"
+    local IFS=$'\n'
+    local candidates=($(dfq complete-query "${COMP_LINE:0:COMP_POINT}" "$cur" 2>/dev/null))
+    if [[ ${#candidates[@]} -gt 0 ]]; then
+        COMPREPLY=("${candidates[@]}")
+        compopt -o nospace 2>/dev/null
+        return 0
+    fi
+    _dfq "$@"
+}
+
+complete -F _dfq_queries -o bashdefault -o default dfq
+"#;
+
+const ZSH_QUERIES: &str = r#"_dfq_queries() {
+    local -a candidates
+    candidates=(${(f)"$(dfq complete-query "${BUFFER[1,CURSOR]}" 2>/dev/null)"})
+    compadd -S '' -- $candidates
+}
+
+"#;
+
+const FISH_QUERIES: &str = r#"
+complete -c dfq -n "__fish_dfq_needs_command" -f -a "(dfq complete-query (commandline -cp))"
+"#;
+
+/// Rewrites a fragment of clap_complete's output. Debug builds (and so the
+/// CLI tests) fail when the fragment is missing, which means a clap_complete
+/// upgrade changed the generated script and the rewrite needs updating.
+fn replace_generated(script: &str, from: &str, to: &str) -> String {
+    debug_assert!(
+        script.contains(from),
+        "generated completion script no longer contains {from}"
+    );
+    script.replace(from, to)
+}
+
+pub(crate) fn with_dynamic_queries(shell: CompletionShell, script: String) -> String {
+    match shell {
+        CompletionShell::Bash => script + BASH_QUERIES,
+        CompletionShell::Zsh => {
+            let script = replace_generated(
+                &script,
+                "'::queries -- Query expression:_default'",
+                "'::queries -- Query expression:_dfq_queries'",
+            );
+            let script = replace_generated(&script, ":QUERY:_default'", ":QUERY:_dfq_queries'");
+            match script.find("if [ \"$funcstack[1]\" = \"_dfq\" ]") {
+                Some(index) => format!("{}{ZSH_QUERIES}{}", &script[..index], &script[index..]),
+                None => script + ZSH_QUERIES,
+            }
+        }
+        CompletionShell::Fish => script + FISH_QUERIES,
+    }
+}
+
+pub(crate) fn write_query_candidates(line: &str, current: Option<&str>) -> Result<(), AppError> {
+    let mut stdout = std::io::stdout().lock();
+    for candidate in query_candidates(line, current) {
+        writeln!(stdout, "{candidate}").map_err(Error::from)?;
+    }
+    Ok(())
+}
+
+fn query_candidates(line: &str, current: Option<&str>) -> Vec<String> {
+    let words = shell_words(line);
+    let Some((word, previous)) = words.split_last() else {
+        return Vec::new();
+    };
+    if word.starts_with('-') {
+        return Vec::new();
+    }
+    if previous
+        .last()
+        .is_some_and(|option| VALUE_OPTIONS.contains(&option.as_str()))
+    {
+        return Vec::new();
+    }
+
+    let mut file = "Dockerfile".to_string();
+    let mut options = previous.iter().skip(1);
+    while let Some(option) = options.next() {
+        match option.as_str() {
+            "--stdin" | "completion" | "repl" | "lint" | "check" => return Vec::new(),
+            "-f" | "--file" => {
+                if let Some(path) = options.next() {
+                    file = path.clone();
+                }
+            }
+            _ => {
+                if let Some(path) = option.strip_prefix("--file=") {
+                    file = path.to_string();
+                }
+            }
+        }
+    }
+
+    let Ok(dockerfile) = std::fs::read_to_string(&file) else {
+        return Vec::new();
+    };
+    let Ok(model) = parse_dockerfile(&dockerfile) else {
+        return Vec::new();
+    };
+
+    let completion = complete_query(&model, word);
+    let head = &word[..completion.start];
+    let skip = match current {
+        Some(current) if word.ends_with(current) => word.len() - current.len(),
+        _ => 0,
+    };
+    completion
+        .candidates
+        .into_iter()
+        .map(|candidate| format!("{head}{candidate}")[skip..].to_string())
+        .collect()
+}
+
+fn shell_words(line: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut word = String::new();
+    let mut in_word = false;
+    let mut quote = None;
+    let mut chars = line.chars();
+
+    while let Some(ch) = chars.next() {
+        match (quote, ch) {
+            (Some(open), _) if ch == open => quote = None,
+            (Some('"'), '\\') => {
+                if let Some(next) = chars.next() {
+                    word.push(next);
+                }
+            }
+            (Some(_), _) => word.push(ch),
+            (None, '\'' | '"') => {
+                quote = Some(ch);
+                in_word = true;
+            }
+            (None, '\\') => {
+                if let Some(next) = chars.next() {
+                    word.push(next);
+                }
+                in_word = true;
+            }
+            (None, _) if ch.is_whitespace() => {
+                if in_word {
+                    words.push(std::mem::take(&mut word));
+                    in_word = false;
+                }
+            }
+            (None, _) => {
+                word.push(ch);
+                in_word = true;
+            }
+        }
+    }
+
+    words.push(word);
+    words
+}
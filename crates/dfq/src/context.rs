@@ -0,0 +1,56 @@
+use std::path::Path;
+
+use dfq_core::Error;
+use dfq_core::context::BuildContext;
+use dfq_core::dockerignore::{DockerIgnore, ignore_files};
+
+use crate::AppError;
+
+pub(crate) fn load(dir: &str, dockerfile: Option<&str>) -> Result<BuildContext, AppError> {
+    let root = Path::new(dir);
+    if !root.is_dir() {
+        return Err(AppError::usage(format!(
+            "build context '{dir}' is not a directory"
+        )));
+    }
+
+    let mut ignore = DockerIgnore::default();
+    for path in ignore_files(root, dockerfile.map(Path::new)) {
+        match std::fs::read_to_string(&path) {
+            Ok(contents) => {
+                ignore = DockerIgnore::parse(&contents).map_err(|error| match error {
+                    Error::Config { msg } => Error::config(format!("{}: {msg}", path.display())),
+                    other => other,
+                })?;
+                break;
+            }
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => {}
+            Err(error) => return Err(Error::from(error).into()),
+        }
+    }
+
+    let mut files = Vec::new();
+    walk(root, "", &ignore, &mut files).map_err(Error::from)?;
+    Ok(BuildContext::new(files, ignore))
+}
+
+fn walk(
+    dir: &Path,
+    prefix: &str,
+    ignore: &DockerIgnore,
+    out: &mut Vec<String>,
+) -> std::io::Result<()> {
+    for entry in std::fs::read_dir(dir)? {
+        let entry = entry?;
+        let Ok(name) = entry.file_name().into_string() else {
+            continue;
+        };
+        let path = format!("{prefix}{name}");
+        if !entry.file_type()?.is_dir() {
+            out.push(path);
+        } else if !ignore.skips_dir(&path) {
+            walk(&entry.path(), &format!("{path}/"), ignore, out)?;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,273 @@
+use std::collections::BTreeMap;
+
+use clap::ValueEnum;
+
+use dfq_core::Error;
+use dfq_core::exit_code::ExitCode;
+use dfq_core::lint::{Baseline, Finding, RULES, Severity, lint};
+use dfq_core::parser::parse_dockerfile;
+use dfq_core::report::{Report, ReportResult, ReportRule};
+use dfq_core::value::Value;
+
+use crate::{
+    AppError, Cli, OutputFormat, build_args, build_file, dockerfile_path, output_format,
+    read_dockerfile, scan, write_json_line, write_rendered, write_scalar_items,
+};
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, ValueEnum)]
+pub(crate) enum LintSeverity {
+    Info,
+    #[default]
+    Warning,
+    Error,
+}
+
+impl From<LintSeverity> for Severity {
+    fn from(value: LintSeverity) -> Self {
+        match value {
+            LintSeverity::Info => Self::Info,
+            LintSeverity::Warning => Self::Warning,
+            LintSeverity::Error => Self::Error,
+        }
+    }
+}
+
+pub(crate) fn run(
+    mut cli: Cli,
+    paths: Vec<String>,
+    severity: LintSeverity,
+    list_rules: bool,
+    baseline: Option<String>,
+    write_baseline: Option<String>,
+) -> Result<(), AppError> {
+    let format = report_format(&cli, "lint")?;
+    if list_rules {
+        return write_rules(format);
+    }
+    let baseline = baseline.as_deref().map(load_baseline).transpose()?;
+    let inputs = inputs(&mut cli, paths, "lint")?;
+    let overrides = build_args::overrides(&cli)?;
+
+    let minimum = Severity::from(severity);
+    let mut linted = Vec::new();
+    let mut reported = Vec::new();
+    let mut failure = None;
+    for path in &inputs {
+        match read_input(&cli, path).and_then(|contents| Ok(parse_dockerfile(&contents)?)) {
+            Ok(model) => {
+                linted.push(path.clone());
+                reported.extend(
+                    lint(&model, &overrides)
+                        .into_iter()
+                        .filter(|finding| finding.rule.severity >= minimum)
+                        .map(|finding| (path.as_str(), finding)),
+                );
+            }
+            Err(error) => {
+                eprintln!("{path}: {}", error.message);
+                failure.get_or_insert(AppError {
+                    message: String::new(),
+                    ..error
+                });
+            }
+        }
+    }
+
+    if let Some(path) = &write_baseline {
+        let baseline =
+            Baseline::from_findings(reported.iter().map(|(path, finding)| (*path, finding)));
+        std::fs::write(path, baseline.to_toml_string()).map_err(Error::from)?;
+        eprintln!("wrote {} findings to {path}", baseline.len());
+        return failure.map_or(Ok(()), Err);
+    }
+    if let Some(baseline) = &baseline {
+        reported = baseline.retain_new(reported);
+    }
+
+    if matches!(format, OutputFormat::Sarif | OutputFormat::Junit) {
+        let report = Report {
+            rules: RULES
+                .iter()
+                .filter(|rule| rule.severity >= minimum)
+                .map(ReportRule::from)
+                .collect(),
+            paths: linted,
+            results: reported
+                .iter()
+                .map(|(path, finding)| ReportResult::from_finding(path, finding))
+                .collect(),
+        };
+        write_report(format, &report, "dfq lint")?;
+    } else {
+        write_findings(format, &reported)?;
+    }
+    if let Some(failure) = failure {
+        return Err(failure);
+    }
+    if !reported.is_empty() {
+        return Err(AppError {
+            code: ExitCode::Lint,
+            message: String::new(),
+            kind: "lint",
+            span: None,
+            path: None,
+            query: None,
+        });
+    }
+    Ok(())
+}
+
+fn load_baseline(path: &str) -> Result<Baseline, AppError> {
+    let contents = std::fs::read_to_string(path).map_err(Error::from)?;
+    Baseline::parse(&contents).map_err(|error| match error {
+        Error::Config { msg } => Error::config(format!("{path}: {msg}")).into(),
+        other => other.into(),
+    })
+}
+
+pub(crate) fn report_format(cli: &Cli, command: &str) -> Result<OutputFormat, AppError> {
+    let format = output_format(cli);
+    if !matches!(
+        format,
+        OutputFormat::Text
+            | OutputFormat::Json
+            | OutputFormat::JsonPretty
+            | OutputFormat::Sarif
+            | OutputFormat::Junit
+    ) {
+        return Err(AppError::usage(format!(
+            "{command} supports only text, json, json-pretty, sarif, and junit output"
+        )));
+    }
+    Ok(format)
+}
+
+pub(crate) fn inputs(
+    cli: &mut Cli,
+    paths: Vec<String>,
+    command: &str,
+) -> Result<Vec<String>, AppError> {
+    if !cli.queries.is_empty() || !cli.exprs.is_empty() || cli.query_file.is_some() {
+        return Err(AppError::usage(format!("{command} does not take queries")));
+    }
+    cli.files.extend(paths);
+    if cli.stdin && (!cli.files.is_empty() || !cli.recursive.is_empty()) {
+        return Err(AppError::usage(
+            "--stdin is mutually exclusive with --file and --recursive",
+        ));
+    }
+    build_file::validate(cli)?;
+    build_file::apply(cli)?;
+
+    Ok(if scan::is_multi(cli) {
+        scan::discover(cli)?
+    } else if cli.stdin {
+        vec!["<stdin>".to_string()]
+    } else {
+        vec![dockerfile_path(cli).to_string()]
+    })
+}
+
+pub(crate) fn read_input(cli: &Cli, path: &str) -> Result<String, AppError> {
+    if cli.stdin {
+        read_dockerfile(cli)
+    } else {
+        std::fs::read_to_string(path).map_err(|error| AppError::from(Error::from(error)))
+    }
+}
+
+fn write_findings(format: OutputFormat, findings: &[(&str, Finding)]) -> Result<(), AppError> {
+    if format == OutputFormat::Text {
+        let lines: Vec<String> = findings
+            .iter()
+            .map(|(path, finding)| {
+                format!(
+                    "{path}:{}: {}[{}]: {}",
+                    finding.start_line,
+                    finding.rule.severity.as_str(),
+                    finding.rule.id,
+                    finding.message
+                )
+            })
+            .collect();
+        return write_scalar_items(&lines, false, false);
+    }
+
+    let payload = Value::Array(
+        findings
+            .iter()
+            .map(|(path, finding)| {
+                let mut value = finding.to_value();
+                if let Value::Object(map) = &mut value {
+                    map.insert("path".to_string(), Value::String(path.to_string()));
+                }
+                value
+            })
+            .collect(),
+    );
+    write_payload(format, &payload)
+}
+
+fn write_rules(format: OutputFormat) -> Result<(), AppError> {
+    if matches!(format, OutputFormat::Sarif | OutputFormat::Junit) {
+        return Err(AppError::usage(
+            "--list-rules supports only text, json, and json-pretty output",
+        ));
+    }
+    if format == OutputFormat::Text {
+        let lines: Vec<String> = RULES
+            .iter()
+            .map(|rule| {
+                format!(
+                    "{}  {:<7}  {:<22}  {}",
+                    rule.id,
+                    rule.severity.as_str(),
+                    rule.name,
+                    rule.description
+                )
+            })
+            .collect();
+        return write_scalar_items(&lines, false, false);
+    }
+
+    let payload = Value::Array(
+        RULES
+            .iter()
+            .map(|rule| {
+                Value::Object(BTreeMap::from([
+                    ("id".to_string(), Value::String(rule.id.to_string())),
+                    ("name".to_string(), Value::String(rule.name.to_string())),
+                    (
+                        "severity".to_string(),
+                        Value::String(rule.severity.as_str().to_string()),
+                    ),
+                    (
+                        "description".to_string(),
+                        Value::String(rule.description.to_string()),
+                    ),
+                ]))
+            })
+            .collect(),
+    );
+    write_payload(format, &payload)
+}
+
+pub(crate) fn write_report(
+    format: OutputFormat,
+    report: &Report,
+    name: &str,
+) -> Result<(), AppError> {
+    if format == OutputFormat::Sarif {
+        write_json_line(&report.to_sarif().to_json_pretty_string())
+    } else {
+        write_rendered(Some(report.to_junit(name)), "junit")
+    }
+}
+
+pub(crate) fn write_payload(format: OutputFormat, payload: &Value) -> Result<(), AppError> {
+    if format == OutputFormat::JsonPretty {
+        write_json_line(&payload.to_json_pretty_string())
+    } else {
+        write_json_line(&payload.to_json_string())
+    }
+}
@@ -8,15 +8,30 @@ use clap_complete::{
 };
 
 use dfq_core::Error;
-use dfq_core::eval::Evaluator;
+use dfq_core::context::BuildContext;
+use dfq_core::error::Span;
+use dfq_core::eval::{EvalResult, Evaluator};
 use dfq_core::exit_code::ExitCode;
 use dfq_core::parser::parse_dockerfile;
-use dfq_core::query::parse_query;
+use dfq_core::query::{Query, parse_query};
+use dfq_core::template::Template;
 use dfq_core::value::Value;
 
-const LONG_ABOUT: &str = "Parse a Dockerfile and query resolved values such as ARGs, FROM parents, STAGE metadata, and RUN commands.\n\nUse --json for structured queries. In text mode, scalar arrays are streamed one item per line, so queries like RUN[*] can be piped to grep.";
+use crate::lint::LintSeverity;
 
-const AFTER_HELP: &str = "Examples:\n  dfq ARG.VERSION\n  dfq --build-arg VERSION=1.2.3 FROM[0].RESOLVED\n  dfq --json STAGE\n  dfq 'STAGE[\"builder\"].ARG.VERSION'\n  dfq RUN[*] | grep apt-get\n  dfq 'RUN.GREP(\"apt-get\")'\n  dfq 'RUN[*].GREP(\"apt-get\")'\n  dfq 'RUN.GREP(\"apt-get\").COUNT'\n  dfq --json 'RUN[*].SPAN'\n  dfq 'RESOLVE(\"img:${VERSION}\")'";
+mod build_args;
+mod build_file;
+mod check;
+mod completion;
+mod context;
+mod lint;
+mod repl;
+mod scan;
+mod watch;
+
+const LONG_ABOUT: &str = "Parse a Dockerfile and query resolved values such as ARGs, FROM parents, STAGE metadata, RUN commands, and COPY sources.\n\nUse --json for structured queries. In text mode, scalar arrays are streamed one item per line, so queries like RUN[*] can be piped to grep.\n\nSeveral queries can be evaluated against a single parse of the Dockerfile by passing more than one QUERY, repeating -e, or using --query-file.";
+
+const AFTER_HELP: &str = "Examples:\n  dfq ARG.VERSION\n  dfq --build-arg VERSION=1.2.3 FROM[0].RESOLVED\n  dfq --json STAGE\n  dfq 'STAGE[\"builder\"].ARG.VERSION'\n  dfq RUN[*] | grep apt-get\n  dfq 'RUN.GREP(\"apt-get\")'\n  dfq 'RUN[*].GREP(\"apt-get\")'\n  dfq 'RUN.GREP(\"apt-get\").COUNT'\n  dfq --json 'RUN[*].SPAN'\n  dfq --json 'STAGE[*].{name: NAME, base: PARENT.RESOLVED}'\n  dfq '[ARG.VERSION, FROM[0].RESOLVED]'\n  dfq 'STAGE[0].ARG.FOO // \"fallback\"'\n  dfq 'EXISTS(STAGE[0].ARG.FOO)'\n  dfq --json ARG.VERSION FROM[0].RESOLVED\n  dfq --query-file queries.txt --json\n  eval \"$(dfq --format export ARG)\"\n  dfq --format github ARG >> \"$GITHUB_OUTPUT\"\n  dfq --format yaml STAGE\n  dfq --template '{{ .name }} -> {{ .parent.resolved }}' STAGE\n  dfq --format table --columns index,name,parent.resolved,instructions.count STAGE\n  dfq --format ndjson 'RUN[*].{command: COMMAND, span: SPAN}'\n  dfq 'RESOLVE(\"img:${VERSION}\")'\n  dfq --bake docker-bake.hcl --bake-target app FROM[0].RESOLVED\n  dfq --compose compose.yaml --service api FROM[0].RESOLVED\n  dfq -r . FROM[*].RESOLVED\n  dfq --context . COPY.MISSING\n  dfq --context . 'IGNORED(\"node_modules/x\")'\n  dfq --format ndjson PACKAGES\n  dfq -r . DIRECTIVES.SYNTAX\n  dfq --json 'INSTRUCTION[*].{keyword: KEYWORD, comment: COMMENT}'\n  dfq lint --severity info -r .\n  dfq check --policy policy.toml -r .\n  dfq lint --format sarif -r . > dfq.sarif\n  dfq lint --baseline .dfq-baseline.toml -r .\n  dfq -i\n  dfq --watch FROM[*].RESOLVED";
 
 fn main() {
     let code = match run() {
@@ -35,6 +50,10 @@ fn main() {
 struct AppError {
     code: ExitCode,
     message: String,
+    kind: &'static str,
+    span: Option<Span>,
+    path: Option<String>,
+    query: Option<String>,
 }
 
 impl AppError {
@@ -42,7 +61,52 @@ impl AppError {
         Self {
             code: ExitCode::Usage,
             message: message.into(),
+            kind: "usage",
+            span: None,
+            path: None,
+            query: None,
+        }
+    }
+
+    fn with_query(mut self, source: &str) -> Self {
+        if self.query.is_none() {
+            self.query = Some(source.to_string());
         }
+        self
+    }
+
+    fn to_json_value(&self) -> Value {
+        let mut error = BTreeMap::new();
+        error.insert("kind".to_string(), Value::String(self.kind.to_string()));
+        error.insert(
+            "message".to_string(),
+            Value::String(self.message.lines().next().unwrap_or_default().to_string()),
+        );
+        error.insert(
+            "span".to_string(),
+            self.span.map_or(Value::Null, |span| {
+                let mut out = BTreeMap::new();
+                out.insert("start".to_string(), Value::Number(span.start as i64));
+                out.insert("end".to_string(), Value::Number(span.end as i64));
+                Value::Object(out)
+            }),
+        );
+        error.insert(
+            "path".to_string(),
+            self.path.clone().map_or(Value::Null, Value::String),
+        );
+        error.insert(
+            "exit_code".to_string(),
+            Value::Number(i64::from(self.code.as_i32())),
+        );
+
+        let mut out = BTreeMap::new();
+        out.insert(
+            "query".to_string(),
+            self.query.clone().map_or(Value::Null, Value::String),
+        );
+        out.insert("error".to_string(), Value::Object(error));
+        Value::Object(out)
     }
 }
 
@@ -51,6 +115,10 @@ impl From<Error> for AppError {
         Self {
             code: ExitCode::from(&value),
             message: value.to_string(),
+            kind: value.kind(),
+            span: value.span(),
+            path: value.path().map(str::to_string),
+            query: None,
         }
     }
 }
@@ -62,6 +130,65 @@ enum Commands {
         #[arg(value_enum, value_name = "SHELL")]
         shell: CompletionShell,
     },
+    #[command(about = "Query the Dockerfile interactively")]
+    Repl,
+    #[command(
+        about = "Check Dockerfiles against built-in lint rules",
+        long_about = "Check Dockerfiles against the built-in rule set: unpinned base images, apt-get install without --no-install-recommends or list cleanup, ADD with URLs, USER root in the final stage, a missing HEALTHCHECK, multiple CMDs, cd in RUN, unused ARGs, and duplicate stage names. Each finding reports the rule id, severity, line, and message. Comments like '# dfq:ignore DFQ001' (next instruction), '# dfq:ignore-next-line' (all rules), and '# dfq:disable DFQ009' (rest of the file) suppress findings, and --baseline hides findings recorded earlier with --write-baseline. Exits with 1 when findings are reported."
+    )]
+    Lint {
+        #[arg(
+            value_name = "PATH",
+            help = "Dockerfiles, globs, or directories to lint (default ./Dockerfile)"
+        )]
+        paths: Vec<String>,
+        #[arg(
+            long = "severity",
+            value_enum,
+            value_name = "LEVEL",
+            default_value_t = LintSeverity::Warning,
+            help = "Report and fail on findings at or above LEVEL"
+        )]
+        severity: LintSeverity,
+        #[arg(long = "list-rules", help = "List the built-in rules and exit")]
+        list_rules: bool,
+        #[arg(
+            long = "baseline",
+            value_name = "FILE",
+            help = "Report only findings that are not recorded in FILE"
+        )]
+        baseline: Option<String>,
+        #[arg(
+            long = "write-baseline",
+            value_name = "FILE",
+            conflicts_with = "baseline",
+            help = "Record the current findings in FILE and exit successfully"
+        )]
+        write_baseline: Option<String>,
+    },
+    #[command(
+        about = "Check Dockerfiles against a policy of query assertions",
+        long_about = "Evaluate the rules of a TOML policy file against Dockerfiles. Each [[rule]] has an id, a dfq query, and one or more expectations: equals, matches (a regular expression), not_contains, count (e.g. \"<= 3\"), number (a numeric comparison), or all/any tables that apply expectations to every or some array item. Failing rules are reported with their message; dfq exits with 7 when a rule with severity error (the default) fails."
+    )]
+    Check {
+        #[arg(
+            value_name = "PATH",
+            help = "Dockerfiles, globs, or directories to check (default ./Dockerfile)"
+        )]
+        paths: Vec<String>,
+        #[arg(
+            long = "policy",
+            value_name = "FILE",
+            required = true,
+            help = "TOML policy file with [[rule]] entries"
+        )]
+        policy: String,
+    },
+    #[command(name = "complete-query", hide = true)]
+    Complete {
+        line: String,
+        current: Option<String>,
+    },
 }
 
 #[derive(Clone, Copy, Debug, Eq, PartialEq, ValueEnum)]
@@ -71,6 +198,31 @@ enum CompletionShell {
     Fish,
 }
 
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, ValueEnum)]
+enum ErrorStream {
+    #[default]
+    Stdout,
+    Stderr,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, ValueEnum)]
+enum OutputFormat {
+    Text,
+    Json,
+    JsonPretty,
+    Ndjson,
+    Yaml,
+    Toml,
+    Table,
+    Csv,
+    Tsv,
+    Env,
+    Export,
+    Github,
+    Sarif,
+    Junit,
+}
+
 #[derive(Debug, Parser)]
 #[command(
     name = "dfq",
@@ -85,14 +237,34 @@ struct Cli {
     #[arg(
         short = 'f',
         long = "file",
+        global = true,
         value_name = "PATH",
-        help = "Read Dockerfile from a file path",
-        long_help = "Read Dockerfile content from PATH. If omitted, dfq uses ./Dockerfile."
+        help = "Read Dockerfiles from file paths, globs, or directories",
+        long_help = "Read Dockerfile content from PATH. If omitted, dfq uses ./Dockerfile. Repeat the flag to query several files; PATH may also be a glob such as 'services/*/Dockerfile' or '**/*.Dockerfile', or a directory whose Dockerfiles are queried. With more than one file, results are reported per file."
+    )]
+    files: Vec<String>,
+
+    #[arg(
+        short = 'r',
+        long = "recursive",
+        global = true,
+        value_name = "PATH",
+        help = "Query every Dockerfile found under a directory",
+        long_help = "Recursively discover Dockerfile, Containerfile, *.Dockerfile, and Dockerfile.* files under PATH (hidden directories are skipped) and query each of them. Files are parsed in parallel and results are reported per file: text output prefixes each line with 'path:', JSON output is an object keyed by path. Repeatable."
+    )]
+    recursive: Vec<String>,
+
+    #[arg(
+        short = 'i',
+        long = "interactive",
+        help = "Start an interactive query session",
+        long_help = "Parse the Dockerfile once and read queries interactively, with history and TAB completion of namespaces, fields, stage names, and arg names. Meta-commands: :set build-arg K=V, :unset build-arg K, :strict on|off, :json [on|off], :help, :quit. Same as the repl subcommand."
     )]
-    file: Option<String>,
+    interactive: bool,
 
     #[arg(
         long = "stdin",
+        global = true,
         help = "Read Dockerfile from stdin",
         long_help = "Read Dockerfile content from stdin. This conflicts with --file."
     )]
@@ -100,28 +272,134 @@ struct Cli {
 
     #[arg(
         long = "context",
-        hide = true,
+        global = true,
         value_name = "PATH",
-        help = "Reserved compatibility flag",
-        long_help = "Reserved compatibility flag for future build-context-aware behavior. Accepted in v1 but ignored."
+        help = "Build context directory for COPY and CONTEXT queries",
+        long_help = "Load the build context directory at PATH, applying <Dockerfile>.dockerignore or the context's .dockerignore, so that COPY[*].SOURCES_MATCHED lists the context files each COPY/ADD source picks up, COPY[*].MISSING lists sources that match nothing, and CONTEXT.FILES lists every file sent to the build. IGNORED(\"path\") reports whether the ignore rules exclude a path. Sources copied --from another stage or image and remote ADD URLs are not checked."
     )]
     context: Option<String>,
 
+    #[arg(skip)]
+    build_context: Option<BuildContext>,
+
+    #[arg(
+        long = "bake",
+        value_name = "FILE",
+        help = "Take the Dockerfile and build args from a bake file",
+        long_help = "Read a Docker Buildx bake definition (docker-bake.hcl or JSON) and use the selected target's context and dockerfile as the Dockerfile path, its args as build-arg defaults, and its first platform for TARGETPLATFORM, TARGETOS, TARGETARCH, and TARGETVARIANT. Variables and ${VAR} interpolation are supported, and variables can be overridden from the environment as with docker buildx bake. Explicit --build-arg, --build-arg-file, and DFQ_BUILD_ARG_* values win over bake args. Conflicts with --file, --stdin, and --compose."
+    )]
+    bake: Option<String>,
+
+    #[arg(
+        long = "bake-target",
+        value_name = "NAME",
+        help = "Bake target or single-target group to use",
+        long_help = "Select the bake target to read with --bake. Groups are accepted when they contain exactly one target. Defaults to 'default'."
+    )]
+    bake_target: Option<String>,
+
+    #[arg(
+        long = "compose",
+        value_name = "FILE",
+        help = "Take the Dockerfile and build args from a compose file",
+        long_help = "Read a compose file and use the selected service's build section: context and dockerfile (relative to the compose file's directory) as the Dockerfile path, args (map or list) as build-arg defaults, and the first platform for TARGETPLATFORM and friends. ${VAR} interpolation uses the environment and the .env file next to the compose file. Explicit --build-arg, --build-arg-file, and DFQ_BUILD_ARG_* values win over compose args. Conflicts with --file, --stdin, and --bake."
+    )]
+    compose: Option<String>,
+
+    #[arg(
+        long = "service",
+        value_name = "NAME",
+        help = "Compose service to read with --compose",
+        long_help = "Select the compose service whose build section is used with --compose. May be omitted when exactly one service has a build section."
+    )]
+    service: Option<String>,
+
+    #[arg(skip)]
+    build_file_args: BTreeMap<String, String>,
+
     #[arg(
         long = "build-arg",
+        global = true,
         value_name = "K[=V]",
         help = "Override build ARG values",
-        long_help = "Override ARG values. Repeat the flag as needed. Use KEY=VALUE or KEY (empty value). Overrides take precedence over stage defaults and global defaults."
+        long_help = "Override ARG values. Repeat the flag as needed. Use KEY=VALUE, or KEY to take the value from the environment like docker build does (KEY is left unset when the variable is missing). Overrides take precedence over --build-arg-file, DFQ_BUILD_ARG_* variables, stage defaults, and global defaults."
     )]
     build_args: Vec<String>,
 
+    #[arg(
+        long = "build-arg-file",
+        value_name = "PATH",
+        help = "Read build ARG values from a dotenv file",
+        long_help = "Read ARG overrides from a dotenv-style file with KEY=VALUE lines. Blank lines and # comments are ignored, an optional 'export ' prefix is accepted, and values may be single-quoted (literal) or double-quoted (backslash escapes, may span lines). Repeat the flag as needed; later files win."
+    )]
+    build_arg_files: Vec<String>,
+
+    #[arg(
+        long = "no-build-arg-env",
+        help = "Do not read build ARG values from the environment",
+        long_help = "Ignore DFQ_BUILD_ARG_<KEY> environment variables and treat --build-arg KEY without a value as an empty string instead of looking KEY up in the environment."
+    )]
+    no_build_arg_env: bool,
+
     #[arg(
         long = "json",
+        global = true,
         help = "Emit a JSON envelope",
         long_help = "Emit a JSON envelope with { query, value, type, meta }. Required for object/array results unless the result is an array of scalars."
     )]
     json: bool,
 
+    #[arg(
+        long = "error-stream",
+        value_enum,
+        value_name = "STREAM",
+        help = "Where to write JSON error envelopes",
+        long_help = "Where to write the JSON error envelope when --json, --format json, or --format json-pretty is set: stdout (default) or stderr; any other output mode is a usage error. The envelope has the shape { query, error: { kind, message, span, path, exit_code } } and the process still exits with the matching exit code."
+    )]
+    error_stream: Option<ErrorStream>,
+
+    #[arg(
+        long = "format",
+        value_enum,
+        global = true,
+        value_name = "FORMAT",
+        help = "Select the output format",
+        long_help = "Select the output format. text (default) prints scalars and scalar arrays; json is the same as --json and json-pretty indents it. ndjson prints the bare result as compact JSON, one array element per line. yaml and toml print the bare result (toml requires an object, and arrays must not contain nulls). table, csv and tsv render an object or an array of objects as rows, flattening nested objects into dotted column names. env prints object results as KEY=value lines with POSIX shell quoting, export adds an 'export ' prefix for use with eval, and github writes key=value pairs for $GITHUB_OUTPUT using heredoc delimiters for multiline values. With several queries, env/export/github use the query names as keys. sarif (SARIF 2.1.0 for code scanning) and junit (JUnit XML) report dfq lint and dfq check results."
+    )]
+    format: Option<OutputFormat>,
+
+    #[arg(
+        long = "columns",
+        value_name = "COLS",
+        value_delimiter = ',',
+        help = "Select columns for tabular output",
+        long_help = "Comma-separated columns for --format table, csv, or tsv, in output order. A column also selects its nested fields, so 'parent' expands to parent.raw, parent.resolved, and so on."
+    )]
+    columns: Vec<String>,
+
+    #[arg(
+        long = "no-header",
+        help = "Omit the header row in tabular output",
+        long_help = "Omit the header row for --format table, csv, or tsv."
+    )]
+    no_header: bool,
+
+    #[arg(
+        long = "template",
+        value_name = "TEMPLATE",
+        help = "Render results through a template",
+        long_help = "Render each result item through TEMPLATE, followed by a newline. Array results render once per element. Supports {{ .field.path }}, {{ . }}, {{ $.root }}, {{ if .x }}/{{ else if .y }}/{{ else }}/{{ end }}, {{ if not .x }}, {{ range .list }} with $index/$key, and {{- / -}} whitespace trimming."
+    )]
+    template: Option<String>,
+
+    #[arg(
+        long = "template-file",
+        value_name = "PATH",
+        help = "Read the output template from a file",
+        long_help = "Read the output template from PATH. Behaves like --template."
+    )]
+    template_file: Option<String>,
+
     #[arg(
         long = "raw",
         help = "Disable trailing newline for scalar output",
@@ -138,6 +416,7 @@ struct Cli {
 
     #[arg(
         long = "strict",
+        global = true,
         help = "Error on missing interpolation variables",
         long_help = "Error when interpolation references missing variables instead of expanding them to empty strings."
     )]
@@ -150,6 +429,37 @@ struct Cli {
     )]
     show_missing: bool,
 
+    #[arg(
+        long = "watch",
+        help = "Re-evaluate whenever the Dockerfile changes",
+        long_help = "Keep running and re-print the query results whenever the Dockerfile changes on disk. The file is polled, so no external services are needed. Evaluation errors are reported without stopping the watch. Only the Dockerfile is re-read; --context, bake, compose, and build-arg files are loaded once at startup. Requires --file or ./Dockerfile; conflicts with --stdin."
+    )]
+    watch: bool,
+
+    #[arg(
+        long = "watch-interval",
+        value_name = "MS",
+        default_value_t = 500,
+        help = "Polling interval for --watch in milliseconds",
+        long_help = "How often --watch checks the Dockerfile for changes, in milliseconds. Defaults to 500."
+    )]
+    watch_interval: u64,
+
+    #[arg(
+        long = "changed-only",
+        help = "With --watch, print only when the result changes",
+        long_help = "With --watch, skip re-printing when the Dockerfile changed but the query results did not."
+    )]
+    changed_only: bool,
+
+    #[arg(
+        long = "on-change",
+        value_name = "CMD",
+        help = "With --watch, run a command after each update",
+        long_help = "With --watch, run CMD through 'sh -c' after each printed update. The first evaluation does not trigger it."
+    )]
+    on_change: Option<String>,
+
     #[arg(
         short = 'v',
         long = "verbose",
@@ -158,12 +468,35 @@ struct Cli {
     )]
     verbose: bool,
 
+    #[arg(
+        short = 'e',
+        long = "expr",
+        value_name = "QUERY",
+        help = "Add a query expression",
+        long_help = "Add a query expression to evaluate. Repeat the flag as needed; expressions are evaluated after positional queries against the same parsed Dockerfile."
+    )]
+    exprs: Vec<String>,
+
+    #[arg(
+        long = "query-file",
+        value_name = "PATH",
+        help = "Read queries from a file",
+        long_help = "Read queries from PATH, one per line. Lines may be named with NAME = QUERY; the name becomes the JSON key. Blank lines and lines starting with '#' are ignored."
+    )]
+    query_file: Option<String>,
+
     #[arg(
         value_name = "QUERY",
         help = "Query expression",
-        long_help = "Query expression to evaluate. Examples: ARG.VERSION, FROM[0].RESOLVED, STAGE[\"builder\"].ARG.VERSION, RUN[*], RUN.GREP(\"apt-get\"), RUN[*].GREP(\"apt-get\")."
+        long_help = "Query expression to evaluate. Examples: ARG.VERSION, FROM[0].RESOLVED, STAGE[\"builder\"].ARG.VERSION, RUN[*], RUN.GREP(\"apt-get\"), RUN[*].GREP(\"apt-get\"), STAGE[*].{name: NAME}, [ARG.VERSION, FROM[0].RESOLVED]. Pass several to evaluate them in one invocation: JSON output becomes an object keyed by query, text output prints results in order."
     )]
-    query: Option<String>,
+    queries: Vec<String>,
+}
+
+#[derive(Debug)]
+struct NamedQuery {
+    name: String,
+    query: Query,
 }
 
 fn run() -> Result<(), AppError> {
@@ -181,81 +514,389 @@ fn run() -> Result<(), AppError> {
         }
     };
 
-    execute(cli)
+    match &cli.command {
+        Some(Commands::Completion { shell }) => return write_completion(*shell),
+        Some(Commands::Repl) => return repl::run(cli),
+        Some(Commands::Lint {
+            paths,
+            severity,
+            list_rules,
+            baseline,
+            write_baseline,
+        }) => {
+            let (paths, severity, list_rules) = (paths.clone(), *severity, *list_rules);
+            let (baseline, write_baseline) = (baseline.clone(), write_baseline.clone());
+            return lint::run(cli, paths, severity, list_rules, baseline, write_baseline);
+        }
+        Some(Commands::Check { paths, policy }) => {
+            let (paths, policy) = (paths.clone(), policy.clone());
+            return check::run(cli, paths, &policy);
+        }
+        Some(Commands::Complete { line, current }) => {
+            return completion::write_query_candidates(line, current.as_deref());
+        }
+        None if cli.interactive => return repl::run(cli),
+        None => {}
+    }
+    validate_cli(&cli)?;
+
+    let json_errors = json_error_output(&cli);
+    execute(cli).map_err(|error| match json_errors {
+        Some((stream, pretty)) => report_json_error(error, stream, pretty),
+        None => error,
+    })
 }
 
-fn execute(cli: Cli) -> Result<(), AppError> {
-    if let Some(command) = cli.command {
-        return execute_command(command);
+fn json_error_output(cli: &Cli) -> Option<(ErrorStream, bool)> {
+    if cli.template.is_some() || cli.template_file.is_some() {
+        return None;
+    }
+    match output_format(cli) {
+        OutputFormat::Json => Some((cli.error_stream.unwrap_or_default(), false)),
+        OutputFormat::JsonPretty => Some((cli.error_stream.unwrap_or_default(), true)),
+        _ => None,
     }
+}
 
-    validate_cli(&cli)?;
-    let _reserved_context = &cli.context;
+fn report_json_error(error: AppError, stream: ErrorStream, pretty: bool) -> AppError {
+    if error.message.is_empty() {
+        return error;
+    }
+    let value = error.to_json_value();
+    let payload = if pretty {
+        value.to_json_pretty_string()
+    } else {
+        value.to_json_string()
+    };
+    match stream {
+        ErrorStream::Stdout => println!("{payload}"),
+        ErrorStream::Stderr => eprintln!("{payload}"),
+    }
+    AppError {
+        message: String::new(),
+        ..error
+    }
+}
+
+fn execute(mut cli: Cli) -> Result<(), AppError> {
+    build_file::apply(&mut cli)?;
+    if let Some(dir) = &cli.context {
+        let dockerfile = (!cli.stdin && !scan::is_multi(&cli)).then(|| dockerfile_path(&cli));
+        cli.build_context = Some(context::load(dir, dockerfile)?);
+    }
+
+    let queries = collect_queries(&cli)?;
+    let template = load_template(&cli)?;
+    let overrides = build_args::overrides(&cli)?;
+
+    if cli.watch {
+        return watch::run(&cli, &queries, template.as_ref(), &overrides);
+    }
+    if scan::is_multi(&cli) {
+        return scan::run(&cli, &queries, template.as_ref(), &overrides);
+    }
 
     let dockerfile = read_dockerfile(&cli)?;
-    let model = parse_dockerfile(&dockerfile)?;
-    let query_source = cli.query.as_deref().ok_or_else(|| {
-        AppError::usage("the following required arguments were not provided:\n  <QUERY>")
-    })?;
-    let query = parse_query(query_source)?;
-
-    let overrides = cli
-        .build_args
-        .iter()
-        .map(|arg| parse_build_arg(arg))
-        .collect::<Result<BTreeMap<_, _>, _>>()?;
+    let results = evaluate_queries(&cli, &queries, &dockerfile, &overrides)?;
+    write_results(&cli, &queries, template.as_ref(), results)
+}
 
-    let evaluator = Evaluator::new(&model, &overrides, cli.strict);
-    let result = evaluator.evaluate(&query)?;
+fn evaluate_queries(
+    cli: &Cli,
+    queries: &[NamedQuery],
+    dockerfile: &str,
+    overrides: &BTreeMap<String, String>,
+) -> Result<Vec<EvalResult>, AppError> {
+    let model = parse_dockerfile(dockerfile)?;
+    let evaluator =
+        Evaluator::new(&model, overrides, cli.strict).with_context(cli.build_context.as_ref());
+    let mut results = Vec::with_capacity(queries.len());
+    for named in queries {
+        let result = evaluator
+            .evaluate(&named.query)
+            .map_err(|error| AppError::from(error).with_query(&named.query.source))?;
 
-    if cli.verbose {
-        eprintln!(
-            "scope={} type={}",
-            result.meta.scope.as_str(),
-            result.value.kind()
-        );
+        if cli.verbose {
+            let label = if queries.len() > 1 {
+                format!("{}: ", named.name)
+            } else {
+                String::new()
+            };
+            eprintln!(
+                "{label}scope={} type={}",
+                result.meta.scope.as_str(),
+                result.value.kind()
+            );
+        }
+        results.push(result);
+    }
+    Ok(results)
+}
+
+fn write_results(
+    cli: &Cli,
+    queries: &[NamedQuery],
+    template: Option<&Template>,
+    results: Vec<EvalResult>,
+) -> Result<(), AppError> {
+    if let Some(template) = template {
+        return write_templated(template, &combined_value(queries, results));
     }
 
+    match output_format(cli) {
+        OutputFormat::Text => write_text_results(cli, queries, results),
+        OutputFormat::Json => write_json_results(cli, queries, results, false),
+        OutputFormat::JsonPretty => write_json_results(cli, queries, results, true),
+        OutputFormat::Ndjson => write_ndjson(&combined_value(queries, results)),
+        OutputFormat::Yaml => write_rendered(
+            Some(combined_value(queries, results).to_yaml_string()),
+            "yaml",
+        ),
+        OutputFormat::Toml => write_toml(&combined_value(queries, results)),
+        OutputFormat::Table => write_rendered(
+            combined_value(queries, results).to_table_string(&cli.columns, !cli.no_header),
+            "table",
+        ),
+        OutputFormat::Csv => write_rendered(
+            combined_value(queries, results).to_delimited_string(',', &cli.columns, !cli.no_header),
+            "csv",
+        ),
+        OutputFormat::Tsv => write_rendered(
+            combined_value(queries, results).to_delimited_string(
+                '\t',
+                &cli.columns,
+                !cli.no_header,
+            ),
+            "tsv",
+        ),
+        OutputFormat::Env => {
+            write_rendered(combined_value(queries, results).to_env_string(false), "env")
+        }
+        OutputFormat::Export => write_rendered(
+            combined_value(queries, results).to_env_string(true),
+            "export",
+        ),
+        OutputFormat::Github => write_rendered(
+            combined_value(queries, results).to_github_output_string(),
+            "github",
+        ),
+        OutputFormat::Sarif | OutputFormat::Junit => Err(report_format_error()),
+    }
+}
+
+fn output_format(cli: &Cli) -> OutputFormat {
     if cli.json {
-        let include_var_details = cli.show_missing || cli.verbose;
-        let payload = json_envelope(
-            &query.source,
-            result.value,
-            result.meta.to_value(include_var_details),
+        OutputFormat::Json
+    } else {
+        cli.format.unwrap_or(OutputFormat::Text)
+    }
+}
+
+fn write_text_results(
+    cli: &Cli,
+    queries: &[NamedQuery],
+    results: Vec<EvalResult>,
+) -> Result<(), AppError> {
+    if let [result] = results.as_slice() {
+        if let Some(text) = result.value.render_scalar() {
+            return write_scalar_text(&text, cli.raw, cli.nul);
+        }
+        if let Some(items) = result.value.render_scalar_array() {
+            return write_scalar_items(&items, cli.raw, cli.nul);
+        }
+        return Err(AppError::usage("structured result requires --json"));
+    }
+
+    let mut lines = Vec::new();
+    for (named, result) in queries.iter().zip(results) {
+        if let Some(text) = result.value.render_scalar() {
+            lines.push(text);
+        } else if let Some(items) = result.value.render_scalar_array() {
+            lines.extend(items);
+        } else {
+            return Err(AppError::usage(format!(
+                "structured result for '{}' requires --json",
+                named.name
+            )));
+        }
+    }
+    write_scalar_items(&lines, cli.raw, cli.nul)
+}
+
+fn write_json_results(
+    cli: &Cli,
+    queries: &[NamedQuery],
+    results: Vec<EvalResult>,
+    pretty: bool,
+) -> Result<(), AppError> {
+    let payload = json_payload(cli, queries, results);
+    if pretty {
+        write_json_line(&payload.to_json_pretty_string())
+    } else {
+        write_json_line(&payload.to_json_string())
+    }
+}
+
+fn json_payload(cli: &Cli, queries: &[NamedQuery], results: Vec<EvalResult>) -> Value {
+    let include_var_details = cli.show_missing || cli.verbose;
+    let mut envelopes = BTreeMap::new();
+    for (named, result) in queries.iter().zip(results) {
+        envelopes.insert(
+            named.name.clone(),
+            json_envelope(
+                &named.query.source,
+                result.value,
+                result.meta.to_value(include_var_details),
+            ),
         );
-        let mut stdout = std::io::stdout().lock();
-        stdout.write_all(payload.as_bytes()).map_err(Error::from)?;
+    }
+
+    if envelopes.len() == 1 {
+        envelopes.into_values().next().unwrap_or(Value::Null)
+    } else {
+        Value::Object(envelopes)
+    }
+}
+
+fn write_ndjson(value: &Value) -> Result<(), AppError> {
+    let mut stdout = std::io::stdout().lock();
+    for item in value.stream_items() {
+        stdout
+            .write_all(item.to_json_string().as_bytes())
+            .map_err(Error::from)?;
         stdout.write_all(b"\n").map_err(Error::from)?;
-        return Ok(());
     }
+    Ok(())
+}
 
-    if let Some(text) = result.value.render_scalar() {
-        write_scalar_text(&text, cli.raw, cli.nul)?;
-        return Ok(());
+fn combined_value(queries: &[NamedQuery], mut results: Vec<EvalResult>) -> Value {
+    if results.len() == 1 {
+        return results.remove(0).value;
     }
 
-    if let Some(items) = result.value.render_scalar_array() {
-        write_scalar_items(&items, cli.raw, cli.nul)?;
-        return Ok(());
+    Value::Object(
+        queries
+            .iter()
+            .zip(results)
+            .map(|(named, result)| (named.name.clone(), result.value))
+            .collect(),
+    )
+}
+
+fn load_template(cli: &Cli) -> Result<Option<Template>, AppError> {
+    let source = match (&cli.template, &cli.template_file) {
+        (Some(source), None) => source.clone(),
+        (None, Some(path)) => std::fs::read_to_string(path).map_err(Error::from)?,
+        _ => return Ok(None),
+    };
+    Ok(Some(Template::parse(&source)?))
+}
+
+fn write_templated(template: &Template, value: &Value) -> Result<(), AppError> {
+    let mut stdout = std::io::stdout().lock();
+    for item in value.stream_items() {
+        let rendered = template.render(item);
+        stdout.write_all(rendered.as_bytes()).map_err(Error::from)?;
+        if !rendered.ends_with('\n') {
+            stdout.write_all(b"\n").map_err(Error::from)?;
+        }
+    }
+    Ok(())
+}
+
+fn report_format_error() -> AppError {
+    AppError::usage("--format sarif and junit are only supported by lint and check")
+}
+
+fn write_toml(value: &Value) -> Result<(), AppError> {
+    if value.has_null_array_items() {
+        return Err(AppError::usage(
+            "--format toml requires arrays without null items; substitute a default with //",
+        ));
+    }
+    write_rendered(value.to_toml_string(), "toml")
+}
+
+fn write_rendered(rendered: Option<String>, format: &str) -> Result<(), AppError> {
+    let rendered = rendered
+        .ok_or_else(|| AppError::usage(format!("--format {format} requires an object result")))?;
+    let mut stdout = std::io::stdout().lock();
+    stdout
+        .write_all(rendered.as_bytes())
+        .map_err(Error::from)
+        .map_err(AppError::from)
+}
+
+fn collect_queries(cli: &Cli) -> Result<Vec<NamedQuery>, AppError> {
+    let mut sources: Vec<(Option<String>, String)> = cli
+        .queries
+        .iter()
+        .chain(&cli.exprs)
+        .map(|source| (None, source.clone()))
+        .collect();
+
+    if let Some(path) = cli.query_file.as_deref() {
+        let contents = std::fs::read_to_string(path).map_err(Error::from)?;
+        for line in contents.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (name, source) = split_named_query(line);
+            sources.push((name.map(str::to_string), source.to_string()));
+        }
     }
 
-    Err(AppError::usage("structured result requires --json"))
+    if sources.is_empty() {
+        return Err(AppError::usage(
+            "the following required arguments were not provided:\n  <QUERY>",
+        ));
+    }
+
+    let mut queries: Vec<NamedQuery> = Vec::with_capacity(sources.len());
+    for (name, source) in sources {
+        let query =
+            parse_query(&source).map_err(|error| AppError::from(error).with_query(&source))?;
+        let name = name.unwrap_or(source);
+        if queries.iter().any(|existing| existing.name == name) {
+            return Err(AppError::usage(format!("duplicate query name '{name}'")));
+        }
+        queries.push(NamedQuery { name, query });
+    }
+    Ok(queries)
 }
 
-fn execute_command(command: Commands) -> Result<(), AppError> {
-    match command {
-        Commands::Completion { shell } => write_completion(shell),
+fn split_named_query(line: &str) -> (Option<&str>, &str) {
+    let Some((name, source)) = line.split_once('=') else {
+        return (None, line);
+    };
+    let name = name.trim();
+    let is_name = name
+        .bytes()
+        .next()
+        .is_some_and(|byte| byte.is_ascii_alphabetic() || byte == b'_')
+        && name
+            .bytes()
+            .all(|byte| byte.is_ascii_alphanumeric() || byte == b'_' || byte == b'-');
+    if is_name {
+        (Some(name), source.trim())
+    } else {
+        (None, line)
     }
 }
 
 fn write_completion(shell: CompletionShell) -> Result<(), AppError> {
     let mut command = Cli::command();
-    let mut stdout = std::io::stdout().lock();
+    let mut script = Vec::new();
     match shell {
-        CompletionShell::Bash => generate(Bash, &mut command, "dfq", &mut stdout),
-        CompletionShell::Zsh => generate(Zsh, &mut command, "dfq", &mut stdout),
-        CompletionShell::Fish => generate(Fish, &mut command, "dfq", &mut stdout),
+        CompletionShell::Bash => generate(Bash, &mut command, "dfq", &mut script),
+        CompletionShell::Zsh => generate(Zsh, &mut command, "dfq", &mut script),
+        CompletionShell::Fish => generate(Fish, &mut command, "dfq", &mut script),
     }
+    let script = completion::with_dynamic_queries(shell, String::from_utf8_lossy(&script).into());
+
+    let mut stdout = std::io::stdout().lock();
+    stdout.write_all(script.as_bytes()).map_err(Error::from)?;
     stdout.flush().map_err(Error::from).map_err(AppError::from)
 }
 
@@ -292,24 +933,40 @@ fn write_scalar_items(items: &[String], raw: bool, nul: bool) -> Result<(), AppE
     Ok(())
 }
 
-fn json_envelope(query: &str, value: Value, meta: Value) -> String {
+fn write_json_line(payload: &str) -> Result<(), AppError> {
+    let mut stdout = std::io::stdout().lock();
+    stdout.write_all(payload.as_bytes()).map_err(Error::from)?;
+    stdout.write_all(b"\n").map_err(Error::from)?;
+    Ok(())
+}
+
+fn json_envelope(query: &str, value: Value, meta: Value) -> Value {
     let mut out = BTreeMap::new();
     out.insert("query".to_string(), Value::String(query.to_string()));
     out.insert("value".to_string(), value.clone());
     out.insert("type".to_string(), Value::String(value.kind().to_string()));
     out.insert("meta".to_string(), meta);
-    Value::Object(out).to_json_string()
+    Value::Object(out)
 }
 
 fn validate_cli(cli: &Cli) -> Result<(), AppError> {
-    if cli.command.is_none() && cli.query.is_none() {
+    if cli.command.is_none()
+        && cli.queries.is_empty()
+        && cli.exprs.is_empty()
+        && cli.query_file.is_none()
+    {
         return Err(AppError::usage(
             "the following required arguments were not provided:\n  <QUERY>",
         ));
     }
-    if cli.stdin && cli.file.is_some() {
+    if cli.stdin && !cli.files.is_empty() {
         return Err(AppError::usage("--stdin is mutually exclusive with --file"));
     }
+    if cli.stdin && !cli.recursive.is_empty() {
+        return Err(AppError::usage(
+            "--stdin is mutually exclusive with --recursive",
+        ));
+    }
     if cli.json && cli.raw {
         return Err(AppError::usage("--json is mutually exclusive with --raw"));
     }
@@ -319,9 +976,74 @@ fn validate_cli(cli: &Cli) -> Result<(), AppError> {
     if cli.raw && cli.nul {
         return Err(AppError::usage("--raw is mutually exclusive with --null"));
     }
+    if cli.json
+        && let Some(format) = cli.format
+        && format != OutputFormat::Json
+    {
+        return Err(AppError::usage(
+            "--json is mutually exclusive with --format",
+        ));
+    }
+    if cli.command.is_none()
+        && matches!(
+            output_format(cli),
+            OutputFormat::Sarif | OutputFormat::Junit
+        )
+    {
+        return Err(report_format_error());
+    }
+    if cli.template.is_some() && cli.template_file.is_some() {
+        return Err(AppError::usage(
+            "--template is mutually exclusive with --template-file",
+        ));
+    }
+    if (cli.template.is_some() || cli.template_file.is_some())
+        && (cli.json || cli.format.is_some() || cli.raw || cli.nul)
+    {
+        return Err(AppError::usage(
+            "--template cannot be combined with --json, --format, --raw, or --null",
+        ));
+    }
+    if cli.error_stream.is_some() && json_error_output(cli).is_none() {
+        return Err(AppError::usage(
+            "--error-stream requires --json, --format json, or --format json-pretty",
+        ));
+    }
+    build_file::validate(cli)?;
+    if cli.watch && scan::is_multi(cli) {
+        return Err(AppError::usage("--watch requires a single Dockerfile"));
+    }
+    if cli.watch && cli.stdin {
+        return Err(AppError::usage(
+            "--watch is mutually exclusive with --stdin",
+        ));
+    }
+    if !cli.watch && (cli.changed_only || cli.on_change.is_some()) {
+        return Err(AppError::usage(
+            "--changed-only and --on-change require --watch",
+        ));
+    }
+    let tabular = matches!(
+        output_format(cli),
+        OutputFormat::Table | OutputFormat::Csv | OutputFormat::Tsv
+    );
+    if !tabular && (!cli.columns.is_empty() || cli.no_header) {
+        return Err(AppError::usage(
+            "--columns and --no-header require --format table, csv, or tsv",
+        ));
+    }
+    if output_format(cli) != OutputFormat::Text && (cli.raw || cli.nul) {
+        return Err(AppError::usage(
+            "--raw and --null are only supported for text output",
+        ));
+    }
     Ok(())
 }
 
+fn dockerfile_path(cli: &Cli) -> &str {
+    cli.files.first().map_or("Dockerfile", String::as_str)
+}
+
 fn read_dockerfile(cli: &Cli) -> Result<String, AppError> {
     if cli.stdin {
         let mut input = String::new();
@@ -331,24 +1053,7 @@ fn read_dockerfile(cli: &Cli) -> Result<String, AppError> {
         return Ok(input);
     }
 
-    let path = cli.file.as_deref().unwrap_or("Dockerfile");
-    std::fs::read_to_string(path)
+    std::fs::read_to_string(dockerfile_path(cli))
         .map_err(Error::from)
         .map_err(AppError::from)
 }
-
-fn parse_build_arg(input: &str) -> Result<(String, String), AppError> {
-    if let Some((key, value)) = input.split_once('=') {
-        let key = key.trim();
-        if key.is_empty() {
-            return Err(AppError::usage("build-arg key cannot be empty"));
-        }
-        return Ok((key.to_string(), value.to_string()));
-    }
-
-    let key = input.trim();
-    if key.is_empty() {
-        return Err(AppError::usage("build-arg key cannot be empty"));
-    }
-    Ok((key.to_string(), String::new()))
-}
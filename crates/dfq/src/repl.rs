@@ -0,0 +1,259 @@
+use std::collections::BTreeMap;
+use std::io::IsTerminal;
+use std::path::PathBuf;
+
+use rustyline::completion::Completer;
+use rustyline::error::ReadlineError;
+use rustyline::highlight::Highlighter;
+use rustyline::hint::Hinter;
+use rustyline::history::DefaultHistory;
+use rustyline::validate::Validator;
+use rustyline::{Context, Editor, Helper};
+
+use dfq_core::complete::complete_query;
+use dfq_core::context::BuildContext;
+use dfq_core::eval::Evaluator;
+use dfq_core::model::DockerfileModel;
+use dfq_core::parser::parse_dockerfile;
+use dfq_core::query::parse_query;
+
+use crate::build_args::{self, parse_build_arg};
+use crate::build_file;
+use crate::context;
+use crate::scan;
+use crate::{AppError, Cli, dockerfile_path, json_envelope, read_dockerfile};
+
+const META_COMMANDS: &[&str] = &[
+    ":help",
+    ":json",
+    ":quit",
+    ":set build-arg ",
+    ":strict",
+    ":unset build-arg ",
+];
+
+const HELP: &str = "Enter a query such as ARG.VERSION or STAGE[\"builder\"].PARENT.RESOLVED.\n\
+Meta-commands:\n  \
+:set build-arg K=V   override a build ARG\n  \
+:unset build-arg K   remove an override\n  \
+:strict on|off       error on missing interpolation variables\n  \
+:json [on|off]       print JSON envelopes instead of text\n  \
+:help                show this help\n  \
+:quit                leave the REPL (also Ctrl-D)";
+
+struct ReplHelper {
+    model: DockerfileModel,
+}
+
+impl Completer for ReplHelper {
+    type Candidate = String;
+
+    fn complete(
+        &self,
+        line: &str,
+        pos: usize,
+        _ctx: &Context<'_>,
+    ) -> rustyline::Result<(usize, Vec<String>)> {
+        let line = &line[..pos];
+        if line.starts_with(':') {
+            let candidates = META_COMMANDS
+                .iter()
+                .filter(|command| command.starts_with(line))
+                .map(|command| command.to_string())
+                .collect();
+            return Ok((0, candidates));
+        }
+
+        let completion = complete_query(&self.model, line);
+        Ok((completion.start, completion.candidates))
+    }
+}
+
+impl Hinter for ReplHelper {
+    type Hint = String;
+}
+
+impl Highlighter for ReplHelper {}
+
+impl Validator for ReplHelper {}
+
+impl Helper for ReplHelper {}
+
+struct Session {
+    model: DockerfileModel,
+    overrides: BTreeMap<String, String>,
+    from_env: bool,
+    strict: bool,
+    context: Option<BuildContext>,
+    json: bool,
+    show_missing: bool,
+}
+
+pub(crate) fn run(mut cli: Cli) -> Result<(), AppError> {
+    if !cli.queries.is_empty() || !cli.exprs.is_empty() || cli.query_file.is_some() {
+        return Err(AppError::usage("--interactive does not take queries"));
+    }
+    if cli.stdin {
+        return Err(AppError::usage(
+            "--interactive cannot read the Dockerfile from --stdin",
+        ));
+    }
+
+    if scan::is_multi(&cli) {
+        return Err(AppError::usage(
+            "--interactive requires a single Dockerfile",
+        ));
+    }
+    build_file::validate(&cli)?;
+    build_file::apply(&mut cli)?;
+
+    let dockerfile = read_dockerfile(&cli)?;
+    let mut session = Session {
+        model: parse_dockerfile(&dockerfile)?,
+        overrides: build_args::overrides(&cli)?,
+        from_env: !cli.no_build_arg_env,
+        strict: cli.strict,
+        context: cli
+            .context
+            .as_deref()
+            .map(|dir| context::load(dir, Some(dockerfile_path(&cli))))
+            .transpose()?,
+        json: cli.json,
+        show_missing: cli.show_missing || cli.verbose,
+    };
+
+    let mut editor = Editor::<ReplHelper, DefaultHistory>::new().map_err(readline_error)?;
+    editor.set_helper(Some(ReplHelper {
+        model: session.model.clone(),
+    }));
+    let history = history_path();
+    if let Some(path) = &history {
+        let _ = editor.load_history(path);
+    }
+
+    loop {
+        let line = match editor.readline("dfq> ") {
+            Ok(line) => line,
+            Err(ReadlineError::Interrupted) => continue,
+            Err(ReadlineError::Eof) => break,
+            Err(error) => return Err(readline_error(error)),
+        };
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        let _ = editor.add_history_entry(line);
+
+        if let Some(command) = line.strip_prefix(':') {
+            match session.meta_command(command) {
+                Ok(true) => {}
+                Ok(false) => break,
+                Err(message) => eprintln!("{message}"),
+            }
+            continue;
+        }
+        session.evaluate(line);
+    }
+
+    if let Some(path) = &history {
+        let _ = editor.save_history(path);
+    }
+    Ok(())
+}
+
+impl Session {
+    fn evaluate(&self, source: &str) {
+        let query = match parse_query(source) {
+            Ok(query) => query,
+            Err(error) => {
+                eprintln!("{error}");
+                return;
+            }
+        };
+        let evaluator = Evaluator::new(&self.model, &self.overrides, self.strict)
+            .with_context(self.context.as_ref());
+        let result = match evaluator.evaluate(&query) {
+            Ok(result) => result,
+            Err(error) => {
+                eprintln!("{error}");
+                return;
+            }
+        };
+
+        if self.json {
+            let meta = result.meta.to_value(self.show_missing);
+            println!(
+                "{}",
+                json_envelope(&query.source, result.value, meta).to_json_string()
+            );
+        } else if let Some(text) = result.value.render_scalar() {
+            println!("{text}");
+        } else if let Some(items) = result.value.render_scalar_array() {
+            for item in items {
+                println!("{item}");
+            }
+        } else {
+            println!("{}", result.value.to_json_pretty_string());
+        }
+    }
+
+    fn meta_command(&mut self, command: &str) -> Result<bool, String> {
+        // The value of `:set build-arg K=V` may contain spaces, so it is taken
+        // verbatim after the first two words instead of being split.
+        let (name, rest) = split_word(command);
+        let (subcommand, arg) = split_word(rest);
+        if name == "set" && subcommand == "build-arg" && !arg.is_empty() {
+            let (key, value) = parse_build_arg(arg, self.from_env)
+                .map_err(|error| error.message)?
+                .ok_or_else(|| format!("build-arg '{arg}' is not set in the environment"))?;
+            self.overrides.insert(key, value);
+            return Ok(true);
+        }
+
+        let mut words = command.split_whitespace();
+        match (words.next(), words.next(), words.next(), words.next()) {
+            (Some("q" | "quit" | "exit"), None, None, None) => return Ok(false),
+            (Some("help"), None, None, None) => println!("{HELP}"),
+            (Some("unset"), Some("build-arg"), Some(key), None) => {
+                if self.overrides.remove(key).is_none() {
+                    return Err(format!("build-arg '{key}' is not set"));
+                }
+            }
+            (Some("strict"), Some(mode), None, None) => self.strict = parse_switch(mode)?,
+            (Some("json"), None, None, None) => self.json = !self.json,
+            (Some("json"), Some(mode), None, None) => self.json = parse_switch(mode)?,
+            _ => return Err(format!("unknown command ':{command}' (try :help)")),
+        }
+        Ok(true)
+    }
+}
+
+fn split_word(text: &str) -> (&str, &str) {
+    let text = text.trim();
+    match text.split_once(char::is_whitespace) {
+        Some((word, rest)) => (word, rest.trim_start()),
+        None => (text, ""),
+    }
+}
+
+fn parse_switch(mode: &str) -> Result<bool, String> {
+    match mode {
+        "on" => Ok(true),
+        "off" => Ok(false),
+        _ => Err(format!("expected 'on' or 'off', found '{mode}'")),
+    }
+}
+
+fn history_path() -> Option<PathBuf> {
+    if !std::io::stdin().is_terminal() {
+        return None;
+    }
+    std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".dfq_history"))
+}
+
+fn readline_error(error: ReadlineError) -> AppError {
+    match error {
+        ReadlineError::Io(source) => dfq_core::Error::from(source).into(),
+        other => dfq_core::Error::eval(format!("line editor failed: {other}")).into(),
+    }
+}
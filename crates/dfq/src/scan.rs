@@ -0,0 +1,303 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::num::NonZeroUsize;
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc;
+use std::thread;
+
+use dfq_core::Error;
+use dfq_core::eval::EvalResult;
+use dfq_core::glob::{glob_match, is_glob};
+use dfq_core::template::Template;
+use dfq_core::value::Value;
+
+use crate::{
+    AppError, Cli, NamedQuery, OutputFormat, combined_value, evaluate_queries, json_payload,
+    output_format, write_json_line, write_ndjson, write_rendered, write_scalar_items, write_toml,
+};
+
+type FileResult = Result<Vec<EvalResult>, AppError>;
+
+pub(crate) fn is_multi(cli: &Cli) -> bool {
+    !cli.recursive.is_empty()
+        || cli.files.len() > 1
+        || cli
+            .files
+            .iter()
+            .any(|path| is_glob(path) || Path::new(path).is_dir())
+}
+
+pub(crate) fn run(
+    cli: &Cli,
+    queries: &[NamedQuery],
+    template: Option<&Template>,
+    overrides: &BTreeMap<String, String>,
+) -> Result<(), AppError> {
+    let format = output_format(cli);
+    if template.is_some()
+        || !matches!(
+            format,
+            OutputFormat::Text
+                | OutputFormat::Json
+                | OutputFormat::JsonPretty
+                | OutputFormat::Ndjson
+                | OutputFormat::Yaml
+                | OutputFormat::Toml
+        )
+    {
+        return Err(AppError::usage(
+            "multiple Dockerfiles support only text, json, json-pretty, ndjson, yaml, and toml output",
+        ));
+    }
+
+    let paths = discover(cli)?;
+    let results = evaluate_all(cli, queries, &paths, overrides);
+
+    let mut failure = None;
+    let mut fail = |path: &str, error: AppError, report: bool| {
+        if report {
+            eprintln!("{path}: {}", error.message);
+        }
+        if failure.is_none() {
+            failure = Some(AppError {
+                message: String::new(),
+                ..error
+            });
+        }
+    };
+
+    match format {
+        OutputFormat::Text => {
+            let mut lines = Vec::new();
+            for (path, result) in paths.iter().zip(results) {
+                match result.and_then(|results| text_lines(queries, results)) {
+                    Ok(items) => {
+                        lines.extend(items.into_iter().map(|item| format!("{path}:{item}")))
+                    }
+                    Err(error) => fail(path, error, true),
+                }
+            }
+            write_scalar_items(&lines, cli.raw, cli.nul)?;
+        }
+        OutputFormat::Json | OutputFormat::JsonPretty => {
+            let mut by_path = BTreeMap::new();
+            for (path, result) in paths.iter().zip(results) {
+                let value = match result {
+                    Ok(results) => json_payload(cli, queries, results),
+                    Err(error) => {
+                        let value = error.to_json_value();
+                        fail(path, error, false);
+                        value
+                    }
+                };
+                by_path.insert(path.clone(), value);
+            }
+            let payload = Value::Object(by_path);
+            if format == OutputFormat::JsonPretty {
+                write_json_line(&payload.to_json_pretty_string())?;
+            } else {
+                write_json_line(&payload.to_json_string())?;
+            }
+        }
+        _ => {
+            let mut by_path = BTreeMap::new();
+            let mut records = Vec::new();
+            for (path, result) in paths.iter().zip(results) {
+                match result {
+                    Ok(results) => {
+                        let value = combined_value(queries, results);
+                        for item in value.stream_items() {
+                            records.push(Value::Object(BTreeMap::from([
+                                ("path".to_string(), Value::String(path.clone())),
+                                ("value".to_string(), item.clone()),
+                            ])));
+                        }
+                        by_path.insert(path.clone(), value);
+                    }
+                    Err(error) => fail(path, error, true),
+                }
+            }
+            match format {
+                OutputFormat::Ndjson => write_ndjson(&Value::Array(records))?,
+                OutputFormat::Yaml => {
+                    write_rendered(Some(Value::Object(by_path).to_yaml_string()), "yaml")?
+                }
+                _ => write_toml(&Value::Object(by_path))?,
+            }
+        }
+    }
+
+    failure.map_or(Ok(()), Err)
+}
+
+fn text_lines(queries: &[NamedQuery], results: Vec<EvalResult>) -> Result<Vec<String>, AppError> {
+    let mut lines = Vec::new();
+    for (named, result) in queries.iter().zip(results) {
+        if let Some(text) = result.value.render_scalar() {
+            lines.push(text);
+        } else if let Some(items) = result.value.render_scalar_array() {
+            lines.extend(items);
+        } else {
+            return Err(AppError::usage(format!(
+                "structured result for '{}' requires --json",
+                named.name
+            )));
+        }
+    }
+    Ok(lines)
+}
+
+fn evaluate_all(
+    cli: &Cli,
+    queries: &[NamedQuery],
+    paths: &[String],
+    overrides: &BTreeMap<String, String>,
+) -> Vec<FileResult> {
+    let workers = thread::available_parallelism()
+        .map_or(1, NonZeroUsize::get)
+        .min(paths.len());
+    let next = AtomicUsize::new(0);
+    let mut results: Vec<Option<FileResult>> = paths.iter().map(|_| None).collect();
+
+    thread::scope(|scope| {
+        let (sender, receiver) = mpsc::channel();
+        for _ in 0..workers {
+            let sender = sender.clone();
+            let next = &next;
+            scope.spawn(move || {
+                loop {
+                    let index = next.fetch_add(1, Ordering::Relaxed);
+                    let Some(path) = paths.get(index) else {
+                        break;
+                    };
+                    let result = std::fs::read_to_string(path)
+                        .map_err(|error| AppError::from(Error::from(error)))
+                        .and_then(|contents| evaluate_queries(cli, queries, &contents, overrides));
+                    if sender.send((index, result)).is_err() {
+                        break;
+                    }
+                }
+            });
+        }
+        drop(sender);
+        for (index, result) in receiver {
+            results[index] = Some(result);
+        }
+    });
+
+    results.into_iter().flatten().collect()
+}
+
+pub(crate) fn discover(cli: &Cli) -> Result<Vec<String>, AppError> {
+    let mut paths = Vec::new();
+    for path in &cli.files {
+        if is_glob(path) {
+            let matches = expand_glob(path).map_err(Error::from)?;
+            if matches.is_empty() {
+                return Err(AppError::usage(format!("no files match '{path}'")));
+            }
+            paths.extend(matches);
+        } else if Path::new(path).is_dir() {
+            walk(path, false, &mut paths).map_err(Error::from)?;
+        } else {
+            paths.push(path.clone());
+        }
+    }
+    for path in &cli.recursive {
+        if Path::new(path).is_dir() {
+            walk(path, true, &mut paths).map_err(Error::from)?;
+        } else {
+            paths.push(path.clone());
+        }
+    }
+
+    let mut seen = BTreeSet::new();
+    paths.retain(|path| seen.insert(path.clone()));
+    if paths.is_empty() {
+        return Err(AppError::usage("no Dockerfiles found"));
+    }
+    Ok(paths)
+}
+
+fn is_dockerfile_name(name: &str) -> bool {
+    if name.ends_with(".dockerignore") {
+        return false;
+    }
+    name == "Dockerfile"
+        || name == "Containerfile"
+        || name.ends_with(".Dockerfile")
+        || name.starts_with("Dockerfile.")
+}
+
+fn walk(dir: &str, recursive: bool, out: &mut Vec<String>) -> std::io::Result<()> {
+    for (name, path, is_dir) in entries(dir)? {
+        if is_dir {
+            if recursive && !name.starts_with('.') {
+                walk(&path, recursive, out)?;
+            }
+        } else if is_dockerfile_name(&name) {
+            out.push(path);
+        }
+    }
+    Ok(())
+}
+
+fn expand_glob(pattern: &str) -> std::io::Result<Vec<String>> {
+    let components: Vec<&str> = pattern.split('/').collect();
+    let literal = components
+        .iter()
+        .position(|component| is_glob(component))
+        .unwrap_or(components.len());
+    let base = match components[..literal].join("/") {
+        base if base.is_empty() && pattern.starts_with('/') => "/".to_string(),
+        base => base,
+    };
+    let remaining = &components[literal..];
+    let globstar = remaining
+        .iter()
+        .position(|component| *component == "**")
+        .unwrap_or(usize::MAX);
+    let descend = |level: usize, name: &str| {
+        level >= globstar || (level + 1 < remaining.len() && glob_match(remaining[level], name))
+    };
+
+    let mut matches = Vec::new();
+    let mut pending = vec![(base, 0usize)];
+    while let Some((dir, level)) = pending.pop() {
+        let listing = match entries(&dir) {
+            Ok(listing) => listing,
+            Err(_) if level > 0 => continue,
+            Err(error) => return Err(error),
+        };
+        for (name, path, is_dir) in listing {
+            if is_dir {
+                if name != ".git" && descend(level, &name) {
+                    pending.push((path, level + 1));
+                }
+            } else if glob_match(pattern, &path) {
+                matches.push(path);
+            }
+        }
+    }
+    matches.sort();
+    Ok(matches)
+}
+
+fn entries(dir: &str) -> std::io::Result<Vec<(String, String, bool)>> {
+    let mut out = Vec::new();
+    let listing = if dir.is_empty() { "." } else { dir };
+    for entry in std::fs::read_dir(listing)? {
+        let entry = entry?;
+        let Ok(name) = entry.file_name().into_string() else {
+            continue;
+        };
+        let path = match dir.trim_end_matches('/') {
+            "" if dir.is_empty() => name.clone(),
+            trimmed => format!("{trimmed}/{name}"),
+        };
+        let is_dir = entry.file_type()?.is_dir();
+        out.push((name, path, is_dir));
+    }
+    out.sort();
+    Ok(out)
+}
@@ -0,0 +1,80 @@
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::process::Command;
+use std::thread;
+use std::time::Duration;
+
+use dfq_core::template::Template;
+
+use crate::{
+    AppError, Cli, NamedQuery, dockerfile_path, evaluate_queries, json_error_output,
+    read_dockerfile, report_json_error, write_results,
+};
+
+pub(crate) fn run(
+    cli: &Cli,
+    queries: &[NamedQuery],
+    template: Option<&Template>,
+    overrides: &BTreeMap<String, String>,
+) -> Result<(), AppError> {
+    let path = dockerfile_path(cli);
+    let interval = Duration::from_millis(cli.watch_interval.max(1));
+    let mut contents = read_dockerfile(cli)?;
+    let mut previous = None;
+    let mut initial = true;
+
+    loop {
+        match evaluate_queries(cli, queries, &contents, overrides) {
+            Ok(results) => {
+                if !(cli.changed_only && previous.as_ref() == Some(&results)) {
+                    previous = Some(results.clone());
+                    match write_results(cli, queries, template, results) {
+                        Ok(()) if !initial => run_on_change(cli),
+                        Ok(()) => {}
+                        Err(error) => report(cli, error),
+                    }
+                }
+            }
+            Err(error) => {
+                previous = None;
+                report(cli, error);
+            }
+        }
+        let _ = std::io::stdout().flush();
+
+        initial = false;
+        contents = wait_for_change(path, &contents, interval);
+    }
+}
+
+fn wait_for_change(path: &str, current: &str, interval: Duration) -> String {
+    loop {
+        thread::sleep(interval);
+        if let Ok(contents) = std::fs::read_to_string(path)
+            && contents != current
+        {
+            return contents;
+        }
+    }
+}
+
+fn run_on_change(cli: &Cli) {
+    let Some(command) = cli.on_change.as_deref() else {
+        return;
+    };
+    let _ = std::io::stdout().flush();
+    match Command::new("sh").arg("-c").arg(command).status() {
+        Ok(status) if status.success() => {}
+        Ok(status) => eprintln!("--on-change command failed: {status}"),
+        Err(error) => eprintln!("--on-change command failed: {error}"),
+    }
+}
+
+fn report(cli: &Cli, error: AppError) {
+    match json_error_output(cli) {
+        Some((stream, pretty)) => {
+            report_json_error(error, stream, pretty);
+        }
+        None => eprintln!("{}", error.message),
+    }
+}
@@ -1,8 +1,8 @@
 use std::fs;
-use std::io::Write;
-use std::path::PathBuf;
-use std::process::{Command, Output, Stdio};
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Output, Stdio};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 fn unique_path(suffix: &str) -> PathBuf {
     let timestamp = SystemTime::now()
@@ -53,15 +53,27 @@ fn readme_fixture() -> Fixture {
     Fixture::new(README_DOCKERFILE)
 }
 
+/// A dfq command that ignores any `DFQ_BUILD_ARG_*` values in the test
+/// runner's environment, so results only depend on what each test sets.
+fn dfq_command() -> Command {
+    let mut command = Command::new(env!("CARGO_BIN_EXE_dfq"));
+    for (key, _) in std::env::vars_os() {
+        if key.to_string_lossy().starts_with("DFQ_BUILD_ARG_") {
+            command.env_remove(key);
+        }
+    }
+    command
+}
+
 fn run(args: &[&str]) -> Output {
-    Command::new(env!("CARGO_BIN_EXE_dfq"))
+    dfq_command()
         .args(args)
         .output()
         .expect("command should run")
 }
 
 fn run_with_stdin(args: &[&str], input: &str) -> Output {
-    let mut child = Command::new(env!("CARGO_BIN_EXE_dfq"))
+    let mut child = dfq_command()
         .args(args)
         .stdin(Stdio::piped())
         .stdout(Stdio::piped())
@@ -157,6 +169,91 @@ fn duplicate_stage_name_selector_returns_eval_error() {
     assert!(stderr_text(&output).contains("stage selector \"dup\" is ambiguous"));
 }
 
+#[test]
+fn query_errors_show_caret_and_suggestions() {
+    let fixture = readme_fixture();
+
+    let parse_output = run(&["--file", fixture.path_str(), "STAGE[0]..NAME"]);
+    assert_eq!(parse_output.status.code(), Some(2));
+    let stderr = stderr_text(&parse_output);
+    assert!(stderr.contains("\n  STAGE[0]..NAME\n"));
+    assert!(stderr.contains("\n           ^"));
+
+    let typo_output = run(&["--file", fixture.path_str(), "STAGE[0].PARNET.RAW"]);
+    assert_eq!(typo_output.status.code(), Some(4));
+    assert!(stderr_text(&typo_output).contains("(did you mean 'PARENT'?)"));
+
+    let alias_output = run(&["--file", fixture.path_str(), "RUN[0].CMD"]);
+    assert_eq!(alias_output.status.code(), Some(4));
+    assert!(stderr_text(&alias_output).contains("(did you mean 'COMMAND'?)"));
+
+    let arg_output = run(&[
+        "--file",
+        fixture.path_str(),
+        "STAGE[\"builder\"].ARG.VERSON.DEFAULT",
+    ]);
+    assert_eq!(arg_output.status.code(), Some(4));
+    assert!(stderr_text(&arg_output).contains(": unset arg at segment 2"));
+}
+
+#[test]
+fn json_errors_use_an_envelope() {
+    let fixture = readme_fixture();
+
+    let not_found = run(&[
+        "--file",
+        fixture.path_str(),
+        "--json",
+        "STAGE[0].PARNET.RAW",
+    ]);
+    assert_eq!(not_found.status.code(), Some(4));
+    assert_eq!(stderr_text(&not_found), "");
+    let stdout = stdout_text(&not_found);
+    assert!(stdout.starts_with("{\"error\":{\"exit_code\":4,\"kind\":\"not_found\""));
+    assert!(stdout.contains("\"path\":\"STAGE[0].PARNET.RAW\""));
+    assert!(stdout.ends_with("\"query\":\"STAGE[0].PARNET.RAW\"}\n"));
+
+    let parse_error = run(&[
+        "--file",
+        fixture.path_str(),
+        "--json",
+        "--error-stream",
+        "stderr",
+        "ARG.",
+    ]);
+    assert_eq!(parse_error.status.code(), Some(2));
+    assert_eq!(stdout_text(&parse_error), "");
+    let stderr = stderr_text(&parse_error);
+    assert!(stderr.contains("\"kind\":\"query_parse\""));
+    assert!(stderr.contains("\"span\":{\"end\":5,\"start\":4}"));
+
+    let usage_error = run(&[
+        "--file",
+        fixture.path_str(),
+        "--json",
+        "--build-arg",
+        "=x",
+        "ARG",
+    ]);
+    assert_eq!(usage_error.status.code(), Some(64));
+    assert!(stdout_text(&usage_error).contains("\"kind\":\"usage\""));
+
+    let ignored_stream = run(&[
+        "--file",
+        fixture.path_str(),
+        "--format",
+        "yaml",
+        "--error-stream",
+        "stderr",
+        "ARG",
+    ]);
+    assert_eq!(ignored_stream.status.code(), Some(64));
+    assert!(
+        stderr_text(&ignored_stream)
+            .contains("--error-stream requires --json, --format json, or --format json-pretty")
+    );
+}
+
 #[test]
 fn strict_missing_var_returns_eval_error() {
     let fixture = Fixture::new("FROM alpine\n");
@@ -290,6 +387,342 @@ fn readme_common_examples_are_valid() {
     assert!(stderr_text(&strict_output).contains("missing variable 'NOPE'"));
 }
 
+#[test]
+fn readme_projection_examples_are_valid() {
+    let fixture = readme_fixture();
+
+    let object_output = run(&[
+        "--file",
+        fixture.path_str(),
+        "--json",
+        "STAGE[*].{name: NAME, base: PARENT.RESOLVED, runs: INSTRUCTIONS.COUNT}",
+    ]);
+    assert!(object_output.status.success());
+    assert!(stdout_text(&object_output).contains(
+        "\"value\":[{\"base\":\"alpine:0.5.13\",\"name\":\"builder\",\"runs\":3},{\"base\":\"builder\",\"name\":null,\"runs\":1}]"
+    ));
+
+    let array_output = run(&[
+        "--file",
+        fixture.path_str(),
+        "[ARG.VERSION, FROM[0].RESOLVED]",
+    ]);
+    assert!(array_output.status.success());
+    assert_eq!(stdout_text(&array_output), "0.5.13\nalpine:0.5.13\n");
+}
+
+#[test]
+fn readme_default_examples_are_valid() {
+    let fixture = readme_fixture();
+
+    let fallback_output = run(&[
+        "--file",
+        fixture.path_str(),
+        "STAGE[0].ARG.FOO // \"fallback\"",
+    ]);
+    assert!(fallback_output.status.success());
+    assert_eq!(stdout_text(&fallback_output), "fallback\n");
+
+    let grouped_output = run(&[
+        "--file",
+        fixture.path_str(),
+        "--json",
+        "STAGE[*].(NAME // \"unnamed\")",
+    ]);
+    assert!(grouped_output.status.success());
+    let grouped_stdout = stdout_text(&grouped_output);
+    assert!(grouped_stdout.contains("\"value\":[\"builder\",\"unnamed\"]"));
+    assert!(!grouped_stdout.contains("missing_paths"));
+
+    let exists_output = run(&[
+        "--file",
+        fixture.path_str(),
+        "EXISTS(STAGE[\"builder\"].ARG.VERSION)",
+    ]);
+    assert!(exists_output.status.success());
+    assert_eq!(stdout_text(&exists_output), "true\n");
+
+    let has_output = run(&["--file", fixture.path_str(), "ARG.HAS(\"NOPE\")"]);
+    assert!(has_output.status.success());
+    assert_eq!(stdout_text(&has_output), "false\n");
+}
+
+#[test]
+fn multiple_queries_share_one_invocation() {
+    let fixture = readme_fixture();
+
+    let text_output = run(&[
+        "--file",
+        fixture.path_str(),
+        "ARG.VERSION",
+        "-e",
+        "FROM[*].RESOLVED",
+    ]);
+    assert!(text_output.status.success());
+    assert_eq!(
+        stdout_text(&text_output),
+        "0.5.13\nalpine:0.5.13\nbuilder\n"
+    );
+
+    let query_file = Fixture::new("# release metadata\nversion = ARG.VERSION\n\nRUN.COUNT\n");
+    let json_output = run(&[
+        "--file",
+        fixture.path_str(),
+        "--query-file",
+        query_file.path_str(),
+        "--json",
+    ]);
+    assert!(json_output.status.success());
+    let json_stdout = stdout_text(&json_output);
+    assert!(json_stdout.starts_with("{\"RUN.COUNT\":{"));
+    assert!(json_stdout.contains(
+        "\"version\":{\"meta\":{\"scope\":\"global\"},\"query\":\"ARG.VERSION\",\"type\":\"string\",\"value\":\"0.5.13\"}"
+    ));
+
+    let duplicate_output = run(&["--file", fixture.path_str(), "ARG.VERSION", "ARG.VERSION"]);
+    assert_eq!(duplicate_output.status.code(), Some(64));
+    assert!(stderr_text(&duplicate_output).contains("duplicate query name 'ARG.VERSION'"));
+}
+
+#[test]
+fn shell_output_formats_render_objects() {
+    let fixture = Fixture::new(
+        "ARG VERSION=1.2.3\n\
+         ARG GREETING=\"it's here\"\n\
+         FROM alpine\n",
+    );
+
+    let export_output = run(&["--file", fixture.path_str(), "--format", "export", "ARG"]);
+    assert!(export_output.status.success());
+    assert_eq!(
+        stdout_text(&export_output),
+        "export GREETING='it'\\''s here'\nexport VERSION=1.2.3\n"
+    );
+
+    let resolved = Fixture::new(
+        "ARG VERSION=1\n\
+         ARG IMG=alpine:${VERSION}\n\
+         FROM ${IMG}\n\
+         ARG VERSION\n",
+    );
+    let output = run(&[
+        "--file",
+        resolved.path_str(),
+        "--build-arg",
+        "VERSION=2",
+        "--format",
+        "export",
+        "ARG",
+    ]);
+    assert!(output.status.success());
+    assert_eq!(
+        stdout_text(&output),
+        "export IMG=alpine:2\nexport VERSION=2\n"
+    );
+    let output = run(&[
+        "--file",
+        resolved.path_str(),
+        "--format",
+        "env",
+        "STAGE[0].ARG",
+    ]);
+    assert_eq!(stdout_text(&output), "VERSION=1\n");
+
+    let env_output = run(&[
+        "--file",
+        fixture.path_str(),
+        "--format",
+        "env",
+        "{version: ARG.VERSION}",
+    ]);
+    assert!(env_output.status.success());
+    assert_eq!(stdout_text(&env_output), "version=1.2.3\n");
+
+    let github_output = run(&[
+        "--file",
+        fixture.path_str(),
+        "--format",
+        "github",
+        "{version: ARG.VERSION, notes: RESOLVE(\"a\\nb\")}",
+    ]);
+    assert!(github_output.status.success());
+    assert_eq!(
+        stdout_text(&github_output),
+        "notes<<DFQ_EOF\na\nb\nDFQ_EOF\nversion=1.2.3\n"
+    );
+
+    let scalar_output = run(&[
+        "--file",
+        fixture.path_str(),
+        "--format",
+        "env",
+        "ARG.VERSION",
+    ]);
+    assert_eq!(scalar_output.status.code(), Some(64));
+    assert!(stderr_text(&scalar_output).contains("--format env requires an object result"));
+}
+
+#[test]
+fn structured_output_formats_render_results() {
+    let fixture = readme_fixture();
+
+    let yaml_output = run(&[
+        "--file",
+        fixture.path_str(),
+        "--format",
+        "yaml",
+        "STAGE[*].{name: NAME, base: PARENT.RESOLVED}",
+    ]);
+    assert!(yaml_output.status.success());
+    assert_eq!(
+        stdout_text(&yaml_output),
+        "---\n- base: \"alpine:0.5.13\"\n  name: builder\n- base: builder\n  name: ~\n"
+    );
+
+    let toml_output = run(&[
+        "--file",
+        fixture.path_str(),
+        "--format",
+        "toml",
+        "{version: ARG.VERSION, stages: STAGE[*].{name: NAME // \"final\"}}",
+    ]);
+    assert!(toml_output.status.success());
+    assert_eq!(
+        stdout_text(&toml_output),
+        "version = \"0.5.13\"\n\n[[stages]]\nname = \"builder\"\n\n[[stages]]\nname = \"final\"\n"
+    );
+
+    let toml_nulls = run(&[
+        "--file",
+        fixture.path_str(),
+        "--format",
+        "toml",
+        "{names: STAGE[*].NAME}",
+    ]);
+    assert_eq!(toml_nulls.status.code(), Some(64));
+    assert!(stderr_text(&toml_nulls).contains("--format toml requires arrays without null items"));
+
+    let pretty_output = run(&[
+        "--file",
+        fixture.path_str(),
+        "--format",
+        "json-pretty",
+        "ARG.VERSION",
+    ]);
+    assert!(pretty_output.status.success());
+    assert!(stdout_text(&pretty_output).contains("\n  \"query\": \"ARG.VERSION\",\n"));
+
+    let ndjson_output = run(&[
+        "--file",
+        fixture.path_str(),
+        "--format",
+        "ndjson",
+        "RUN[*].SPAN",
+    ]);
+    assert!(ndjson_output.status.success());
+    assert_eq!(
+        stdout_text(&ndjson_output),
+        "{\"end\":4,\"start\":4}\n{\"end\":5,\"start\":5}\n{\"end\":7,\"start\":7}\n"
+    );
+}
+
+#[test]
+fn tabular_output_formats_render_object_arrays() {
+    let fixture = readme_fixture();
+
+    let table_output = run(&[
+        "--file",
+        fixture.path_str(),
+        "--format",
+        "table",
+        "--columns",
+        "index,name,parent.resolved,instructions.count",
+        "STAGE",
+    ]);
+    assert!(table_output.status.success());
+    assert_eq!(
+        stdout_text(&table_output),
+        "index  name     parent.resolved  instructions.count\n\
+         0      builder  alpine:0.5.13    3\n\
+         1               builder          1\n"
+    );
+
+    let csv_output = run(&[
+        "--file",
+        fixture.path_str(),
+        "--format",
+        "csv",
+        "--columns",
+        "parent",
+        "--no-header",
+        "STAGE[1]",
+    ]);
+    assert!(csv_output.status.success());
+    assert_eq!(stdout_text(&csv_output), "stage,builder,builder,builder\n");
+
+    let tsv_output = run(&[
+        "--file",
+        fixture.path_str(),
+        "--format",
+        "tsv",
+        "RUN[*].{index: INDEX, command: COMMAND}",
+    ]);
+    assert!(tsv_output.status.success());
+    assert_eq!(
+        stdout_text(&tsv_output),
+        "command\tindex\napk add --no-cache curl\t0\necho \"build complete\"\t1\necho \"runtime\"\t2\n"
+    );
+
+    let misuse_output = run(&["--file", fixture.path_str(), "--no-header", "STAGE"]);
+    assert_eq!(misuse_output.status.code(), Some(64));
+    assert!(stderr_text(&misuse_output).contains("--columns and --no-header require"));
+}
+
+#[test]
+fn template_output_renders_each_item() {
+    let fixture = readme_fixture();
+
+    let inline_output = run(&[
+        "--file",
+        fixture.path_str(),
+        "--template",
+        "{{ .name }} -> {{ .parent.resolved }}{{ if not .name }}final{{ end }}",
+        "STAGE",
+    ]);
+    assert!(inline_output.status.success());
+    assert_eq!(
+        stdout_text(&inline_output),
+        "builder -> alpine:0.5.13\n -> builderfinal\n"
+    );
+
+    let template_file = Fixture::new(
+        "Release {{ .version }}\n\
+         {{- range .bases }}\n- {{ . }}{{ end }}\n",
+    );
+    let file_output = run(&[
+        "--file",
+        fixture.path_str(),
+        "--template-file",
+        template_file.path_str(),
+        "{version: ARG.VERSION, bases: FROM}",
+    ]);
+    assert!(file_output.status.success());
+    assert_eq!(
+        stdout_text(&file_output),
+        "Release 0.5.13\n- alpine:0.5.13\n- builder\n"
+    );
+
+    let bad_output = run(&[
+        "--file",
+        fixture.path_str(),
+        "--template",
+        "{{ if .name }}",
+        "STAGE",
+    ]);
+    assert_eq!(bad_output.status.code(), Some(64));
+    assert!(stderr_text(&bad_output).contains("template error: if is missing {{ end }}"));
+}
+
 #[test]
 fn readme_run_examples_are_valid() {
     let fixture = readme_fixture();
@@ -439,6 +872,45 @@ fn readme_exit_codes_are_observable() {
     assert_eq!(usage_output.status.code(), Some(64));
 }
 
+#[test]
+fn interactive_session_reuses_one_parse() {
+    let fixture = readme_fixture();
+
+    let output = run_with_stdin(
+        &["--file", fixture.path_str(), "-i"],
+        "FROM[0].RESOLVED\n\
+         :set build-arg VERSION=1.0.0\n\
+         FROM[0].RESOLVED\n\
+         :json\n\
+         ARG.VERSION\n\
+         :json off\n\
+         :set build-arg VERSION=hello  world\n\
+         ARG.VERSION\n\
+         STAGE[0].PARNET.RAW\n\
+         :bogus\n\
+         :quit\n\
+         ARG.VERSION\n",
+    );
+    assert!(output.status.success());
+    assert_eq!(
+        stdout_text(&output),
+        "alpine:0.5.13\n\
+         alpine:1.0.0\n\
+         {\"meta\":{\"scope\":\"global\"},\"query\":\"ARG.VERSION\",\"type\":\"string\",\"value\":\"1.0.0\"}\n\
+         hello  world\n"
+    );
+    let stderr = stderr_text(&output);
+    assert!(stderr.contains("(did you mean 'PARENT'?)"));
+    assert!(stderr.contains("unknown command ':bogus'"));
+
+    let repl_output = run_with_stdin(&["--file", fixture.path_str(), "repl"], "ARG.VERSION\n");
+    assert_eq!(stdout_text(&repl_output), "0.5.13\n");
+
+    let misuse_output = run(&["--file", fixture.path_str(), "-i", "ARG.VERSION"]);
+    assert_eq!(misuse_output.status.code(), Some(64));
+    assert!(stderr_text(&misuse_output).contains("--interactive does not take queries"));
+}
+
 #[test]
 fn bash_completion_is_emitted() {
     let output = run(&["completion", "bash"]);
@@ -457,6 +929,9 @@ fn zsh_completion_is_emitted() {
     let stdout = stdout_text(&output);
     assert!(stdout.contains("#compdef dfq"));
     assert!(stdout.contains("_dfq \"$@\""));
+    assert!(stdout.contains("'::queries -- Query expression:_dfq_queries'"));
+    assert!(stdout.contains("'*--expr=[Add a query expression]:QUERY:_dfq_queries'"));
+    assert!(!stdout.contains(":QUERY:_default'"));
 }
 
 #[test]
@@ -469,6 +944,33 @@ fn fish_completion_is_emitted() {
     assert!(stdout.contains("__fish_dfq_needs_command"));
 }
 
+#[test]
+fn dynamic_completion_offers_dockerfile_names() {
+    let fixture = readme_fixture();
+    let line = |rest: &str| format!("dfq --file {} {rest}", fixture.path_str());
+
+    let namespaces = run(&["complete-query", &line("ST")]);
+    assert!(namespaces.status.success());
+    assert_eq!(stdout_text(&namespaces), "STAGE\n");
+
+    let stage_names = run(&["complete-query", &line("'STAGE[\"b")]);
+    assert_eq!(stdout_text(&stage_names), "STAGE[\"builder\"]\n");
+
+    let bash_word = run(&["complete-query", &line("'STAGE[\"b"), "b"]);
+    assert_eq!(stdout_text(&bash_word), "builder\"]\n");
+
+    let arg_names = run(&["complete-query", &line("-e ARG.")]);
+    assert_eq!(stdout_text(&arg_names), "ARG.VERSION\n");
+
+    let option_value = run(&["complete-query", &line("--format ")]);
+    assert_eq!(stdout_text(&option_value), "");
+
+    for shell in ["bash", "zsh", "fish"] {
+        let script = run(&["completion", shell]);
+        assert!(stdout_text(&script).contains("dfq complete-query"));
+    }
+}
+
 #[test]
 fn help_contains_detailed_flag_and_query_docs() {
     let output = run(&["--help"]);
@@ -482,3 +984,701 @@ fn help_contains_detailed_flag_and_query_docs() {
     assert!(stdout.contains("RUN[*].GREP(\"apt-get\")"));
     assert!(stdout.contains("Query expression to evaluate"));
 }
+
+#[test]
+fn watch_mode_reprints_when_results_change() {
+    let fixture = Fixture::new("ARG VERSION=1\nFROM alpine:${VERSION}\n");
+    let mut child = KillOnDrop(
+        dfq_command()
+            .args([
+                "--file",
+                fixture.path_str(),
+                "--watch",
+                "--watch-interval",
+                "20",
+                "--changed-only",
+                "--on-change",
+                "echo changed",
+                "FROM[0].RESOLVED",
+            ])
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .expect("command should spawn"),
+    );
+
+    let stdout = child.0.stdout.take().expect("stdout should be piped");
+    let (sender, receiver) = std::sync::mpsc::channel();
+    std::thread::spawn(move || {
+        for line in BufReader::new(stdout).lines() {
+            if sender.send(line).is_err() {
+                break;
+            }
+        }
+    });
+    let next_line = || {
+        receiver
+            .recv_timeout(Duration::from_secs(10))
+            .expect("watch should print a line")
+            .expect("stdout should be utf-8")
+    };
+
+    // The first result is printed before the file is touched. Whether or not
+    // the watcher observes the comment-only rewrite, --changed-only must keep
+    // it from printing, so the next line is the VERSION=2 result.
+    assert_eq!(next_line(), "alpine:1");
+    replace_file(
+        &fixture.path,
+        "# comment\nARG VERSION=1\nFROM alpine:${VERSION}\n",
+    );
+    replace_file(&fixture.path, "ARG VERSION=2\nFROM alpine:${VERSION}\n");
+    assert_eq!(next_line(), "alpine:2");
+    assert_eq!(next_line(), "changed");
+}
+
+/// Swaps in new contents with a rename so a polling reader never sees a
+/// half-written file.
+fn replace_file(path: &Path, contents: &str) {
+    let staged = unique_path("staged");
+    fs::write(&staged, contents).expect("staged write should succeed");
+    fs::rename(&staged, path).expect("rename should succeed");
+}
+
+/// Kills a spawned child when a test ends, including when an assertion fails.
+struct KillOnDrop(Child);
+
+impl Drop for KillOnDrop {
+    fn drop(&mut self) {
+        let _ = self.0.kill();
+        let _ = self.0.wait();
+    }
+}
+
+#[test]
+fn watch_only_flags_require_watch() {
+    let fixture = readme_fixture();
+    let output = run(&[
+        "--file",
+        fixture.path_str(),
+        "--changed-only",
+        "ARG.VERSION",
+    ]);
+    assert_eq!(output.status.code(), Some(64));
+    assert!(stderr_text(&output).contains("--changed-only and --on-change require --watch"));
+}
+
+#[test]
+fn build_args_are_read_from_the_environment_and_env_files() {
+    let fixture = Fixture::new(
+        "ARG VERSION=0.5.13\n\
+         ARG REGISTRY=docker.io\n\
+         ARG DISTRO=alpine\n\
+         FROM ${REGISTRY}/${DISTRO}:${VERSION}\n",
+    );
+    let env_file = Fixture::new(
+        "# registry settings\n\
+         export REGISTRY=\"ghcr.io\" # comment\n\
+         DISTRO='debian#slim'\n",
+    );
+    let dfq = || {
+        let mut command = dfq_command();
+        command
+            .env("VERSION", "2.0")
+            .env("DFQ_BUILD_ARG_DISTRO", "ubuntu")
+            .env("DFQ_BUILD_ARG_REGISTRY", "quay.io");
+        command
+    };
+
+    let output = dfq()
+        .args(["--file", fixture.path_str(), "--build-arg", "VERSION"])
+        .arg("FROM[0].RESOLVED")
+        .output()
+        .expect("command should run");
+    assert!(output.status.success());
+    assert_eq!(stdout_text(&output), "quay.io/ubuntu:2.0\n");
+
+    let output = dfq()
+        .args(["--file", fixture.path_str()])
+        .args(["--build-arg-file", env_file.path_str()])
+        .args(["--build-arg", "REGISTRY=localhost", "FROM[0].RESOLVED"])
+        .output()
+        .expect("command should run");
+    assert!(output.status.success());
+    assert_eq!(stdout_text(&output), "localhost/debian#slim:0.5.13\n");
+
+    let output = dfq()
+        .args(["--file", fixture.path_str(), "--no-build-arg-env"])
+        .args(["--build-arg", "VERSION", "FROM[0].RESOLVED"])
+        .output()
+        .expect("command should run");
+    assert!(output.status.success());
+    assert_eq!(stdout_text(&output), "docker.io/alpine:\n");
+
+    let output = dfq()
+        .env_remove("VERSION")
+        .args(["--file", fixture.path_str(), "--build-arg", "VERSION"])
+        .arg("ARG.VERSION")
+        .output()
+        .expect("command should run");
+    assert_eq!(stdout_text(&output), "0.5.13\n");
+}
+
+#[test]
+fn malformed_build_arg_files_report_the_line() {
+    let fixture = readme_fixture();
+    let env_file = Fixture::new("VERSION=1\nBROKEN=\"unterminated\n");
+    let output = run(&[
+        "--file",
+        fixture.path_str(),
+        "--build-arg-file",
+        env_file.path_str(),
+        "ARG.VERSION",
+    ]);
+    assert_eq!(output.status.code(), Some(64));
+    assert!(stderr_text(&output).contains(":2: unterminated quoted value"));
+}
+
+#[test]
+fn bake_file_supplies_dockerfile_args_and_platform() {
+    let dockerfile = Fixture::new(
+        "ARG VERSION=0.5.13\n\
+         FROM alpine:${VERSION} AS build\n\
+         ARG TARGETARCH\n\
+         FROM scratch AS runtime\n",
+    );
+    let bake = Fixture::new(&format!(
+        "variable \"VERSION\" {{\n  default = \"1.2.3\"\n}}\n\
+         target \"app\" {{\n  dockerfile = \"{}\"\n  target = \"build\"\n  \
+         platforms = [\"linux/arm64\"]\n  args = {{\n    VERSION = \"v${{VERSION}}\"\n  }}\n}}\n",
+        dockerfile.path_str()
+    ));
+
+    let output = run(&[
+        "--bake",
+        bake.path_str(),
+        "--bake-target",
+        "app",
+        "FROM[0].RESOLVED",
+        "STAGE[\"build\"].ARG.TARGETARCH",
+    ]);
+    assert!(output.status.success(), "{}", stderr_text(&output));
+    assert_eq!(stdout_text(&output), "alpine:v1.2.3\narm64\n");
+
+    let output = run(&[
+        "--bake",
+        bake.path_str(),
+        "--bake-target",
+        "app",
+        "--build-arg",
+        "VERSION=edge",
+        "FROM[0].RESOLVED",
+    ]);
+    assert_eq!(stdout_text(&output), "alpine:edge\n");
+
+    let output = run(&["--bake", bake.path_str(), "ARG.VERSION"]);
+    assert_eq!(output.status.code(), Some(64));
+    assert!(stderr_text(&output).contains("unknown bake target 'default' (available: app)"));
+
+    let output = run(&["--bake", bake.path_str(), "--file", "Dockerfile", "ARG"]);
+    assert_eq!(output.status.code(), Some(64));
+    assert!(stderr_text(&output).contains("--bake is mutually exclusive with --file"));
+}
+
+#[test]
+fn compose_build_section_configures_evaluation() {
+    let dir = unique_path("compose");
+    fs::create_dir_all(dir.join("api")).expect("project dir should be created");
+    fs::write(
+        dir.join("api/Dockerfile"),
+        "ARG VERSION=0.1\n\
+         FROM alpine:${VERSION} AS build\n\
+         FROM build AS runtime\n\
+         ARG CHANNEL\n",
+    )
+    .expect("dockerfile write should succeed");
+    fs::write(dir.join(".env"), "VERSION=3.2\n").expect("env write should succeed");
+    fs::write(
+        dir.join("compose.yaml"),
+        "services:\n  api:\n    build:\n      context: api\n      target: build\n      \
+         args:\n        - VERSION=${VERSION}\n        - CHANNEL=${CHANNEL:-stable}\n",
+    )
+    .expect("compose write should succeed");
+    let compose = dir.join("compose.yaml");
+    let compose = compose.to_str().expect("path should be utf-8");
+
+    let output = run(&["--compose", compose, "FROM[0].RESOLVED"]);
+    assert!(output.status.success(), "{}", stderr_text(&output));
+    assert_eq!(stdout_text(&output), "alpine:3.2\n");
+
+    let output = dfq_command()
+        .env("CHANNEL", "beta")
+        .args(["--compose", compose, "--service", "api"])
+        .arg("STAGE[\"runtime\"].ARG.CHANNEL")
+        .output()
+        .expect("command should run");
+    assert_eq!(stdout_text(&output), "beta\n");
+
+    let output = run(&["--compose", compose, "--service", "web", "ARG"]);
+    assert_eq!(output.status.code(), Some(64));
+    assert!(stderr_text(&output).contains("unknown compose service 'web' (available: api)"));
+
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn context_flags_copy_sources_missing_from_the_build_context() {
+    let dir = unique_path("context");
+    fs::create_dir_all(dir.join("src")).expect("context dir should be created");
+    fs::create_dir_all(dir.join("node_modules/pkg")).expect("context dir should be created");
+    fs::write(
+        dir.join("Dockerfile"),
+        "FROM node:20\n\
+         COPY package.json yarn.lock ./\n\
+         COPY src/ /app/src/\n\
+         COPY node_modules /app/node_modules\n",
+    )
+    .expect("dockerfile write should succeed");
+    fs::write(dir.join("package.json"), "{}\n").expect("context write should succeed");
+    fs::write(dir.join("src/index.js"), "\n").expect("context write should succeed");
+    fs::write(dir.join("node_modules/pkg/index.js"), "\n").expect("context write should succeed");
+    fs::write(dir.join(".dockerignore"), "node_modules\n").expect("ignore write should succeed");
+    let context = dir.to_str().expect("path should be utf-8");
+    let dockerfile = dir.join("Dockerfile");
+    let dockerfile = dockerfile.to_str().expect("path should be utf-8");
+
+    let output = run(&["-f", dockerfile, "--context", context, "COPY.MISSING"]);
+    assert!(output.status.success(), "{}", stderr_text(&output));
+    assert_eq!(stdout_text(&output), "yarn.lock\nnode_modules\n");
+
+    let output = run(&[
+        "-f",
+        dockerfile,
+        "--context",
+        context,
+        "--json",
+        "COPY[*].SOURCES_MATCHED",
+    ]);
+    assert!(output.status.success(), "{}", stderr_text(&output));
+    assert!(
+        stdout_text(&output).contains(r#""value":[["package.json"],["src/index.js"],[]]"#),
+        "{}",
+        stdout_text(&output)
+    );
+
+    let output = run(&["-f", dockerfile, "--context", context, "CONTEXT.FILES"]);
+    assert_eq!(
+        stdout_text(&output),
+        ".dockerignore\nDockerfile\npackage.json\nsrc/index.js\n"
+    );
+
+    let output = run(&["-f", dockerfile, "COPY[0].MISSING"]);
+    assert!(!output.status.success());
+    assert!(stderr_text(&output).contains("MISSING requires a build context"));
+
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn dockerfile_specific_ignore_file_takes_precedence() {
+    let dir = unique_path("dockerignore");
+    fs::create_dir_all(dir.join("docs")).expect("context dir should be created");
+    fs::write(dir.join("app.Dockerfile"), "FROM alpine\nCOPY docs /docs\n")
+        .expect("dockerfile write should succeed");
+    fs::write(dir.join("docs/guide.md"), "\n").expect("context write should succeed");
+    fs::write(dir.join("docs/draft.md"), "\n").expect("context write should succeed");
+    fs::write(dir.join(".dockerignore"), "docs\n").expect("ignore write should succeed");
+    fs::write(
+        dir.join("app.Dockerfile.dockerignore"),
+        "*.Dockerfile*\ndocs/draft.md\n",
+    )
+    .expect("ignore write should succeed");
+    let context = dir.to_str().expect("path should be utf-8");
+    let dockerfile = dir.join("app.Dockerfile");
+    let dockerfile = dockerfile.to_str().expect("path should be utf-8");
+
+    let output = run(&[
+        "-f",
+        dockerfile,
+        "--context",
+        context,
+        "COPY.SOURCES_MATCHED",
+        r#"IGNORED("docs/draft.md")"#,
+        r#"IGNORED("docs/guide.md")"#,
+    ]);
+    assert!(output.status.success(), "{}", stderr_text(&output));
+    assert_eq!(stdout_text(&output), "docs/guide.md\ntrue\nfalse\n");
+
+    fs::write(dir.join("app.Dockerfile.dockerignore"), "docs\n!\n")
+        .expect("ignore write should succeed");
+    let output = run(&["-f", dockerfile, "--context", context, "CONTEXT.FILES"]);
+    assert_eq!(output.status.code(), Some(64));
+    assert!(
+        stderr_text(&output)
+            .contains("app.Dockerfile.dockerignore: line 2: illegal exclusion pattern"),
+        "{}",
+        stderr_text(&output)
+    );
+
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn lint_reports_findings_with_rule_ids_and_lines() {
+    let fixture = Fixture::new(
+        "ARG UNUSED\n\
+         FROM ubuntu AS build\n\
+         RUN apt-get update && apt-get install -y curl\n\
+         FROM alpine:3.20\n\
+         HEALTHCHECK CMD true\n\
+         USER root\n",
+    );
+
+    let output = run(&["lint", fixture.path_str()]);
+    assert_eq!(output.status.code(), Some(1), "{}", stderr_text(&output));
+    let path = fixture.path_str();
+    assert_eq!(
+        stdout_text(&output),
+        format!(
+            "{path}:2: warning[DFQ001]: base image ubuntu has no tag; pin a version or digest\n\
+             {path}:3: warning[DFQ002]: apt-get install without --no-install-recommends\n\
+             {path}:3: warning[DFQ003]: apt-get install without rm -rf /var/lib/apt/lists/* in the same RUN\n\
+             {path}:6: warning[DFQ005]: the final stage runs as root; switch to an unprivileged USER\n"
+        )
+    );
+
+    let output = run(&["lint", "--severity", "info", fixture.path_str()]);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(stdout_text(&output).starts_with(&format!(
+        "{path}:1: info[DFQ009]: ARG UNUSED is never used by a FROM or redeclared in a stage\n"
+    )));
+
+    let output = run(&["lint", "--severity", "error", "--json", fixture.path_str()]);
+    assert!(output.status.success(), "{}", stderr_text(&output));
+    assert_eq!(stdout_text(&output), "[]\n");
+
+    let output = run(&[
+        "-f",
+        fixture.path_str(),
+        "--build-arg",
+        "X=1",
+        "lint",
+        "--json",
+    ]);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(stdout_text(&output).contains(
+        r#"{"message":"base image ubuntu has no tag; pin a version or digest","name":"unpinned-base-image","path":"#
+    ));
+    assert!(
+        stdout_text(&output)
+            .contains(r#""rule":"DFQ001","severity":"warning","span":{"end":2,"start":2}}"#)
+    );
+
+    let output = run(&["lint", "--list-rules"]);
+    assert!(output.status.success());
+    assert_eq!(stdout_text(&output).lines().count(), 10);
+}
+
+#[test]
+fn lint_passes_info_findings_by_default_and_counts_run_args_as_used() {
+    let fixture = Fixture::new(
+        "FROM debian:12\n\
+         ARG DEBIAN_FRONTEND=noninteractive\n\
+         RUN apt-get install -y --no-install-recommends curl && rm -rf /var/lib/apt/lists/*\n\
+         USER app\n",
+    );
+    let path = fixture.path_str();
+
+    let output = run(&["lint", path]);
+    assert!(output.status.success(), "{}", stdout_text(&output));
+    assert_eq!(stdout_text(&output), "");
+
+    let output = run(&["lint", "--severity", "info", path]);
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(
+        stdout_text(&output),
+        format!("{path}:1: info[DFQ006]: the final stage has no HEALTHCHECK\n")
+    );
+}
+
+#[test]
+fn check_evaluates_policy_rules_and_exits_with_policy_code() {
+    let fixture = Fixture::new(
+        "ARG VERSION=3.19\n\
+         FROM registry.internal/base/alpine:${VERSION} AS build\n\
+         RUN apk add curl\n\
+         FROM alpine:latest\n",
+    );
+    let policy = Fixture::new(
+        "[[rule]]\n\
+         id = \"internal-registry\"\n\
+         description = \"Base images must come from registry.internal\"\n\
+         query = \"FROM[*].REGISTRY\"\n\
+         all = { equals = \"registry.internal\" }\n\
+         \n\
+         [[rule]]\n\
+         id = \"version\"\n\
+         query = \"ARG.VERSION\"\n\
+         matches = '^\\d+\\.\\d+$'\n\
+         \n\
+         [[rule]]\n\
+         id = \"no-latest\"\n\
+         query = \"FROM[*].RESOLVED\"\n\
+         severity = \"warning\"\n\
+         all.not_contains = \":latest\"\n",
+    );
+
+    let output = run(&["check", "--policy", policy.path_str(), fixture.path_str()]);
+    assert_eq!(output.status.code(), Some(7), "{}", stderr_text(&output));
+    let path = fixture.path_str();
+    assert_eq!(
+        stdout_text(&output),
+        format!(
+            "{path}: error[internal-registry]: Base images must come from registry.internal: item 1: expected equals \"registry.internal\", got \"docker.io\"\n\
+             {path}: warning[no-latest]: item 1: expected not_contains \":latest\", got \"alpine:latest\"\n\
+             1 passed, 2 failed\n"
+        )
+    );
+
+    let output = run(&[
+        "-f",
+        fixture.path_str(),
+        "--build-arg",
+        "VERSION=edge",
+        "check",
+        "--policy",
+        policy.path_str(),
+        "--json",
+    ]);
+    assert_eq!(output.status.code(), Some(7));
+    assert!(
+        stdout_text(&output).contains(
+            r#""message":"expected matches /^\\d+\\.\\d+$/, got \"edge\"","passed":false"#
+        )
+    );
+
+    let lenient = Fixture::new("[[rule]]\nid = \"stages\"\nquery = \"STAGE\"\ncount = \">= 2\"\n");
+    let output = run(&["check", "--policy", lenient.path_str(), fixture.path_str()]);
+    assert!(output.status.success(), "{}", stderr_text(&output));
+    assert_eq!(stdout_text(&output), "1 passed, 0 failed\n");
+
+    let invalid = Fixture::new("[[rule]]\nid = \"x\"\nquery = \"ARG\"\nequal = 1\n");
+    let output = run(&["check", "--policy", invalid.path_str(), fixture.path_str()]);
+    assert_eq!(output.status.code(), Some(64));
+    assert!(stderr_text(&output).contains("rule 1: 'x': unknown key 'equal'"));
+}
+
+#[test]
+fn lint_honours_suppression_comments_and_baselines() {
+    let fixture = Fixture::new(
+        "# dfq:disable DFQ006\n\
+         # dfq:ignore DFQ001\n\
+         FROM ubuntu\n\
+         # dfq:ignore-next-line\n\
+         RUN cd /src\n\
+         RUN cd /x\n",
+    );
+    let path = fixture.path_str();
+
+    let output = run(&["lint", "--severity", "info", path]);
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(
+        stdout_text(&output),
+        format!("{path}:6: info[DFQ008]: use WORKDIR instead of cd in RUN\n")
+    );
+
+    let baseline = unique_path("baseline");
+    let baseline = baseline.to_str().expect("path should be utf-8");
+    let output = run(&[
+        "lint",
+        "--severity",
+        "info",
+        "--write-baseline",
+        baseline,
+        path,
+    ]);
+    assert!(output.status.success(), "{}", stderr_text(&output));
+    assert_eq!(
+        stderr_text(&output),
+        format!("wrote 1 findings to {baseline}\n")
+    );
+
+    fs::write(
+        &fixture.path,
+        "# dfq:disable DFQ006\nFROM ubuntu:24.04\nRUN cd /x\nRUN cd /y\n",
+    )
+    .expect("write should succeed");
+    let output = run(&["lint", "--severity", "info", "--baseline", baseline, path]);
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(
+        stdout_text(&output),
+        format!("{path}:4: info[DFQ008]: use WORKDIR instead of cd in RUN\n")
+    );
+    let _ = fs::remove_file(baseline);
+}
+
+#[test]
+fn lint_and_check_emit_sarif_and_junit_reports() {
+    let fixture = Fixture::new("FROM alpine:3.20\nHEALTHCHECK CMD true\nCMD a\nCMD b\n");
+    let path = fixture.path_str();
+
+    let output = run(&["lint", "--format", "sarif", path]);
+    assert_eq!(output.status.code(), Some(1), "{}", stderr_text(&output));
+    let sarif = stdout_text(&output);
+    assert!(sarif.contains("\"version\": \"2.1.0\""));
+    assert!(sarif.contains("\"ruleId\": \"DFQ007\""));
+    assert!(sarif.contains("\"endLine\": 3,\n                  \"startLine\": 3"));
+    assert!(sarif.contains("\"name\": \"multiple-cmd\""));
+
+    let output = run(&["lint", "--format", "junit", "--severity", "warning", path]);
+    assert_eq!(output.status.code(), Some(1));
+    let junit = stdout_text(&output);
+    assert!(junit.starts_with("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<testsuites name=\"dfq lint\" tests=\"7\" failures=\"1\" errors=\"0\">\n"));
+    assert!(junit.contains(&format!(
+        "<testcase name=\"DFQ007 multiple-cmd\" classname=\"{path}\">\n      <failure message=\"CMD is overridden by the CMD on line 4\" type=\"warning\">{path}:3: CMD is overridden by the CMD on line 4</failure>"
+    )));
+    assert!(junit.contains(&format!(
+        "<testcase name=\"DFQ001 unpinned-base-image\" classname=\"{path}\"/>"
+    )));
+
+    let policy = Fixture::new(
+        "[[rule]]\nid = \"cmds\"\nquery = \"STAGE[0].INSTRUCTIONS.COUNT\"\nnumber = 1\n\n[[rule]]\nid = \"no-cmd\"\nquery = \"INSTRUCTION[*].KEYWORD\"\nall.not_contains = \"CMD\"\n",
+    );
+    let output = run(&[
+        "check",
+        "--policy",
+        policy.path_str(),
+        "--format",
+        "sarif",
+        path,
+    ]);
+    assert_eq!(output.status.code(), Some(7));
+    let sarif = stdout_text(&output);
+    assert!(sarif.contains("\"level\": \"error\""));
+    assert!(sarif.contains("\"endLine\": 3,\n                  \"startLine\": 3"));
+
+    let output = run(&["--format", "junit", "-f", path, "ARG"]);
+    assert_eq!(output.status.code(), Some(64));
+    assert!(stderr_text(&output).contains("only supported by lint and check"));
+}
+
+#[test]
+fn queries_comments_directives_and_instruction_comments() {
+    let fixture = Fixture::new(
+        "# syntax=docker/dockerfile:1.7\n\
+         FROM alpine:3.20\n\
+         \n\
+         # install sudo as root\n\
+         RUN apk add --update sudo\n",
+    );
+    let path = fixture.path_str();
+
+    let output = run(&["-f", path, "DIRECTIVES.SYNTAX"]);
+    assert!(output.status.success());
+    assert_eq!(stdout_text(&output), "docker/dockerfile:1.7\n");
+
+    let output = run(&["-f", path, "INSTRUCTION[1].COMMENT"]);
+    assert_eq!(stdout_text(&output), "install sudo as root\n");
+
+    let output = run(&[
+        "--format",
+        "ndjson",
+        "-f",
+        path,
+        "COMMENTS[*].{text: TEXT, line: LINE, before: INSTRUCTION}",
+    ]);
+    assert_eq!(
+        stdout_text(&output),
+        "{\"before\":\"RUN apk add --update sudo\",\"line\":4,\"text\":\"install sudo as root\"}\n"
+    );
+
+    let output = run(&["-f", path, "DIRECTIVES.SINTAX"]);
+    assert_eq!(output.status.code(), Some(4));
+    assert!(stderr_text(&output).contains("did you mean 'SYNTAX'?"));
+}
+
+#[test]
+fn packages_report_installs_with_version_pins() {
+    let fixture = Fixture::new(
+        "FROM alpine:3.20 AS build\n\
+         RUN apk add --no-cache curl=7.88.1-r1 \\\n    git\n\
+         FROM python:3.12\n\
+         RUN pip install --no-cache-dir pkg==1.2 && npm install -g pnpm@9.1.0\n",
+    );
+    let path = fixture.path_str();
+
+    let output = run(&["--format", "ndjson", "-f", path, "PACKAGES"]);
+    assert!(output.status.success());
+    assert_eq!(
+        stdout_text(&output),
+        "{\"manager\":\"apk\",\"name\":\"curl\",\"run_index\":0,\"stage\":0,\"version\":\"7.88.1-r1\"}\n\
+         {\"manager\":\"apk\",\"name\":\"git\",\"run_index\":0,\"stage\":0,\"version\":null}\n\
+         {\"manager\":\"pip\",\"name\":\"pkg\",\"run_index\":1,\"stage\":1,\"version\":\"1.2\"}\n\
+         {\"manager\":\"npm\",\"name\":\"pnpm\",\"run_index\":1,\"stage\":1,\"version\":\"9.1.0\"}\n"
+    );
+
+    let output = run(&["-f", path, "PACKAGES[3].MANAGER"]);
+    assert_eq!(stdout_text(&output), "npm\n");
+
+    let output = run(&["-f", path, "PACKAGES[4]"]);
+    assert_eq!(output.status.code(), Some(4));
+}
+
+#[test]
+fn recursive_scan_reports_results_per_file() {
+    let dir = unique_path("scan");
+    fs::create_dir_all(dir.join("api")).expect("scan dir should be created");
+    fs::create_dir_all(dir.join("jobs/nightly")).expect("scan dir should be created");
+    fs::create_dir_all(dir.join(".git")).expect("scan dir should be created");
+    fs::write(dir.join("api/Dockerfile"), "FROM alpine:3.20\n").expect("write should succeed");
+    fs::write(
+        dir.join("jobs/nightly/batch.Dockerfile"),
+        "ARG PY=3.12\nFROM python:${PY} AS build\nFROM scratch\n",
+    )
+    .expect("write should succeed");
+    fs::write(dir.join("jobs/Dockerfile.dockerignore"), "*.tmp\n").expect("write should succeed");
+    fs::write(dir.join(".git/Dockerfile"), "FROM ignored\n").expect("write should succeed");
+    let root = dir.to_str().expect("path should be utf-8");
+
+    let output = run(&["-r", root, "FROM[*].RESOLVED"]);
+    assert!(output.status.success(), "{}", stderr_text(&output));
+    assert_eq!(
+        stdout_text(&output),
+        format!(
+            "{root}/api/Dockerfile:alpine:3.20\n\
+             {root}/jobs/nightly/batch.Dockerfile:python:3.12\n\
+             {root}/jobs/nightly/batch.Dockerfile:scratch\n"
+        )
+    );
+
+    let pattern = format!("{root}/**/*.Dockerfile");
+    let output = run(&[
+        "-f",
+        &pattern,
+        "-f",
+        &format!("{root}/api"),
+        "--json",
+        "ARG.PY",
+    ]);
+    assert_eq!(output.status.code(), Some(4));
+    let stdout = stdout_text(&output);
+    assert!(stdout.starts_with(&format!(
+        "{{\"{root}/api/Dockerfile\":{{\"error\":{{\"exit_code\":4,\"kind\":\"not_found\""
+    )));
+    assert!(stdout.contains(&format!(
+        "\"{root}/jobs/nightly/batch.Dockerfile\":{{\"meta\":{{\"scope\":\"global\"}},\"query\":\"ARG.PY\",\"type\":\"string\",\"value\":\"3.12\"}}"
+    )));
+
+    let rooted = format!("/[{}]{}/api/Dockerfile", &root[1..2], &root[2..]);
+    let output = run(&["-f", &rooted, "FROM[0]"]);
+    assert!(output.status.success(), "{}", stderr_text(&output));
+    assert_eq!(
+        stdout_text(&output),
+        format!("{root}/api/Dockerfile:alpine:3.20\n")
+    );
+
+    let output = run(&["-r", root, "--watch", "ARG"]);
+    assert_eq!(output.status.code(), Some(64));
+    assert!(stderr_text(&output).contains("--watch requires a single Dockerfile"));
+
+    let _ = fs::remove_dir_all(&dir);
+}